@@ -13,6 +13,10 @@
     EvaluateSelectionAsCommand,
     EvaluateClipboardAsCommand,
     EvaluateSelectionAsLookObject,
+    /// Use the named register for the next copy, cut or paste.
+    SelectRegister(char),
+    /// Execute a command string. Used for keybinds added at runtime with `add_keybind`.
+    EvaluateCommand(String),
 }
 impl EditorAction{
     fn action_name(&self) -> String{
@@ -30,7 +34,9 @@ impl EditorAction{
             EditorAction::ToggleStatusBar => "toggle status bar",
             EditorAction::EvaluateSelectionAsCommand => "evaluate selection as command",
             EditorAction::EvaluateClipboardAsCommand => "evaluate clipboard as command",
-            EditorAction::EvaluateSelectionAsLookObject => "look"
+            EditorAction::EvaluateSelectionAsLookObject => "look",
+            EditorAction::SelectRegister(_) => "select register",
+            EditorAction::EvaluateCommand(command) => command,
         };
         name.to_string()
     }
@@ -73,6 +79,12 @@ impl EditorAction{
     Surround,         //this would not benefit from using a count. use existing selection primitives to select text to surround
     SurroundingPair,  //TODO: this may benefit from using a count. would select the 'count'th surrounding pair
     FlipDirection,
+    /// Moves the primary selection to the next match of the search register's regex, wrapping around at buffer end.
+    SelectNextMatch,
+    /// Moves the primary selection to the previous match of the search register's regex, wrapping around at buffer start.
+    SelectPreviousMatch,
+    /// Adds the next match of the search register's regex, after the primary selection, as the new primary selection.
+    AddNextMatch,
         //TODO: SplitSelectionLines,    //split current selection into a selection for each line. error if single line
 }
 impl SelectionAction{
@@ -115,6 +127,9 @@ impl SelectionAction{
             SelectionAction::SelectLine => "select line",
             SelectionAction::Surround => "surround",
             SelectionAction::SurroundingPair => "select nearest surrounding bracket pair",
+            SelectionAction::SelectNextMatch => "select next match",
+            SelectionAction::SelectPreviousMatch => "select previous match",
+            SelectionAction::AddNextMatch => "add next match",
         };
         name.to_string()
     }
@@ -144,7 +159,16 @@ impl EditAction{
             EditAction::AddSurround('{', '}') => "add surrounding curly braces",
             EditAction::AddSurround('(', ')') => "add surrounding parens",
             EditAction::AddSurround('<', '>') => "add surrounding angle braces",
-            _ => "unimplemented"
+            EditAction::AddSurround(_, _) => "add surrounding pair",
+            EditAction::InsertChar(c) => &format!("insert char {}", c),
+            EditAction::InsertNewline => "insert newline",
+            EditAction::InsertTab => "insert tab",
+            EditAction::Delete => "delete",
+            EditAction::Backspace => "backspace",
+            EditAction::Cut => "cut",
+            EditAction::Paste => "paste",
+            EditAction::Undo => "undo",
+            EditAction::Redo => "redo",
         };
         name.to_string()
     }
@@ -189,6 +213,8 @@ impl ViewAction{
     Accept,
     Exit,
     GotoModeSelectionAction(SelectionAction),
+    /// Toggles a flag applied to the pattern typed in [`Mode::Find`](crate::mode::Mode::Find).
+    ToggleSearchFlag(crate::search::SearchFlag),
 }
 impl UtilAction{
     fn action_name(&self) -> String{
@@ -209,7 +235,10 @@ impl UtilAction{
             UtilAction::MoveHome => "util text box move cursor home",
             UtilAction::MoveLeft => "util text box move cursor left",
             UtilAction::MoveRight => "util text box move cursor right",
-            UtilAction::Paste => "util text box paste"
+            UtilAction::Paste => "util text box paste",
+            UtilAction::ToggleSearchFlag(crate::search::SearchFlag::Literal) => "toggle literal search",
+            UtilAction::ToggleSearchFlag(crate::search::SearchFlag::IgnoreCase) => "toggle case insensitive search",
+            UtilAction::ToggleSearchFlag(crate::search::SearchFlag::Word) => "toggle whole word search",
         };
         name.to_string()
     }
@@ -232,6 +261,125 @@ impl Action{
         };
         command_name.to_string()
     }
+    /// Every built in action that can be invoked by name from the command line. Selection actions have a count of 1.
+    /// Actions that need an argument(inserting a specific char, selecting a specific register, etc.) are not included.
+    pub fn registry() -> Vec<Action>{
+        use crate::mode::Mode;
+        let mut actions = vec![
+            Action::EditorAction(EditorAction::ModePop),
+            Action::EditorAction(EditorAction::Quit),
+            Action::EditorAction(EditorAction::QuitIgnoringChanges),
+            Action::EditorAction(EditorAction::Save),
+            Action::EditorAction(EditorAction::Copy),
+            Action::EditorAction(EditorAction::ToggleLineNumbers),
+            Action::EditorAction(EditorAction::ToggleStatusBar),
+            Action::EditorAction(EditorAction::EvaluateSelectionAsCommand),
+            Action::EditorAction(EditorAction::EvaluateClipboardAsCommand),
+            Action::EditorAction(EditorAction::EvaluateSelectionAsLookObject),
+        ];
+        //modes displaying a message are entered by the editor itself, so they aren't included
+        for mode in [Mode::View, Mode::Goto, Mode::Command, Mode::Find, Mode::Split, Mode::Keep, Mode::Remove, Mode::Replace, Mode::Object, Mode::AddSurround, Mode::Register]{
+            actions.push(Action::EditorAction(EditorAction::ModePush(mode, None)));
+        }
+        for selection_action in [
+            SelectionAction::MoveCursorUp,
+            SelectionAction::MoveCursorDown,
+            SelectionAction::MoveCursorLeft,
+            SelectionAction::MoveCursorRight,
+            SelectionAction::MoveCursorWordBoundaryForward,
+            SelectionAction::MoveCursorWordBoundaryBackward,
+            SelectionAction::MoveCursorLineEnd,
+            SelectionAction::MoveCursorHome,
+            SelectionAction::MoveCursorBufferStart,
+            SelectionAction::MoveCursorBufferEnd,
+            SelectionAction::MoveCursorPageUp,
+            SelectionAction::MoveCursorPageDown,
+            SelectionAction::ExtendSelectionUp,
+            SelectionAction::ExtendSelectionDown,
+            SelectionAction::ExtendSelectionLeft,
+            SelectionAction::ExtendSelectionRight,
+            SelectionAction::ExtendSelectionWordBoundaryBackward,
+            SelectionAction::ExtendSelectionWordBoundaryForward,
+            SelectionAction::ExtendSelectionLineEnd,
+            SelectionAction::ExtendSelectionHome,
+            SelectionAction::ExtendSelectionBufferStart,
+            SelectionAction::ExtendSelectionBufferEnd,
+            SelectionAction::ExtendSelectionPageUp,
+            SelectionAction::ExtendSelectionPageDown,
+            SelectionAction::SelectLine,
+            SelectionAction::SelectAll,
+            SelectionAction::CollapseSelectionToAnchor,
+            SelectionAction::CollapseSelectionToCursor,
+            SelectionAction::ClearNonPrimarySelections,
+            SelectionAction::AddSelectionAbove,
+            SelectionAction::AddSelectionBelow,
+            SelectionAction::RemovePrimarySelection,
+            SelectionAction::IncrementPrimarySelection,
+            SelectionAction::DecrementPrimarySelection,
+            SelectionAction::Surround,
+            SelectionAction::SurroundingPair,
+            SelectionAction::FlipDirection,
+            SelectionAction::SelectNextMatch,
+            SelectionAction::SelectPreviousMatch,
+            SelectionAction::AddNextMatch,
+        ]{
+            actions.push(Action::SelectionAction(selection_action, 1));
+        }
+        for edit_action in [
+            EditAction::InsertNewline,
+            EditAction::InsertTab,
+            EditAction::Delete,
+            EditAction::Backspace,
+            EditAction::Cut,
+            EditAction::Paste,
+            EditAction::Undo,
+            EditAction::Redo,
+            EditAction::AddSurround('[', ']'),
+            EditAction::AddSurround('{', '}'),
+            EditAction::AddSurround('(', ')'),
+            EditAction::AddSurround('<', '>'),
+        ]{
+            actions.push(Action::EditAction(edit_action));
+        }
+        for view_action in [
+            ViewAction::CenterVerticallyAroundCursor,
+            ViewAction::ScrollUp,
+            ViewAction::ScrollDown,
+            ViewAction::ScrollLeft,
+            ViewAction::ScrollRight,
+        ]{
+            actions.push(Action::ViewAction(view_action));
+        }
+        actions
+    }
+    /// Whether this action can be performed while `mode` is the current mode. Actions expect to be performed from the
+    /// modes whose keybinds map to them, or from modes falling through to insert.
+    pub fn runs_in(&self, mode: &crate::mode::Mode) -> bool{
+        use crate::mode::Mode;
+        match self{
+            Action::EditorAction(EditorAction::Quit) => matches!(mode, Mode::Insert | Mode::Command | Mode::Error | Mode::Warning | Mode::Notify | Mode::Info),
+            //also runs in error mode, when confirming a quit with unsaved changes. that error mode is only entered by quit
+            Action::EditorAction(EditorAction::QuitIgnoringChanges) => matches!(mode, Mode::Insert | Mode::Command),
+            Action::EditorAction(EditorAction::Save) => matches!(mode, Mode::Insert | Mode::Command | Mode::Warning | Mode::Notify | Mode::Info),
+            Action::EditorAction(EditorAction::Copy) => matches!(mode, Mode::Insert | Mode::Warning | Mode::Notify | Mode::Info),
+            Action::EditorAction(EditorAction::ToggleLineNumbers | EditorAction::ToggleStatusBar) => matches!(mode, Mode::Insert | Mode::Command),
+            Action::EditorAction(EditorAction::SelectRegister(_)) => *mode == Mode::Register,
+            //the rest either run from any mode, or exit to insert themselves
+            Action::EditorAction(_) => true,
+            Action::SelectionAction(_, _) => matches!(mode, Mode::Insert | Mode::Object | Mode::Warning | Mode::Notify | Mode::Info),
+            Action::EditAction(_) => matches!(mode, Mode::Insert | Mode::Command | Mode::AddSurround | Mode::Warning | Mode::Notify | Mode::Info),
+            Action::ViewAction(_) => matches!(mode, Mode::Insert | Mode::View | Mode::Warning | Mode::Notify | Mode::Info),
+            Action::UtilAction(UtilAction::GotoModeSelectionAction(_)) => *mode == Mode::Goto,
+            //util actions aren't in the registry, so only the default keybinds of util bar modes map to them
+            Action::UtilAction(_) => true,
+        }
+    }
+    /// Finds the action in [`Action::registry`] whose command name matches `name`. Underscores may be used in place of 
+    /// spaces, so "select_line" and "select line" both name [`SelectionAction::SelectLine`].
+    pub fn from_command_name(name: &str) -> Option<Action>{
+        let name = name.replace('_', " ");
+        Action::registry().into_iter().find(|action| action.command_name() == name)
+    }
     pub fn command_source(&self) -> String{
         let command_source = match self{
             Action::EditorAction(_) | Action::ViewAction(_) | Action::UtilAction(_) => "(edit cli)",
@@ -1,13 +1,10 @@
-//TODO: serve events to events file for interested external programs to read. 
-//certain events may require blocking until all readers send some sort of acknowledge indication 
-//to let edit know to continue with it's usual behavior, or a reader may modify whatever it needs 
-//via filesystem interface, then send some finished indication.
-//only after edit has received a continue or finished indication, should it unblock
+//TODO: intercepted events are acknowledged by a single handler. maybe allow multiple handlers to register for the same 
+//event kind/mode, and only unblock once all of them have acknowledged
 //TODO: research how acme handles events and coordinating response behavior
 
 //TODO: research how acme uses Send + Win to allow a buffer to be used as an interactive command line interface
 
-use std::{io::Write, path::{Path, PathBuf}};
+use std::{io::Write, path::{Path, PathBuf}, os::unix::process::CommandExt, sync::atomic::{AtomicBool, Ordering as AtomicOrdering}};
 use ratatui::{
     prelude::*,
     widgets::*
@@ -17,14 +14,28 @@ use crate::{
     mode::Mode,
     action::{Action, EditorAction, SelectionAction, EditAction, ViewAction, UtilAction},
     mode_stack::ModeStack,
-    ui::{UserInterface, util_bar::*},
+    ui::{UserInterface, util_bar::*, popups::Popups},
+    keybind::{string_to_key_event, key_event_to_string},
     range::Range,
     buffer::Buffer,
     display_area::{self, DisplayArea, DisplayAreaError},
     selection::{self, Selection, CursorSemantics},
     selections::{self, Selections, SelectionsError},
     history::ChangeSet,
+    file_server::{ServedFile, SETTINGS},
+    event_log::{self, EventLog, EditorEvent, EventKind, Intercept, Acknowledgement},
+    instance::Instance,
+    registers::{Registers, YANK_REGISTER, SEARCH_REGISTER, COMMAND_REGISTER},
+    clipboard::Clipboard,
+    user_commands::{UserCommand, UserCommands, substitute_parameters},
+    hooks::{Hooks, HookEvent},
+    user_options::{self, UserOptions, OptionType, OptionValue},
+    shell::{ShellCommands, ShellCommandKind},
+    search::{SearchFlags, SearchOptions},
+    sam,
+    kakoune_style_commands::{parse_command, parse_first_command, to_shell_string, Word, WordType, ExpansionType},
 };
+use serve9p::file_system::FsRequest;
 
 
 
@@ -36,8 +47,13 @@ pub enum WindowEvent{
 pub enum Event{ //TODO: need to disambiguate these events from events in 9p served events file  //System/Editor, Input/Output, External/Internal, ...
     KeyboardInput(crossterm::event::KeyEvent),
     MouseInput(crossterm::event::MouseEvent),
-    NineP(serve9p::file_system::FsRequest),
+    /// A request from a 9p client, with the id of the connection it arrived on.
+    NineP(u64, FsRequest),
+    /// Sent once a 9p connection closes, with its id, so state kept for it can be dropped.
+    NinePDisconnected(u64),
     Window(WindowEvent),
+    /// Sent by the worker thread of the running shell command(see [`crate::shell`]), with the result of each of its runs.
+    ShellCommandFinished(Vec<Result<String, String>>),
     //Tick(timed_event_kind),   //maybe for cursor blink or similar...
 }
 
@@ -60,10 +76,45 @@ pub struct Application{
     config: Config,
     pub buffer: Buffer, 
     preserved_selections: Option<Selections>, 
+    /// Redo history from before [`Mode::Replace`] was entered. Restored if the replacement is cancelled.
+    preserved_redo_stack: Option<Vec<ChangeSet>>,
+    /// Whether [`Mode::Replace`]'s preview of its replacement is applied to the buffer, as the top of the undo stack.
+    replace_preview_applied: bool,
     pub undo_stack: Vec<ChangeSet>,   //maybe have separate buffer and selections undo/redo stacks?...
     pub redo_stack: Vec<ChangeSet>,
     pub selections: Selections,
-    pub clipboard: String,
+    pub registers: Registers,
+    /// Register chosen in [`Mode::Register`]. Used, then cleared, by the next copy, cut or paste.
+    pub selected_register: Option<String>,
+    /// System clipboard commands, and any OSC 52 sequence waiting to be written to the terminal.
+    pub clipboard: Clipboard,
+    /// Commands defined with `add_command`.
+    pub user_commands: UserCommands,
+    /// Commands run in response to editor events, added with `add_hook`.
+    pub hooks: Hooks,
+    /// Options declared with `add_option`.
+    pub user_options: UserOptions,
+    /// The `smart_case` setting, and search flags toggled in [`Mode::Find`].
+    pub search_options: SearchOptions,
+    /// Shell command running on a worker thread, if any, and the timeout applied to every shell command.
+    pub shell: ShellCommands,
+    /// Socket and id this editor serves its file system under, once registered. Exported to shell commands.
+    pub instance: Option<Instance>,
+    /// Number of [`Application::update`] calls in progress. Queued hooks run when the outermost call completes.
+    update_depth: usize,
+    /// Data written to served files, keyed by connection id and qid path. Applied as a whole when that connection clunks the file,
+    /// or dropped if the connection closes first.
+    pending_file_writes: std::collections::HashMap<(u64, u64), Vec<u8>>,
+    event_log: EventLog,
+    /// Event kinds, per mode, that an external handler has registered to intercept.
+    intercepts: Vec<Intercept>,
+    /// The intercepted event awaiting acknowledgement, if any.
+    pending_intercept: Option<EditorEvent>,
+    intercept_acknowledgement: Option<Acknowledgement>,
+    /// Input events received while waiting on an acknowledgement. Handled, in order, before any newly received events.
+    deferred_events: std::collections::VecDeque<Event>,
+    /// Result of the last command written to the served `command` file.
+    command_result: Option<Result<(), String>>,
 }
 impl Application{
     pub fn new(config: Config, buffer_text: &str, file_path: Option<PathBuf>, read_only: bool, terminal: &Terminal<impl Backend>) -> Result<Self, String>{
@@ -81,6 +132,8 @@ impl Application{
             config: config.clone(),
             buffer: buffer.clone(),
             preserved_selections: None,
+            preserved_redo_stack: None,
+            replace_preview_applied: false,
             undo_stack: Vec::new(),
             redo_stack: Vec::new(),
             selections: Selections::new(
@@ -101,7 +154,23 @@ impl Application{
             ),
             buffer_horizontal_start: 0,
             buffer_vertical_start: 0,
-            clipboard: String::new(),
+            registers: Registers::default(),
+            selected_register: None,
+            clipboard: Clipboard::default(),
+            user_commands: UserCommands::default(),
+            hooks: Hooks::default(),
+            user_options: UserOptions::default(),
+            search_options: SearchOptions::default(),
+            shell: ShellCommands::default(),
+            instance: None,
+            update_depth: 0,
+            pending_file_writes: std::collections::HashMap::new(),
+            event_log: EventLog::new(EVENT_LOG_CAPACITY),
+            intercepts: Vec::new(),
+            pending_intercept: None,
+            intercept_acknowledgement: None,
+            deferred_events: std::collections::VecDeque::new(),
+            command_result: None,
         };
 
         instance.setup();
@@ -157,6 +226,61 @@ impl Application{
         )
     }
     pub fn mode(&self) -> Mode{self.mode_stack.top().clone()}
+    /// Message displayed with the current mode, if any. Such as an error message in [`Mode::Error`].
+    pub fn mode_message(&self) -> Option<String>{self.mode_stack.top_message()}
+    pub fn keybinds(&self) -> &indexmap::IndexMap<(Mode, crossterm::event::KeyEvent), Action>{&self.config.keybinds}
+
+    /// Record an event in the served events file, and queue any hooks it triggers.
+    fn emit(&mut self, event: EditorEvent){
+        self.event_log.push(&event);
+        match &event{
+            EditorEvent::InsertText{text, ..} => self.hooks.queue(HookEvent::TextInserted, text.clone()),
+            EditorEvent::RemoveText{text, ..} => self.hooks.queue(HookEvent::TextRemoved, text.clone()),
+            EditorEvent::ModeChange{from, to} => self.hooks.queue(HookEvent::ModeChanged, format!("{:?} {:?}", from, to)),
+            EditorEvent::Error(message) => self.hooks.queue(HookEvent::ErrorOccurred, message.clone()),
+            EditorEvent::Save(file_path) => self.hooks.queue(HookEvent::BufferSaved, file_path.clone()),
+            EditorEvent::Key(_) | EditorEvent::Mouse(_) => {}
+        }
+    }
+    /// Runs the commands of hooks triggered by `event`. A failing hook command is displayed as an error.
+    fn run_hooks(&mut self, event: HookEvent, parameter: &str){
+        if self.hooks.is_running(){return;}
+        let commands = self.hooks.commands(event, parameter);
+        if commands.is_empty(){return;}
+        self.hooks.set_running(true);
+        for command in commands{
+            if let Err(error) = evaluate_commands(self, &command){
+                handle_message(self, DisplayMode::Error, &format!("{:?} hook failed: {}", event, error));
+            }
+        }
+        self.hooks.set_running(false);
+    }
+    /// Runs hooks for events queued since this was last called.
+    fn run_pending_hooks(&mut self){
+        for (event, parameter) in self.hooks.take_pending(){
+            self.run_hooks(event, &parameter);
+        }
+    }
+    /// Emit text events for the most recently applied change set, or the most recently undone change set if `undone` is true.
+    fn emit_text_events(&mut self, undone: bool){
+        let change_set = if undone{self.redo_stack.last()}else{self.undo_stack.last()};
+        if let Some(change_set) = change_set{
+            for event in event_log::text_events(change_set, undone){
+                self.emit(event);
+            }
+        }
+    }
+    /// [`FIND_PROMPT`], listing any search flags toggled in [`Mode::Find`].
+    fn find_prompt(&self) -> String{
+        let flags = self.search_options.find_flags.names();
+        if flags.is_empty(){FIND_PROMPT.to_string()}
+        else{format!("{}({}): ", FIND_PROMPT.trim_end().trim_end_matches(':'), flags.join(", "))}
+    }
+    fn push_mode(&mut self, mode: Mode, message: Option<String>){
+        if self.mode() != mode{self.emit(EditorEvent::ModeChange{from: self.mode(), to: mode.clone()});}
+        if let (Mode::Error, Some(message)) = (&mode, &message){self.emit(EditorEvent::Error(message.clone()));}
+        self.mode_stack.push(mode, message);
+    }
 
     /// Set all data related to document viewport UI.
     fn update_ui_data_document(&mut self){
@@ -181,6 +305,12 @@ impl Application{
         let cursor_position = &self.selections.primary.selection_to_selection2d(&self.buffer, self.config.semantics.clone()).head().clone();
         self.ui.status_bar.cursor_position_widget.text = format!("cursor: {}:{}", cursor_position.y + 1, cursor_position.x + 1)
     }
+    fn update_ui_data_shell_command(&mut self){
+        self.ui.status_bar.shell_command_widget.text = match self.shell.running(){
+            None => String::new(),
+            Some(running) => format!("running: {}", running.command)
+        };
+    }
     fn update_ui_data_mode(&mut self){self.ui.status_bar.mode_widget.text = format!("{:?}: {:#?}", self.mode(), self.mode_stack.len());}
     /// set data related to util bar UI.
     fn update_ui_data_util_bar(&mut self){
@@ -240,12 +370,16 @@ impl Application{
                                 Mode::Command | 
                                 Mode::Find | 
                                 Mode::Goto | 
-                                Mode::Split => 1,
+                                Mode::Split | 
+                                Mode::Keep | 
+                                Mode::Remove | 
+                                Mode::Replace => 1,
                             
                                 Mode::Object |
                                 Mode::Insert |
                                 Mode::View |
-                                Mode::AddSurround => if app.ui.status_bar.show{1}else{0}
+                                Mode::AddSurround |
+                                Mode::Register => if app.ui.status_bar.show{1}else{0}
                             }
                         )
                     ]
@@ -357,7 +491,7 @@ impl Application{
                 )
                 .split(rect)
         }
-        fn layout_status_bar_middle_third(rect: Rect) -> std::rc::Rc<[Rect]>{
+        fn layout_status_bar_middle_third(app: &Application, rect: Rect) -> std::rc::Rc<[Rect]>{
             Layout::default()
                 .direction(Direction::Horizontal)
                 .constraints(
@@ -365,6 +499,12 @@ impl Application{
                         //[0]
                         // selections widget
                         Constraint::Min(0),     //or set selections widget to Max, and surround with 2 padding widgets set to Min(0)?...idk if that will work the same?...
+
+                        //[1]
+                        // shell command widget. empty unless a shell command is running
+                        Constraint::Max(
+                            app.ui.status_bar.shell_command_widget.text.chars().count() as u16
+                        ),
                     ]
                 )
                 .split(rect)
@@ -398,7 +538,7 @@ impl Application{
                 .split(rect)
         }
         fn layout_util_bar(app: &Application, rect: Rect) -> std::rc::Rc<[Rect]>{
-            use crate::ui::util_bar::{GOTO_PROMPT, FIND_PROMPT, SPLIT_PROMPT, COMMAND_PROMPT};
+            use crate::ui::util_bar::{GOTO_PROMPT, SPLIT_PROMPT, KEEP_PROMPT, REMOVE_PROMPT, REPLACE_PROMPT, COMMAND_PROMPT};
             // layout of util rect (goto/find/command/save as)
             Layout::default()
                 .direction(Direction::Horizontal)
@@ -409,8 +549,11 @@ impl Application{
                         Constraint::Length(
                             match app.mode(){
                                 Mode::Goto => GOTO_PROMPT.len() as u16,
-                                Mode::Find => FIND_PROMPT.len() as u16,
+                                Mode::Find => app.find_prompt().len() as u16,
                                 Mode::Split => SPLIT_PROMPT.len() as u16,
+                                Mode::Keep => KEEP_PROMPT.len() as u16,
+                                Mode::Remove => REMOVE_PROMPT.len() as u16,
+                                Mode::Replace => REPLACE_PROMPT.len() as u16,
                                 Mode::Command => COMMAND_PROMPT.len() as u16,
                                 Mode::Error
                                 | Mode::Warning
@@ -419,7 +562,8 @@ impl Application{
                                 | Mode::Insert
                                 | Mode::Object
                                 | Mode::View 
-                                | Mode::AddSurround => 0
+                                | Mode::AddSurround
+                                | Mode::Register => 0
                             }
                         ),
 
@@ -434,11 +578,15 @@ impl Application{
                                 | Mode::Warning
                                 | Mode::Notify
                                 | Mode::Info
-                                | Mode::AddSurround => rect.width,
+                                | Mode::AddSurround
+                                | Mode::Register => rect.width,
                                 Mode::Goto => rect.width - GOTO_PROMPT.len() as u16,
                                 Mode::Command => rect.width - COMMAND_PROMPT.len() as u16,
-                                Mode::Find => rect.width - FIND_PROMPT.len() as u16,
+                                Mode::Find => rect.width - app.find_prompt().len() as u16,
                                 Mode::Split => rect.width - SPLIT_PROMPT.len() as u16,
+                                Mode::Keep => rect.width - KEEP_PROMPT.len() as u16,
+                                Mode::Remove => rect.width - REMOVE_PROMPT.len() as u16,
+                                Mode::Replace => rect.width - REPLACE_PROMPT.len() as u16,
                             }
                         ),
                         // used to fill in space when other two are 0 length
@@ -487,7 +635,7 @@ impl Application{
         let util_rect = layout_util_bar(self, terminal_rect[2]);
 
         let status_bar_first_third_rect = layout_status_bar_first_third(self, status_bar_rect[0]);
-        let status_bar_middle_third_rect = layout_status_bar_middle_third(status_bar_rect[1]);
+        let status_bar_middle_third_rect = layout_status_bar_middle_third(self, status_bar_rect[1]);
         let status_bar_last_third_rect = layout_status_bar_last_third(self, status_bar_rect[2]);
     
         self.ui.document_viewport.line_number_widget.rect = document_viewport_rect[0];
@@ -502,6 +650,7 @@ impl Application{
         self.ui.status_bar.padding_3.rect =              status_bar_first_third_rect[5];
         
         self.ui.status_bar.selections_widget.rect =      status_bar_middle_third_rect[0];//status_bar_rect[5];
+        self.ui.status_bar.shell_command_widget.rect =   status_bar_middle_third_rect[1];
         
         self.ui.status_bar.padding_4.rect =              status_bar_last_third_rect[0];
         self.ui.status_bar.cursor_position_widget.rect = status_bar_last_third_rect[1];//status_bar_rect[6];
@@ -515,6 +664,9 @@ impl Application{
         self.ui.popups.command.rect = sized_centered_rect(self.ui.popups.command.widest_element_len, self.ui.popups.command.num_elements, self.ui.terminal_size);
         self.ui.popups.find.rect = sized_centered_rect(self.ui.popups.find.widest_element_len, self.ui.popups.find.num_elements, self.ui.terminal_size);
         self.ui.popups.split.rect = sized_centered_rect(self.ui.popups.split.widest_element_len, self.ui.popups.split.num_elements, self.ui.terminal_size);
+        self.ui.popups.keep.rect = sized_centered_rect(self.ui.popups.keep.widest_element_len, self.ui.popups.keep.num_elements, self.ui.terminal_size);
+        self.ui.popups.remove.rect = sized_centered_rect(self.ui.popups.remove.widest_element_len, self.ui.popups.remove.num_elements, self.ui.terminal_size);
+        self.ui.popups.replace.rect = sized_centered_rect(self.ui.popups.replace.widest_element_len, self.ui.popups.replace.num_elements, self.ui.terminal_size);
         self.ui.popups.error.rect = sized_centered_rect(self.ui.popups.error.widest_element_len, self.ui.popups.error.num_elements, self.ui.terminal_size);
         self.ui.popups.modified_error.rect = sized_centered_rect(self.ui.popups.modified_error.widest_element_len, self.ui.popups.modified_error.num_elements, self.ui.terminal_size);
         self.ui.popups.warning.rect = sized_centered_rect(self.ui.popups.warning.widest_element_len, self.ui.popups.warning.num_elements, self.ui.terminal_size);
@@ -523,6 +675,7 @@ impl Application{
         self.ui.popups.view.rect = sized_centered_rect(self.ui.popups.view.widest_element_len, self.ui.popups.view.num_elements, self.ui.terminal_size);
         self.ui.popups.object.rect = sized_centered_rect(self.ui.popups.object.widest_element_len, self.ui.popups.object.num_elements, self.ui.terminal_size);
         self.ui.popups.add_surround.rect = sized_centered_rect(self.ui.popups.add_surround.widest_element_len, self.ui.popups.add_surround.num_elements, self.ui.terminal_size);
+        self.ui.popups.register.rect = sized_centered_rect(self.ui.popups.register.widest_element_len, self.ui.popups.register.num_elements, self.ui.terminal_size);
     }
     
     pub fn render(&self, terminal: &mut Terminal<impl Backend>) -> Result<(), String>{
@@ -645,6 +798,7 @@ impl Application{
                     frame.render_widget(generate_widget("", Alignment::Center, false, STATUS_BAR_BACKGROUND_COLOR, STATUS_BAR_BACKGROUND_COLOR), self.ui.status_bar.padding_3.rect);
                     
                     frame.render_widget(generate_widget(&self.ui.status_bar.selections_widget.text, Alignment::Center, true, STATUS_BAR_BACKGROUND_COLOR, SELECTIONS_WIDGET_FOREGROUND_COLOR), self.ui.status_bar.selections_widget.rect);
+                    frame.render_widget(generate_widget(&self.ui.status_bar.shell_command_widget.text, Alignment::Center, true, STATUS_BAR_BACKGROUND_COLOR, SHELL_COMMAND_WIDGET_FOREGROUND_COLOR), self.ui.status_bar.shell_command_widget.rect);
                     
                     frame.render_widget(generate_widget("", Alignment::Center, false, STATUS_BAR_BACKGROUND_COLOR, STATUS_BAR_BACKGROUND_COLOR), self.ui.status_bar.padding_4.rect);
                     frame.render_widget(generate_widget(&self.ui.status_bar.cursor_position_widget.text, Alignment::Center, true, STATUS_BAR_BACKGROUND_COLOR, CURSOR_POSITION_WIDGET_FOREGROUND_COLOR), self.ui.status_bar.cursor_position_widget.rect);
@@ -680,7 +834,7 @@ impl Application{
                         }
                     }
                     Mode::Find => {
-                        frame.render_widget(generate_widget(FIND_PROMPT, Alignment::Center, false, UTIL_BAR_BACKGROUND_COLOR, UTIL_BAR_FOREGROUND_COLOR), self.ui.util_bar.prompt.rect);
+                        frame.render_widget(generate_widget(&self.find_prompt(), Alignment::Center, false, UTIL_BAR_BACKGROUND_COLOR, UTIL_BAR_FOREGROUND_COLOR), self.ui.util_bar.prompt.rect);
                         frame.render_widget(generate_widget(&self.text_box_display_area().text(&self.ui.util_bar.utility_widget.text_box.buffer), Alignment::Left, false, UTIL_BAR_BACKGROUND_COLOR, if self.ui.util_bar.utility_widget.text_box.text_is_valid{UTIL_BAR_FOREGROUND_COLOR}else{UTIL_BAR_INVALID_TEXT_FOREGROUND_COLOR}), self.ui.util_bar.utility_widget.rect);
                         render_util_bar_highlights(self, frame.buffer_mut());
                         if SHOW_CONTEXTUAL_KEYBINDS{
@@ -697,6 +851,33 @@ impl Application{
                             frame.render_widget(generate_popup(&self.ui.popups.split.text, &format!("{}: {}", self.ui.popups.split.title, self.mode_stack.len())/*&self.ui.popups.split.title*/, Color::Black, Color::Yellow), self.ui.popups.split.rect);
                         }
                     }
+                    Mode::Keep => {
+                        frame.render_widget(generate_widget(KEEP_PROMPT, Alignment::Center, false, UTIL_BAR_BACKGROUND_COLOR, UTIL_BAR_FOREGROUND_COLOR), self.ui.util_bar.prompt.rect);
+                        frame.render_widget(generate_widget(&self.text_box_display_area().text(&self.ui.util_bar.utility_widget.text_box.buffer), Alignment::Left, false, UTIL_BAR_BACKGROUND_COLOR, if self.ui.util_bar.utility_widget.text_box.text_is_valid{UTIL_BAR_FOREGROUND_COLOR}else{UTIL_BAR_INVALID_TEXT_FOREGROUND_COLOR}), self.ui.util_bar.utility_widget.rect);
+                        render_util_bar_highlights(self, frame.buffer_mut());
+                        if SHOW_CONTEXTUAL_KEYBINDS{
+                            frame.render_widget(ratatui::widgets::Clear, self.ui.popups.keep.rect);
+                            frame.render_widget(generate_popup(&self.ui.popups.keep.text, &format!("{}: {}", self.ui.popups.keep.title, self.mode_stack.len())/*&self.ui.popups.keep.title*/, Color::Black, Color::Yellow), self.ui.popups.keep.rect);
+                        }
+                    }
+                    Mode::Remove => {
+                        frame.render_widget(generate_widget(REMOVE_PROMPT, Alignment::Center, false, UTIL_BAR_BACKGROUND_COLOR, UTIL_BAR_FOREGROUND_COLOR), self.ui.util_bar.prompt.rect);
+                        frame.render_widget(generate_widget(&self.text_box_display_area().text(&self.ui.util_bar.utility_widget.text_box.buffer), Alignment::Left, false, UTIL_BAR_BACKGROUND_COLOR, if self.ui.util_bar.utility_widget.text_box.text_is_valid{UTIL_BAR_FOREGROUND_COLOR}else{UTIL_BAR_INVALID_TEXT_FOREGROUND_COLOR}), self.ui.util_bar.utility_widget.rect);
+                        render_util_bar_highlights(self, frame.buffer_mut());
+                        if SHOW_CONTEXTUAL_KEYBINDS{
+                            frame.render_widget(ratatui::widgets::Clear, self.ui.popups.remove.rect);
+                            frame.render_widget(generate_popup(&self.ui.popups.remove.text, &format!("{}: {}", self.ui.popups.remove.title, self.mode_stack.len())/*&self.ui.popups.remove.title*/, Color::Black, Color::Yellow), self.ui.popups.remove.rect);
+                        }
+                    }
+                    Mode::Replace => {
+                        frame.render_widget(generate_widget(REPLACE_PROMPT, Alignment::Center, false, UTIL_BAR_BACKGROUND_COLOR, UTIL_BAR_FOREGROUND_COLOR), self.ui.util_bar.prompt.rect);
+                        frame.render_widget(generate_widget(&self.text_box_display_area().text(&self.ui.util_bar.utility_widget.text_box.buffer), Alignment::Left, false, UTIL_BAR_BACKGROUND_COLOR, if self.ui.util_bar.utility_widget.text_box.text_is_valid{UTIL_BAR_FOREGROUND_COLOR}else{UTIL_BAR_INVALID_TEXT_FOREGROUND_COLOR}), self.ui.util_bar.utility_widget.rect);
+                        render_util_bar_highlights(self, frame.buffer_mut());
+                        if SHOW_CONTEXTUAL_KEYBINDS{
+                            frame.render_widget(ratatui::widgets::Clear, self.ui.popups.replace.rect);
+                            frame.render_widget(generate_popup(&self.ui.popups.replace.text, &format!("{}: {}", self.ui.popups.replace.title, self.mode_stack.len())/*&self.ui.popups.replace.title*/, Color::Black, Color::Yellow), self.ui.popups.replace.rect);
+                        }
+                    }
                     Mode::Error => {
                         frame.render_widget(generate_widget(&self.mode_stack.top_message().expect("text being Some should be guaranteed in Error mode"), Alignment::Center, true, ERROR_BACKGROUND_COLOR, ERROR_FOREGROUND_COLOR), self.ui.util_bar.utility_widget.rect);
                         //if &self.mode_stack.top().text.expect("text being Some should be guaranteed in Error mode") == FILE_MODIFIED{
@@ -752,6 +933,12 @@ impl Application{
                             frame.render_widget(generate_popup(&self.ui.popups.add_surround.text, &format!("{}: {}", self.ui.popups.add_surround.title, self.mode_stack.len())/*&self.ui.popups.add_surround.title*/, Color::Black, Color::Yellow), self.ui.popups.add_surround.rect);
                         }
                     }
+                    Mode::Register => {
+                        if SHOW_CONTEXTUAL_KEYBINDS{
+                            frame.render_widget(ratatui::widgets::Clear, self.ui.popups.register.rect);
+                            frame.render_widget(generate_popup(&self.ui.popups.register.text, &format!("{}: {}", self.ui.popups.register.title, self.mode_stack.len()), Color::Black, Color::Yellow), self.ui.popups.register.rect);
+                        }
+                    }
                 }
             }
         ){
@@ -798,24 +985,35 @@ impl Application{
     //Option<Action> because the next step may need to update the same variable if subsequent actions need to be performed
     //though this should always return Some(Action), unless an error is encountered in event reading...
     //alternatively, the next step could do: let action = Some(self.handle_event()?);
-    fn handle_event(&mut self, event_rx: &std::sync::mpsc::Receiver<Event>) -> Result<(), String>{
+    pub fn handle_event(&mut self, event_rx: &std::sync::mpsc::Receiver<Event>) -> Result<(), String>{
         // This is needed because generic keypresses cannot be inserted into keybind hashmap
         fn handle_char_insert(mode: Mode, key_event: crossterm::event::KeyEvent) -> Action{
             use crossterm::event::{KeyCode, KeyModifiers};
             match (key_event.code, key_event.modifiers){
                 (KeyCode::Char(c), KeyModifiers::SHIFT) if matches!(mode, Mode::Insert)                             => Action::EditAction(EditAction::InsertChar(c)),
-                (KeyCode::Char(c), KeyModifiers::SHIFT) if matches!(mode, Mode::Find | Mode::Split | Mode::Command) => Action::UtilAction(UtilAction::InsertChar(c)),
+                (KeyCode::Char(c), KeyModifiers::SHIFT) if matches!(mode, Mode::Find | Mode::Split | Mode::Keep | Mode::Remove | Mode::Replace | Mode::Command) => Action::UtilAction(UtilAction::InsertChar(c)),
                 (KeyCode::Char(c), KeyModifiers::NONE)  if matches!(mode, Mode::Insert)                             => Action::EditAction(EditAction::InsertChar(c)),
                 (KeyCode::Char(c), KeyModifiers::NONE)  if matches!(mode, Mode::Goto) && c.is_numeric()             => Action::UtilAction(UtilAction::InsertChar(c)),
-                (KeyCode::Char(c), KeyModifiers::NONE)  if matches!(mode, Mode::Find | Mode::Split | Mode::Command) => Action::UtilAction(UtilAction::InsertChar(c)),
+                (KeyCode::Char(c), KeyModifiers::NONE)  if matches!(mode, Mode::Register)                           => Action::EditorAction(EditorAction::SelectRegister(c)),
+                (KeyCode::Char(c), KeyModifiers::NONE)  if matches!(mode, Mode::Find | Mode::Split | Mode::Keep | Mode::Remove | Mode::Replace | Mode::Command) => Action::UtilAction(UtilAction::InsertChar(c)),
                 _ => Action::EditorAction(EditorAction::NoOpKeypress)
             }
         }
         //match event_rx.recv(){  //or maybe try_recv() and match error?...
-        match event_rx.recv_timeout(std::time::Duration::from_millis(30)){  //still hanging up randomly on quit...
+        let received = match self.deferred_events.pop_front(){
+            Some(event) => Ok(event),
+            None => event_rx.recv_timeout(std::time::Duration::from_millis(30))  //still hanging up randomly on quit...
+        };
+        match received{
             Ok(event) => {
                 match event{
                     Event::KeyboardInput(key_event) => {
+                        //esc is reserved for cancelling a running shell command, so that a stuck command can always be stopped
+                        if key_event == crossterm::event::KeyEvent::new(crossterm::event::KeyCode::Esc, crossterm::event::KeyModifiers::NONE) && self.shell.cancel(){
+                            return Ok(());
+                        }
+                        self.emit(EditorEvent::Key(key_event));
+                        if self.intercept(EditorEvent::Key(key_event), event_rx) == Acknowledgement::Consumed{return Ok(());}
                         self.update(
                             match self.config.keybinds.get(&(self.mode(), key_event)).cloned(){
                                 Some(action) => action,
@@ -841,6 +1039,8 @@ impl Application{
                     //TODO: figure out how to add mouse events to config.keybinds
                     //TODO: figure out how to accomplish acme style mouse chords
                     Event::MouseInput(mouse_event) => {//self.action(Action::EditorAction(EditorAction::NoOpEvent)),
+                        self.emit(EditorEvent::Mouse(mouse_event));
+                        if self.intercept(EditorEvent::Mouse(mouse_event), event_rx) == Acknowledgement::Consumed{return Ok(());}
                         let crossterm::event::MouseEvent{kind, column, row, modifiers} = mouse_event;
                         use crossterm::event::{MouseEventKind, KeyModifiers, MouseButton};
                         match kind{
@@ -899,7 +1099,18 @@ impl Application{
                             WindowEvent::FocusGained => {self.update(Action::EditorAction(EditorAction::NoOpEvent))}    //display cursor(s)/selection(s)?...
                         }
                     }
-                    Event::NineP(fs_request) => {}
+                    Event::NineP(connection, fs_request) => {self.handle_fs_request(connection, fs_request);}
+                    Event::NinePDisconnected(connection) => {self.handle_fs_disconnect(connection);}
+                    Event::ShellCommandFinished(outputs) => {
+                        if let Some(running) = self.shell.finish(){
+                            self.update_ui_data_shell_command();
+                            if running.is_cancelled(){
+                                handle_message(self, DisplayMode::Notify, "shell command cancelled");
+                            }else if let Err(e) = finish_shell_command(self, running.kind, outputs){
+                                handle_message(self, DisplayMode::Error, &e);
+                            }
+                        }
+                    }
                 }
                 Ok(())
             }
@@ -908,8 +1119,169 @@ impl Application{
         }
     }
 
-    //TODO?: should each action result in a new app state, or an error state?... can these be made more purely functional?...
+    /// If an external handler has registered to intercept `event` in the current mode, blocks until the handler acknowledges it
+    /// through the served `intercept/pending` file. 9p requests for the intercept files continue to be handled while blocked.
+    /// Any other events received, including other 9p requests, are deferred until after `event` has been handled.
+    /// Returns [`Acknowledgement::Pass`] if `event` is not intercepted, or the handler does not respond within [`INTERCEPT_TIMEOUT`].
+    pub fn intercept(&mut self, event: EditorEvent, event_rx: &std::sync::mpsc::Receiver<Event>) -> Acknowledgement{
+        let kind = match event{
+            EditorEvent::Key(_) => EventKind::Key,
+            EditorEvent::Mouse(_) => EventKind::Mouse,
+            _ => return Acknowledgement::Pass
+        };
+        let intercept = Intercept{kind, mode: self.mode()};
+        if !self.intercepts.contains(&intercept){return Acknowledgement::Pass;}
+
+        self.pending_intercept = Some(event);
+        self.intercept_acknowledgement = None;
+        let deadline = std::time::Instant::now() + INTERCEPT_TIMEOUT;
+        let acknowledgement = loop{
+            if let Some(acknowledgement) = self.intercept_acknowledgement.take(){break acknowledgement;}
+            let remaining = deadline.saturating_duration_since(std::time::Instant::now());
+            if remaining.is_zero(){
+                //handler is presumably gone. unregister it, so it doesn't stall every following event
+                self.intercepts.retain(|registered| *registered != intercept);
+                break Acknowledgement::Pass;
+            }
+            match event_rx.recv_timeout(remaining){
+                //the handler only needs the intercept files. anything else(a command, or a write to the buffer) could change
+                //the state the event was intercepted in, so it waits with the other deferred events
+                Ok(Event::NineP(connection, fs_request)) if serves_intercept(&fs_request) => self.handle_fs_request(connection, fs_request),
+                Ok(other) => self.deferred_events.push_back(other),
+                Err(std::sync::mpsc::RecvTimeoutError::Timeout) => {}
+                Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => break Acknowledgement::Pass,
+            }
+        };
+        self.pending_intercept = None;
+        acknowledgement
+    }
+
+    /// Respond to a request from the 9p file server. Replies are sent over the channel included in the request.
+    //writes are buffered until the file is clunked, so that content written over multiple Twrite messages is applied as a single change
+    //except for the command file, where each write is a separate command, so its result can be read back before clunking.
+    //serve9p doesn't tell us which fid a request is for, so writes are kept per connection, and a clunk from another client
+    //can't apply a write still in progress
+    /// Drops writes pending on `connection`. A connection that closes without clunking its files never applies them.
+    pub fn handle_fs_disconnect(&mut self, connection: u64){
+        self.pending_file_writes.retain(|(pending_connection, _), _| *pending_connection != connection);
+    }
+    pub fn handle_fs_request(&mut self, connection: u64, fs_request: FsRequest){
+        fn served_file(qid_path: u64) -> Result<ServedFile, String>{
+            ServedFile::from_qid_path(qid_path).ok_or(String::from("file does not exist"))
+        }
+        //a failed send means the requesting connection has gone away. nothing left to respond to
+        match fs_request{
+            FsRequest::Attach{uname: _, aname: _, reply} => {
+                let _ = reply.send(Ok(ServedFile::Root.qid()));
+            }
+            FsRequest::Walk{user: _, qid_path, wnames, reply} => {
+                let result = served_file(qid_path).and_then(|mut file| {
+                    let mut qids = Vec::new();
+                    for name in &wnames{
+                        match file.walk(name, &self.registers){
+                            Some(child) => {
+                                file = child;
+                                qids.push(file.qid());
+                            }
+                            None => break,
+                        }
+                    }
+                    //only an error if the first element could not be walked. partial walks return the qids walked so far
+                    if qids.is_empty() && !wnames.is_empty(){Err(String::from("file does not exist"))}
+                    else{Ok(qids)}
+                });
+                let _ = reply.send(result);
+            }
+            FsRequest::Open{user: _, qid_path, mode, reply} => {
+                const OWRITE: u8 = 1;
+                const ORDWR: u8 = 2;
+                let result = served_file(qid_path).and_then(|file| {
+                    let opened_for_write = matches!(mode & 0b11, OWRITE | ORDWR);
+                    if opened_for_write{
+                        if file.is_read_only(){return Err(format!("{} is read only", file.name(&self.registers)));}
+                        self.pending_file_writes.remove(&(connection, qid_path));
+                    }
+                    Ok(file.qid())
+                });
+                let _ = reply.send(result);
+            }
+            FsRequest::Read{qid_path, offset, count, reply} => {
+                //the event log is read incrementally, so it is not copied out in full
+                if qid_path == ServedFile::Events.qid_path(){
+                    let _ = reply.send(Ok(self.event_log.read(offset as usize, count as usize)));
+                    return;
+                }
+                let result = served_file(qid_path)
+                    .and_then(|file| read_served_file(self, file))
+                    .map(|content| {
+                        let start = (offset as usize).min(content.len());
+                        let end = start.saturating_add(count as usize).min(content.len());
+                        content[start..end].to_vec()
+                    });
+                let _ = reply.send(result);
+            }
+            FsRequest::Write{qid_path, offset, data, reply} => {
+                let result = served_file(qid_path).and_then(|file| {
+                    if file.is_read_only(){return Err(format!("{} is read only", file.name(&self.registers)));}
+                    if file == ServedFile::Command{
+                        let command = String::from_utf8(data.clone()).map_err(|_| String::from("written content must be valid UTF-8"))?;
+                        execute_served_command(self, &command)?;
+                        return Ok(data.len() as u32);
+                    }
+                    let pending = self.pending_file_writes.entry((connection, qid_path)).or_default();
+                    //data already written past this write is kept. only a write past the end extends it
+                    let offset = offset as usize;
+                    let end = offset + data.len();
+                    if pending.len() < end{pending.resize(end, 0);}
+                    pending[offset..end].copy_from_slice(&data);
+                    Ok(data.len() as u32)
+                });
+                let _ = reply.send(result);
+            }
+            FsRequest::Clunk{qid_path, reply} => {
+                let result = match self.pending_file_writes.remove(&(connection, qid_path)){
+                    None => Ok(()),
+                    Some(data) => {
+                        served_file(qid_path).and_then(|file| {
+                            match String::from_utf8(data){
+                                Err(_) => Err(String::from("written content must be valid UTF-8")),
+                                Ok(content) => write_served_file(self, file, &content)
+                            }
+                        })
+                    }
+                };
+                let _ = reply.send(result);
+            }
+            FsRequest::Stat{qid_path, reply} => {
+                let result = served_file(qid_path).and_then(|file| {
+                    if file.is_directory(){Ok(file.stat(0, &self.registers))}
+                    else if file == ServedFile::Events{Ok(file.stat(self.event_log.len() as u64, &self.registers))}
+                    else{
+                        read_served_file(self, file).map(|content| file.stat(content.len() as u64, &self.registers))
+                    }
+                });
+                let _ = reply.send(result);
+            }
+        }
+    }
+
+    /// Performs `action`. Hooks triggered while performing it run once the outermost update completes.
     pub fn update(&mut self, action: Action){
+        let selections_before = if self.update_depth == 0 && self.hooks.handles(HookEvent::SelectionsChanged){
+            Some(self.selections.clone())
+        }else{None};
+        self.update_depth = self.update_depth + 1;
+        self.perform(action);
+        self.update_depth = self.update_depth - 1;
+        if self.update_depth == 0{
+            if selections_before.is_some_and(|selections| selections != self.selections){
+                self.hooks.queue(HookEvent::SelectionsChanged, self.selections.primary.to_string(&self.buffer));
+            }
+            self.run_pending_hooks();
+        }
+    }
+    //TODO?: should each action result in a new app state, or an error state?... can these be made more purely functional?...
+    fn perform(&mut self, action: Action){
         //impl helper functions here to manage scope of exposure
         //fn esc_handle(app: &mut Application){
         //    assert!(app.mode() == Mode::Insert);
@@ -936,6 +1308,7 @@ impl Application{
                         }
                         //remove current mode from stack
                         if let Ok((popped_mode, popped_text)) = self.mode_stack.pop(){
+                            if popped_mode != self.mode(){self.emit(EditorEvent::ModeChange{from: popped_mode.clone(), to: self.mode()});}
                             if popped_mode == self.mode() && popped_text == self.mode_stack.top_message(){
                                 //continue popping until self.mode() is something else (this would clean up repeated error messages/etc.)
                                 self.update(Action::EditorAction(EditorAction::ModePop));
@@ -943,11 +1316,17 @@ impl Application{
                             }
                             match popped_mode{
                                 Mode::Command | Mode::Goto => {perform_shared_behavior(self);}
-                                Mode::Find | Mode::Split => {
+                                Mode::Find | Mode::Split | Mode::Keep | Mode::Remove => {
                                     perform_shared_behavior(self);
                                     self.preserved_selections = None;   //clear saved selections
                                 }
-                                Mode::Object | Mode::View | Mode::Error | Mode::Warning | Mode::Notify | Mode::Info | Mode::AddSurround | 
+                                Mode::Replace => {
+                                    perform_shared_behavior(self);
+                                    self.preserved_selections = None;   //clear saved selections
+                                    self.preserved_redo_stack = None;
+                                    self.replace_preview_applied = false;   //an applied preview is kept, as the replacement
+                                }
+                                Mode::Object | Mode::View | Mode::Error | Mode::Warning | Mode::Notify | Mode::Info | Mode::AddSurround | Mode::Register | 
                                 Mode::Insert => {/* do nothing */}  //could early return here, if we didn't need to update mode data
                             }
                             //does this belong here, or in ui.rs?...    //by calling here, we only perform this calculation as needed, not on every editor run cycle
@@ -964,9 +1343,10 @@ impl Application{
                         }
                         //let to_mode = stack_member.mode.clone();
                         match to_mode{
-                            Mode::Find | Mode::Split => {
+                            Mode::Find | Mode::Split | Mode::Keep | Mode::Remove | Mode::Replace => {
                                 pop_to_insert(self);
-                                self.mode_stack.push(to_mode, message);
+                                if to_mode == Mode::Replace{self.preserved_redo_stack = Some(self.redo_stack.clone());}
+                                self.push_mode(to_mode, message);
                                 self.preserved_selections = Some(self.selections.clone());  //save selections
                                 if !self.ui.status_bar.show{ // potential fix for status bar bug in todo.rs
                                     perform_shared_behavior(self);
@@ -974,21 +1354,29 @@ impl Application{
                             }
                             Mode::Command | Mode::Goto => {
                                 pop_to_insert(self);
-                                self.mode_stack.push(to_mode, message);
+                                self.push_mode(to_mode, message);
                                 if !self.ui.status_bar.show{ // potential fix for status bar bug in todo.rs
                                     perform_shared_behavior(self);
                                 }
                             }
-                            Mode::Object | Mode::AddSurround | Mode::View => {
+                            Mode::Object | Mode::AddSurround | Mode::Register | Mode::View => {
                                 pop_to_insert(self);
-                                self.mode_stack.push(to_mode, message);
+                                self.push_mode(to_mode, message);
                             }
-                            Mode::Error | Mode::Warning | Mode::Notify | Mode::Info => {self.mode_stack.push(to_mode, message);}
+                            Mode::Error | Mode::Warning | Mode::Notify | Mode::Info => {self.push_mode(to_mode, message);}
                             Mode::Insert => {unreachable!()}    //should always pop to Insert, never push to Insert
                         }
                         //does this belong here, or in ui.rs?...    //by calling here, we only perform this calculation as needed, not on every editor run cycle
                         self.update_ui_data_mode();
                     }
+                    EditorAction::SelectRegister(c) => {
+                        assert!(self.mode() == Mode::Register);
+                        let name = c.to_string();
+                        if self.registers.contains(&name){
+                            self.selected_register = Some(name);
+                            self.update(Action::EditorAction(EditorAction::ModePop));
+                        }else{handle_message(self, DisplayMode::Error, &format!("register {} does not exist", name));}
+                    }
                     EditorAction::NoOpKeypress => {handle_message(self, UNHANDLED_KEYPRESS_DISPLAY_MODE, UNHANDLED_KEYPRESS);}
                     EditorAction::NoOpEvent => {handle_message(self, UNHANDLED_EVENT_DISPLAY_MODE, UNHANDLED_EVENT);}
                     EditorAction::Quit => {
@@ -1036,8 +1424,10 @@ impl Application{
                         //        Err(_) => {handle_message(self, FILE_SAVE_FAILED_DISPLAY_MODE, FILE_SAVE_FAILED);}
                         //    }
                         //}
+                        self.run_hooks(HookEvent::BufferSavePre, &self.buffer.file_path().unwrap_or_default());
                         match save(self){
                             Ok(()) => {
+                                self.emit(EditorEvent::Save(self.buffer.file_path().unwrap_or_default()));
                                 pop_to_insert(self);
                                 self.update_ui_data_document();
                             }
@@ -1108,9 +1498,18 @@ impl Application{
                         }
                     }
                     //this, in combination with copy, is the keyboard centric version of plan9's acme's 2-1 mouse chording
+                    EditorAction::EvaluateCommand(command) => {
+                        if self.mode() != Mode::Insert{pop_to_insert(self);}    //handle insert fallthrough
+                        let execute_result = execute_command(self, &command);
+                        if Result::is_err(&execute_result){
+                            let error = Result::unwrap_err(execute_result);
+                            handle_message(self, DisplayMode::Error, &error);
+                        }
+                    }
                     EditorAction::EvaluateClipboardAsCommand => {
                         if self.mode() != Mode::Insert{pop_to_insert(self);}    //handle insert fallthrough
-                        let execute_result = execute_command(self, &self.clipboard.clone());
+                        let command = self.registers.get(YANK_REGISTER).unwrap_or_default().to_string();
+                        let execute_result = execute_command(self, &command);
                         if Result::is_err(&execute_result){
                             let error = Result::unwrap_err(execute_result);
                             handle_message(self, DisplayMode::Error, &error);
@@ -1204,6 +1603,9 @@ impl Application{
                     SelectionAction::DecrementPrimarySelection => {(selections::decrement_primary_selection(&self.selections), SelectionToFollow::Primary)}
                     SelectionAction::Surround => {(selections::surround(&self.selections, &self.buffer, self.config.semantics.clone()), SelectionToFollow::Primary)},
                     SelectionAction::FlipDirection => {(self.selections.move_cursor_non_overlapping(&self.buffer, self.config.semantics.clone(), selection::flip_direction), SelectionToFollow::Primary)},
+                    SelectionAction::SelectNextMatch => {(select_match(&self.selections, self.registers.get(SEARCH_REGISTER).unwrap_or_default(), &self.buffer, self.config.semantics.clone(), MatchDirection::Next, false, count), SelectionToFollow::Primary)}
+                    SelectionAction::SelectPreviousMatch => {(select_match(&self.selections, self.registers.get(SEARCH_REGISTER).unwrap_or_default(), &self.buffer, self.config.semantics.clone(), MatchDirection::Previous, false, count), SelectionToFollow::Primary)}
+                    SelectionAction::AddNextMatch => {(select_match(&self.selections, self.registers.get(SEARCH_REGISTER).unwrap_or_default(), &self.buffer, self.config.semantics.clone(), MatchDirection::Next, true, count), SelectionToFollow::Primary)}
                 
                         //These may technically be distinct from the other selection actions, because they could be called from object mode, and would need to pop the mode stack after calling...
                         //TODO: SelectionAction::Word => {self.document.word()}
@@ -1277,6 +1679,7 @@ impl Application{
 
                 if self.buffer.read_only{handle_message(self, READ_ONLY_BUFFER_DISPLAY_MODE, READ_ONLY_BUFFER);}
                 else{
+                    let undone = matches!(edit_action, EditAction::Undo);
                     let result = match edit_action{
                         EditAction::InsertChar(c) => insert_string(self, &c.to_string(), self.config.use_hard_tab, self.config.tab_width, self.config.semantics.clone()),
                         EditAction::InsertNewline => insert_string(self, "\n", self.config.use_hard_tab, self.config.tab_width, self.config.semantics.clone()),
@@ -1291,6 +1694,7 @@ impl Application{
                     };
                     match result{
                         Ok(()) => {
+                            self.emit_text_events(undone);
                             //pop_to_insert(self);  //testing to see if this increments EDIT_ACTION_DISPLAY_MODE if selection_out_of_view
                             fn same_mode(mode: Mode, display_mode: DisplayMode) -> bool{
                                 //if mode == Mode::Error && display_mode == DisplayMode::Error{true}
@@ -1392,18 +1796,24 @@ impl Application{
                     UtilAction::MoveHome => text_box.move_cursor_line_start(),
                     UtilAction::MoveLeft => text_box.move_cursor_left(),
                     UtilAction::MoveRight => text_box.move_cursor_right(),
+                    //util bar copy/cut/paste always use the yank register. a register can only be selected from insert mode
                     UtilAction::Cut => {
-                        self.clipboard = text_box.buffer.slice(text_box.selection.range.start, text_box.selection.range.end).to_string();
+                        let _ = self.registers.set(YANK_REGISTER, &text_box.buffer.slice(text_box.selection.range.start, text_box.selection.range.end).to_string());
                         text_box.delete();
                     }
-                    UtilAction::Copy => {self.clipboard = text_box.buffer.slice(text_box.selection.range.start, text_box.selection.range.end).to_string();}
+                    UtilAction::Copy => {let _ = self.registers.set(YANK_REGISTER, &text_box.buffer.slice(text_box.selection.range.start, text_box.selection.range.end).to_string());}
                     UtilAction::Paste => {
                         if text_box.selection.is_extended(){
-                            text_box.buffer.apply_replace(&self.clipboard, &mut text_box.selection, self.config.semantics.clone());
+                            text_box.buffer.apply_replace(self.registers.get(YANK_REGISTER).unwrap_or_default(), &mut text_box.selection, self.config.semantics.clone());
                         }else{
-                            text_box.buffer.apply_insert(&self.clipboard, &mut text_box.selection, self.config.semantics.clone());
+                            text_box.buffer.apply_insert(self.registers.get(YANK_REGISTER).unwrap_or_default(), &mut text_box.selection, self.config.semantics.clone());
                         }
                     }
+                    //the prompt's width changes with the toggled flags
+                    UtilAction::ToggleSearchFlag(flag) => {
+                        self.search_options.find_flags.toggle(flag);
+                        self.layout();
+                    }
                     UtilAction::Accept => {
                         match self.mode(){
                             Mode::Goto => { //TODO: entering a very large number switches util bar text color to the valid state instead of the error state for some reason
@@ -1463,20 +1873,47 @@ impl Application{
                                 }
                             }
                             //Mode::Find | Mode::Split => self.update(Action::EditorAction(EditorAction::ModePop)),
-                            Mode::Find | Mode::Split => {
+                            Mode::Find | Mode::Split | Mode::Keep | Mode::Remove => {
                                 if self.ui.util_bar.utility_widget.text_box.text_is_valid{
+                                    if self.mode() == Mode::Find{
+                                        //flags are applied, so that next/previous match find the same matches
+                                        let regex = self.search_options.find_regex(&self.ui.util_bar.utility_widget.text_box.buffer.to_string());
+                                        let _ = self.registers.set(SEARCH_REGISTER, &regex);
+                                    }
                                     self.update(Action::EditorAction(EditorAction::ModePop));
                                 }else{
                                     handle_message(self, DisplayMode::Error, "invalid regex");
                                 }
                             }
-                            Mode::AddSurround | Mode::Insert | Mode::Object | Mode::View | Mode::Error | Mode::Warning | Mode::Notify | Mode::Info => {unreachable!()}
+                            //the preview is already applied, as a single undoable change
+                            Mode::Replace => {
+                                if self.ui.util_bar.utility_widget.text_box.text_is_valid{
+                                    let preview_applied = self.replace_preview_applied;
+                                    self.update(Action::EditorAction(EditorAction::ModePop));
+                                    if preview_applied{self.emit_text_events(false);}
+                                    else{handle_message(self, SAME_STATE_DISPLAY_MODE, SAME_STATE);}
+                                }else{
+                                    handle_message(self, DisplayMode::Error, "invalid regex, or no matches");
+                                }
+                            }
+                            Mode::AddSurround | Mode::Register | Mode::Insert | Mode::Object | Mode::View | Mode::Error | Mode::Warning | Mode::Notify | Mode::Info => {unreachable!()}
                         }
                         perform_follow_up_behavior = false;
                     }
                     UtilAction::Exit => {
                         match self.mode(){
-                            Mode::Find | Mode::Split => {
+                            Mode::Replace => {
+                                self.ui.util_bar.utility_widget.text_box.text_is_valid = false;
+                                revert_replace_preview(self);
+                                self.redo_stack = self.preserved_redo_stack.take().unwrap_or_default();
+                                self.checked_scroll_and_update(
+                                    &self.selections.primary.clone(), 
+                                    Application::update_ui_data_document, 
+                                    Application::update_ui_data_document
+                                );
+                                self.update(Action::EditorAction(EditorAction::ModePop));
+                            }
+                            Mode::Find | Mode::Split | Mode::Keep | Mode::Remove => {
                                 self.ui.util_bar.utility_widget.text_box.text_is_valid = false;
                                 self.selections = self.preserved_selections.clone().unwrap();   //shouldn't be called unless this value is Some()
                                 self.checked_scroll_and_update(
@@ -1513,7 +1950,8 @@ impl Application{
                         Mode::Warning |
                         Mode::Notify |
                         Mode::Info |
-                        Mode::AddSurround => {/*do nothing*/}
+                        Mode::AddSurround |
+                        Mode::Register => {/*do nothing*/}
                         Mode::Goto => {
                             // run text validity check
                             let mut is_numeric = true;
@@ -1531,7 +1969,7 @@ impl Application{
                                 Some(selections_before_search) => {
                                     match search_selection(
                                         selections_before_search, 
-                                        &self.ui.util_bar.utility_widget.text_box.buffer.to_string(),
+                                        &self.search_options.find_regex(&self.ui.util_bar.utility_widget.text_box.buffer.to_string()),
                                         &self.buffer, 
                                         self.config.semantics.clone()
                                     ){
@@ -1580,6 +2018,66 @@ impl Application{
                                 Application::update_ui_data_selections
                             );
                         }
+                        Mode::Keep | Mode::Remove => {
+                            match &self.preserved_selections{
+                                Some(selections_before_filter) => {
+                                    //an empty or partially typed regex is shown as invalid, rather than reported
+                                    let input = self.ui.util_bar.utility_widget.text_box.buffer.to_string();
+                                    let filtered = match regex::Regex::new(&input){
+                                        Ok(regex) if !input.is_empty() => filter_selections(
+                                            selections_before_filter, 
+                                            &regex, 
+                                            &self.buffer, 
+                                            self.config.semantics.clone(), 
+                                            self.mode() == Mode::Keep
+                                        ),
+                                        _ => Err(SelectionsError::NoSearchMatches)
+                                    };
+                                    match filtered{
+                                        Ok(new_selections) => {
+                                            self.selections = new_selections;
+                                            self.ui.util_bar.utility_widget.text_box.text_is_valid = true;
+                                        }
+                                        //every selection kept is still a valid filter
+                                        Err(SelectionsError::ResultsInSameState) => {
+                                            self.selections = selections_before_filter.clone();
+                                            self.ui.util_bar.utility_widget.text_box.text_is_valid = true;
+                                        }
+                                        Err(_) => {
+                                            self.selections = selections_before_filter.clone();
+                                            self.ui.util_bar.utility_widget.text_box.text_is_valid = false;
+                                        }
+                                    }
+                                }
+                                None => {/* maybe error?... */unreachable!()}
+                            }
+                            self.checked_scroll_and_update(
+                                &self.selections.primary.clone(), 
+                                Application::update_ui_data_document, 
+                                Application::update_ui_data_selections
+                            );
+                        }
+                        Mode::Replace => {
+                            revert_replace_preview(self);
+                            let valid = match parse_replace_input(&self.ui.util_bar.utility_widget.text_box.buffer.to_string()){
+                                Err(_) => false,
+                                Ok((regex, template)) => {
+                                    match replace_matches(self, &regex, &template){
+                                        Err(_) => false,
+                                        Ok(applied) => {
+                                            self.replace_preview_applied = applied;
+                                            true
+                                        }
+                                    }
+                                }
+                            };
+                            self.ui.util_bar.utility_widget.text_box.text_is_valid = valid;
+                            self.checked_scroll_and_update(
+                                &self.selections.primary.clone(), 
+                                Application::update_ui_data_document, 
+                                Application::update_ui_data_document
+                            );
+                        }
                         Mode::Command => {/*do nothing*/}
                     }
                 }
@@ -1604,6 +2102,7 @@ impl Application{
                 }else{/*path is not a file*/}
             }
         }
+        self.run_hooks(HookEvent::Startup, &self.buffer.file_path().unwrap_or_default());
 
         //TODO?: maybe handle input/9p threads here?...
 
@@ -1611,9 +2110,16 @@ impl Application{
             //derive User Interface from Application state
             self.layout();  //TODO: does update_layouts always need to be called, or can this be called only from actions that require it?...
             self.render(terminal)?;            
+            //the terminal backend is stdout(see main.rs), so OSC 52 sequences are written there directly
+            if let Some(sequence) = self.clipboard.take_osc52(){
+                let mut stdout = std::io::stdout();
+                let _ = stdout.write_all(sequence.as_bytes()).and_then(|_| stdout.flush());
+            }
             
             //update Application state
             self.handle_event(&event_rx)?;  //maybe create self.actions: Vec<Action>, and push to this
+            //events from 9p writes may not have passed through update
+            self.run_pending_hooks();
 
             //if input_thread_handle.is_finished(){
             //  match input_thread_handle.join(){
@@ -1662,6 +2168,262 @@ fn handle_application_error(app: &mut Application, e: ApplicationError){    //->
     }
 }
 
+/// Returns the content of a served file.
+fn read_served_file(app: &Application, file: ServedFile) -> Result<Vec<u8>, String>{
+    match file{
+        ServedFile::Root |
+        ServedFile::InterceptDirectory |
+        ServedFile::SettingsDirectory |
+        ServedFile::RegistersDirectory |
+        ServedFile::Register(_) |
+        ServedFile::BufferDirectory |
+        ServedFile::SelectionsDirectory |
+        ServedFile::SelectionsAllDirectory => Err(String::from("cannot read directory")),  //TODO: directory reads should return stat entries for each child
+        ServedFile::RegistersAdd |
+        ServedFile::RegistersDel => Ok(Vec::new()),
+        ServedFile::RegisterContent(id) => {
+            match app.registers.name(id).and_then(|name| app.registers.get(name)){
+                None => Err(String::from("register does not exist")),
+                Some(content) => Ok(content.as_bytes().to_vec())
+            }
+        }
+        ServedFile::Events => Ok(app.event_log.read(0, app.event_log.len())),
+        ServedFile::Setting(name) => option_value(app, name).map(|value| format!("{}\n", value).into_bytes()),
+        ServedFile::Command => {
+            match &app.command_result{
+                None => Ok(Vec::new()),
+                Some(Ok(())) => Ok(b"Ok\n".to_vec()),
+                Some(Err(e)) => Ok(format!("{}\n", e).into_bytes()),
+            }
+        }
+        ServedFile::InterceptRegistrations => {
+            Ok(app.intercepts.iter().map(|intercept| format!("{}\n", intercept)).collect::<String>().into_bytes())
+        }
+        ServedFile::InterceptPending => {
+            match &app.pending_intercept{
+                Some(event) => Ok(format!("{}\n", event).into_bytes()),
+                None => Ok(Vec::new())
+            }
+        }
+        ServedFile::BufferRawText => Ok(app.buffer.to_string().into_bytes()),
+        ServedFile::SelectionsAllRangeAddresses => {
+            let mut addresses = String::new();
+            for address in range_addresses(app){
+                addresses.push_str(&address);
+                addresses.push('\n');
+            }
+            Ok(addresses.into_bytes())
+        }
+        ServedFile::SelectionsAllContent => {
+            let mut contents = String::new();
+            for selection in app.selections.iter(){
+                contents.push_str(&escape_selection_content(&selection.to_string(&app.buffer)));
+                contents.push('\n');
+            }
+            Ok(contents.into_bytes())
+        }
+        ServedFile::SelectionsPrimaryIndex => Ok(format!("{}\n", app.selections.primary_selection_index()).into_bytes()),
+    }
+}
+/// Applies content written to a served file.
+fn write_served_file(app: &mut Application, file: ServedFile, content: &str) -> Result<(), String>{
+    match file{
+        ServedFile::Root |
+        ServedFile::InterceptDirectory |
+        ServedFile::SettingsDirectory |
+        ServedFile::RegistersDirectory |
+        ServedFile::Register(_) |
+        ServedFile::BufferDirectory |
+        ServedFile::SelectionsDirectory |
+        ServedFile::SelectionsAllDirectory => Err(String::from("cannot write to directory")),
+        ServedFile::RegistersAdd => app.registers.add(content.trim()),
+        ServedFile::RegistersDel => app.registers.remove(content.trim()),
+        ServedFile::RegisterContent(id) => {
+            match app.registers.name(id).map(|name| name.to_string()){
+                None => Err(String::from("register does not exist")),
+                Some(name) => app.registers.set(&name, content)
+            }
+        }
+        ServedFile::Events => Err(format!("{} is read only", file.name(&app.registers))),
+        ServedFile::Command => execute_served_command(app, content),
+        ServedFile::Setting(name) => set_option(app, name, content.trim()),
+        ServedFile::InterceptRegistrations => {
+            let mut intercepts: Vec<Intercept> = Vec::new();
+            for line in content.lines().filter(|line| !line.trim().is_empty()){
+                let intercept = line.parse()?;
+                if !intercepts.contains(&intercept){intercepts.push(intercept);}
+            }
+            app.intercepts = intercepts;
+            Ok(())
+        }
+        ServedFile::InterceptPending => {
+            if app.pending_intercept.is_none(){return Err(String::from("no intercepted event awaiting acknowledgement"));}
+            app.intercept_acknowledgement = Some(content.parse()?);
+            Ok(())
+        }
+        ServedFile::BufferRawText => {
+            match replace_buffer_text(app, content, app.config.semantics.clone()){
+                Err(ApplicationError::ReadOnlyBuffer) => Err(String::from(READ_ONLY_BUFFER)),
+                Err(_) => Err(String::from(SAME_STATE)),
+                Ok(()) => {
+                    app.emit_text_events(false);
+                    app.checked_scroll_and_update(
+                        &app.selections.primary.clone(), 
+                        Application::update_ui_data_document, 
+                        Application::update_ui_data_document
+                    );
+                    Ok(())
+                }
+            }
+        }
+        ServedFile::SelectionsAllRangeAddresses => {
+            let mut new_selections = Vec::new();
+            for address in content.lines().filter(|line| !line.trim().is_empty()){
+                let (start, end) = match address.trim().split_once(','){
+                    None => return Err(format!("invalid range address: {address}")),
+                    Some((start, end)) => (address_to_char_index(start, &app.buffer)?, address_to_char_index(end, &app.buffer)?)
+                };
+                if start > end{return Err(format!("range start must not be after range end: {address}"));}
+                let extension_direction = match app.config.semantics{
+                    CursorSemantics::Bar => if start == end{None}else{Some(selection::Direction::Forward)},
+                    CursorSemantics::Block => if app.buffer.next_grapheme_char_index(start) == end{None}else{Some(selection::Direction::Forward)},
+                };
+                //not using Selection::new_from_range, because that asserts invariants hold. we want to error instead
+                let mut new_selection = app.selections.primary.clone();
+                new_selection.range = Range::new(start, end);
+                new_selection.extension_direction = extension_direction;
+                new_selection.stored_line_offset = None;
+                if let Err(e) = new_selection.invariants_hold(&app.buffer, app.config.semantics.clone()){
+                    return Err(format!("invalid selection {address}: {e:?}"));
+                }
+                new_selections.push(new_selection);
+            }
+            if new_selections.is_empty(){return Err(String::from("at least one range address must be provided"));}
+            let primary_index = app.selections.primary_selection_index().min(new_selections.len().saturating_sub(1));
+            app.selections = Selections::new(new_selections, primary_index, &app.buffer, app.config.semantics.clone());
+            app.checked_scroll_and_update(
+                &app.selections.primary.clone(), 
+                Application::update_ui_data_document, 
+                Application::update_ui_data_selections
+            );
+            Ok(())
+        }
+        ServedFile::SelectionsAllContent => {
+            //same format as read. a single line replaces the text in every selection
+            let lines: Vec<&str> = content.strip_suffix('\n').unwrap_or(content).split('\n').collect();
+            let mut replacements = Vec::with_capacity(lines.len());
+            for line in lines{replacements.push(unescape_selection_content(line)?);}
+            if replacements.len() == 1{
+                replacements = vec![replacements.remove(0); app.selections.count()];
+            }else if replacements.len() != app.selections.count(){
+                return Err(format!("expected 1 line, or 1 line per selection({}), got {}", app.selections.count(), replacements.len()));
+            }
+            //writing back what was read changes nothing
+            if app.selections.iter().zip(&replacements).all(|(selection, replacement)| selection.to_string(&app.buffer) == *replacement){
+                return Ok(());
+            }
+            match replace_selections(app, &replacements, app.config.semantics.clone()){
+                Err(ApplicationError::ReadOnlyBuffer) => Err(String::from(READ_ONLY_BUFFER)),
+                Err(_) => Err(String::from(INVALID_INPUT)),
+                Ok(()) => {
+                    app.emit_text_events(false);
+                    app.checked_scroll_and_update(
+                        &app.selections.primary.clone(), 
+                        Application::update_ui_data_document, 
+                        Application::update_ui_data_document
+                    );
+                    Ok(())
+                }
+            }
+        }
+        ServedFile::SelectionsPrimaryIndex => {
+            let index = match content.trim().parse::<usize>(){
+                Err(e) => return Err(format!("{e}")),
+                Ok(index) => index
+            };
+            if index >= app.selections.count(){return Err(format!("primary index must be less than selection count: {}", app.selections.count()));}
+            app.selections = Selections::new(app.selections.flatten(), index, &app.buffer, app.config.semantics.clone());
+            app.checked_scroll_and_update(
+                &app.selections.primary.clone(), 
+                Application::update_ui_data_document, 
+                Application::update_ui_data_selections
+            );
+            Ok(())
+        }
+    }
+}
+/// Whether `fs_request` can be handled while an intercepted event awaits acknowledgement. Requests that only look up files
+/// can, as can any request for the intercept files.
+fn serves_intercept(fs_request: &FsRequest) -> bool{
+    let qid_path = match fs_request{
+        FsRequest::Attach{..} | FsRequest::Walk{..} | FsRequest::Stat{..} => return true,
+        FsRequest::Open{qid_path, ..} | FsRequest::Read{qid_path, ..} | FsRequest::Write{qid_path, ..} | FsRequest::Clunk{qid_path, ..} => *qid_path
+    };
+    matches!(ServedFile::from_qid_path(qid_path), Some(ServedFile::InterceptDirectory | ServedFile::InterceptRegistrations | ServedFile::InterceptPending))
+}
+/// Executes a command written to the served command file, handling its result as if typed in command mode.
+fn execute_served_command(app: &mut Application, command: &str) -> Result<(), String>{
+    // writes from the shell, like `echo`, usually end in a newline that isn't part of the command
+    let result = execute_command(app, command.trim_end_matches('\n'));
+    if let Err(e) = &result{handle_message(app, DisplayMode::Error, e);}
+    app.command_result = Some(result.clone());
+    result
+}
+/// `<start address>,<end address>` of each selection's range, in order.
+fn range_addresses(app: &Application) -> Vec<String>{
+    app.selections.iter()
+        .map(|selection| format!("{},{}", char_index_to_address(selection.range.start, &app.buffer), char_index_to_address(selection.range.end, &app.buffer)))
+        .collect()
+}
+/// Escapes backslashes and newlines in `text`, so that a selection's content fits on one line.
+fn escape_selection_content(text: &str) -> String{
+    text.replace('\\', "\\\\").replace('\n', "\\n")
+}
+/// Reverses [`escape_selection_content`]. Errors on a backslash not followed by another backslash, or `n`.
+fn unescape_selection_content(line: &str) -> Result<String, String>{
+    let mut text = String::new();
+    let mut chars = line.chars();
+    while let Some(char) = chars.next(){
+        match char{
+            '\\' => {
+                match chars.next(){
+                    Some('\\') => text.push('\\'),
+                    Some('n') => text.push('\n'),
+                    _ => return Err(format!("invalid escape in {:?}. use \\\\ for a backslash, and \\n for a newline", line))
+                }
+            }
+            _ => text.push(char)
+        }
+    }
+    Ok(text)
+}
+/// Formats a char index as a 1-based `<line>:<column>` address.
+fn char_index_to_address(char_index: usize, buffer: &Buffer) -> String{
+    //block cursors may extend one past buffer end
+    let line = buffer.char_to_line(char_index.min(buffer.len_chars()));
+    let column = char_index.saturating_sub(buffer.line_to_char(line));
+    format!("{}:{}", line + 1, column + 1)
+}
+/// Parses a 1-based `<line>:<column>` address into a char index.
+fn address_to_char_index(address: &str, buffer: &Buffer) -> Result<usize, String>{
+    let (line, column) = match address.trim().split_once(':'){
+        None => return Err(format!("invalid address: {address}")),
+        Some((line, column)) => (line.parse::<usize>(), column.parse::<usize>())
+    };
+    match (line, column){
+        (Ok(line), Ok(column)) if line > 0 && column > 0 => {
+            let line = line - 1;
+            let column = column - 1;
+            if line >= buffer.len_lines(){return Err(format!("line out of bounds: {address}"));}
+            //block cursors may extend one past buffer end
+            let max_column = if line == buffer.len_lines().saturating_sub(1){buffer.line_width_chars(line, true) + 1}else{buffer.line_width_chars(line, true)};
+            if column > max_column{return Err(format!("column out of bounds: {address}"));}
+            Ok(buffer.line_to_char(line) + column)
+        }
+        _ => Err(format!("invalid address: {address}"))
+    }
+}
+
 /*
     built-in commands vs external programs
     store binaries for editor specific external programs in some associated directory (/edit/bin/<program>)
@@ -1728,104 +2490,395 @@ fn handle_application_error(app: &mut Application, e: ApplicationError){    //->
 //  |       | yes                   | yes                       | no                    | yes                                       | pipe
 //  >       | yes                   | no                        | yes                   | yes                                       | redirect
 //  <       | no                    | yes                       | no                    | yes                                       | insert
-//  !       | no                    | no                        | no                    | ?                                         | do?
+//  !       | no                    | no                        | no                    | yes                                       | do?
 //
-struct CommandParser<'a>{rest: &'a str} //TODO: rest: Option<&'a str>
-impl<'a> CommandParser<'a> {
-    fn new(input: &'a str) -> Self{
-        Self{rest: input.trim_start()}
+/// Runs `command` to completion, returning its stdout if it succeeds. For shell commands whose output is needed before continuing,
+/// such as `%sh{}` expansions. [`start_shell_command`] is used for those the user may wait on.
+fn run_shell_command(app: &Application, stdin: Option<String>, command: &str) -> Result<String, String>{
+    run_shell_process(command, &shell_environment(app), shell_working_directory(&app.buffer).as_deref(), stdin, app.shell.timeout, &AtomicBool::new(false))
+}
+/// Runs `command` once per entry in `stdins`, then handles the outputs as `kind` says. 
+/// Runs happen on a worker thread, which sends their outputs back to the event loop, if there is one. 
+/// Otherwise, they complete before this returns.
+fn start_shell_command(app: &mut Application, command: &str, stdins: Vec<Option<String>>, kind: ShellCommandKind) -> Result<(), String>{
+    if let Some(running) = app.shell.running(){
+        return Err(format!("shell command \"{}\" is still running. press esc to cancel it", running.command));
     }
-    fn next(&mut self) -> Option<&'a str>{  //TODO: could handle lexing values in "" here...
-        if self.rest.is_empty(){
-            return None;
+    let environment_variables = shell_environment(app);
+    let working_directory = shell_working_directory(&app.buffer);
+    let timeout = app.shell.timeout;
+    match app.shell.event_sender(){
+        None => {
+            let outputs = run_shell_processes(command, &environment_variables, working_directory.as_deref(), stdins, timeout, &AtomicBool::new(false));
+            finish_shell_command(app, kind, outputs)
         }
-
-        let mut split = self.rest.splitn(2, char::is_whitespace);
-        let word = split.next().unwrap();
-        self.rest = split.next().unwrap_or("").trim_start();
-        Some(word)
+        Some(event_tx) => {
+            let cancel = app.shell.start(command, kind);
+            let command = command.to_string();
+            std::thread::spawn(move ||{
+                let outputs = run_shell_processes(&command, &environment_variables, working_directory.as_deref(), stdins, timeout, &cancel);
+                //the receiver is only gone if the editor is quitting
+                let _ = event_tx.send(Event::ShellCommandFinished(outputs));
+            });
+            app.update_ui_data_shell_command();
+            Ok(())
+        }
+    }
+}
+/// Handles the outputs of a shell command started with [`start_shell_command`].
+fn finish_shell_command(app: &mut Application, kind: ShellCommandKind, outputs: Vec<Result<String, String>>) -> Result<(), String>{
+    match kind{
+        ShellCommandKind::Pipe{selections, selected_texts} => {
+            //the selections may have moved, or their text changed, while the command ran. replacing them now would clobber something else
+            let current_texts: Vec<String> = app.selections.iter().map(|selection| selection.to_string(&app.buffer)).collect();
+            if app.selections != selections || current_texts != selected_texts{
+                return Err(String::from("selections changed while shell command ran. its output was discarded"));
+            }
+            replace_selections_with_outputs(app, &selected_texts, outputs)?;
+        }
+        ShellCommandKind::Redirect => {
+            let output = outputs.into_iter().next().unwrap_or_else(|| Ok(String::new()))?;
+            //TODO: open new edit window with output in temp buffer, don't display in diagnostic panel
+            if output.trim().is_empty(){
+                handle_message(app, DisplayMode::Notify, "shell command succeeded with no output");
+            }else{
+                handle_message(app, DisplayMode::Info, output.trim_end());
+            }
+        }
+        ShellCommandKind::Bang => {
+            //output is discarded. only the exit status is reported
+            outputs.into_iter().next().unwrap_or_else(|| Ok(String::new()))?;
+            handle_message(app, DisplayMode::Notify, "shell command exited successfully");
+        }
+        ShellCommandKind::Edit{command, text, selections} => {
+            //the sam command's changes are relative to the text it started with
+            if app.selections != selections || app.buffer.to_string() != text{
+                return Err(String::from("buffer or selections changed while shell command ran. its output was discarded"));
+            }
+            let mut outputs = outputs.into_iter();
+            let outcome = sam::execute(&command, &text, &structural_edit_dots(app), &mut |_, _|{
+                outputs.next().unwrap_or_else(|| Err(String::from("shell command produced too few outputs")))
+            })?;
+            apply_structural_edit(app, outcome)?;
+        }
+        ShellCommandKind::Run => {
+            match outputs.into_iter().next().unwrap_or_else(|| Ok(String::new())){
+                Err(error) => {
+                    if error.is_empty(){
+                        return Err(String::from("shell command failed with empty error string"));
+                    }else{
+                        return Err(error);
+                    }
+                }
+                Ok(output) => {
+                    if output.is_empty(){
+                        handle_message(app, DisplayMode::Warning, "shell command succeeded with empty output string");
+                    }else{
+                        //TODO: open new edit window with output in temp buffer, don't display in diagnostic panel
+                        handle_message(app, DisplayMode::Info, &format!("unpiped command output \"{}\" will be sent to new window(not yet implemented)", output));
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+/// Runs `command` once per entry in `stdins`, in parallel, returning each run's result in the same order.
+fn run_shell_processes(command: &str, environment_variables: &std::collections::HashMap<String, String>, working_directory: Option<&Path>, stdins: Vec<Option<String>>, timeout: Option<std::time::Duration>, cancel: &AtomicBool) -> Vec<Result<String, String>>{
+    let parallelism = std::thread::available_parallelism().map_or(1, std::num::NonZeroUsize::get);
+    let mut outputs = Vec::with_capacity(stdins.len());
+    for stdins in stdins.chunks(parallelism){
+        std::thread::scope(|scope|{
+            let runs: Vec<_> = stdins.iter()
+                .map(|stdin|{
+                    let stdin = stdin.clone();
+                    scope.spawn(move || run_shell_process(command, environment_variables, working_directory, stdin, timeout, cancel))
+                })
+                .collect();
+            for run in runs{
+                outputs.push(run.join().unwrap_or_else(|_| Err(String::from("shell command thread panicked"))));
+            }
+        });
+    }
+    outputs
+}
+/// Runs `command` with [`SHELL`], returning its stdout if it succeeds, or its stderr if it fails.
+/// The command is killed if it runs longer than `timeout`, or once `cancel` is set.
+/// Takes the environment separately from the application, so that processes can run on other threads.
+fn run_shell_process(command: &str, environment_variables: &std::collections::HashMap<String, String>, working_directory: Option<&Path>, stdin: Option<String>, timeout: Option<std::time::Duration>, cancel: &AtomicBool) -> Result<String, String>{
+    //let output = match std::process::Command::new("bash").arg("-c").arg(command).output(){
+    //    Err(e) => return Err(format!("{e}")),
+    //    Ok(idk) => idk,
+    //};
+    //let output = std::process::Command::new("sh"/*"bash"*/)
+    //    .arg("-c")
+    //    .arg(command)
+    //    //.env("MY_VAR", "environment variable content")
+    //    .envs(&environment_variables)
+    //    //.stdout(std::process::Stdio::piped()) //i think this is the default with .output()
+    //    //.stderr(std::process::Stdio::piped()) //i think this is the default with .output()
+    //    .output()
+    //    .expect("failed to execute process");
+    let mut shell = std::process::Command::new(SHELL);
+    shell.arg(SHELL_COMMAND_FLAG)
+        .arg(command)
+        //.env("MY_VAR", "environment variable content")
+        .envs(environment_variables)
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        //the shell leads its own process group, so killing the group also kills any processes it started(each side of a pipeline, for instance)
+        .process_group(0);
+    //temp buffers have no file path, so they inherit the terminal's current dir
+    if let Some(working_directory) = working_directory{shell.current_dir(working_directory);}
+    let mut child_process = match shell.spawn(){
+        Err(error) => return Err(format!("failed to execute process: {}", error)),
+        Ok(child_process) => child_process
+    };
+    //TODO: use std::process::Command::new(command)
+    //then get PATH env var, and pass it to .env() to call commands directly with resolved paths, skipping "sh" invocation
+    //stdin is written from another thread, so a command filling its stdout pipe before reading all of its stdin can't deadlock us
+    let stdin_writer = match (stdin, child_process.stdin.take()){
+        (Some(stdin_string), Some(mut stdin)) => Some(std::thread::spawn(move ||{
+            //a command may exit without reading all of its stdin(a failing clipboard tool, for instance). its exit status is what gets reported
+            let _ = stdin.write_all(stdin_string.as_bytes());
+        })),
+        _ => None
+    };
+    //stdout and stderr are read from other threads too, so that the command never blocks on a full pipe while we wait on it
+    fn read_to_end(pipe: Option<impl std::io::Read + Send + 'static>) -> std::thread::JoinHandle<Vec<u8>>{
+        std::thread::spawn(move ||{
+            let mut bytes = Vec::new();
+            if let Some(mut pipe) = pipe{let _ = pipe.read_to_end(&mut bytes);}
+            bytes
+        })
+    }
+    let stdout_reader = read_to_end(child_process.stdout.take());
+    let stderr_reader = read_to_end(child_process.stderr.take());
+    let start = std::time::Instant::now();
+    let mut status = None;
+    //the shell exiting isn't enough. a process it started in the background(`cmd &`) may still hold the pipes open,
+    //so the deadline and cancellation keep applying until they are drained
+    while status.is_none() || !stdout_reader.is_finished() || !stderr_reader.is_finished(){
+        if status.is_none(){
+            match child_process.try_wait(){
+                Err(error) => return Err(format!("failed to execute process: {}", error)),
+                Ok(exit_status) => status = exit_status,
+            }
+            if status.is_some(){continue;}
+        }
+        let stop_reason = if cancel.load(AtomicOrdering::Relaxed){
+            Some(String::from("shell command cancelled"))
+        }else{
+            timeout.filter(|timeout| start.elapsed() >= *timeout).map(|timeout| format!("shell command timed out after {:?}", timeout))
+        };
+        if let Some(stop_reason) = stop_reason{
+            //the pipe threads are left to finish on their own, once every process holding the pipes has exited
+            kill_process_group(&mut child_process);
+            return Err(stop_reason);
+        }
+        std::thread::sleep(SHELL_COMMAND_POLL_INTERVAL);
+    }
+    let status = status.expect("loop only ends once the shell has exited");
+    //a background process may hold stdin without reading it, so the writer isn't joined. it finishes once the pipe closes
+    drop(stdin_writer);
+    let stdout = stdout_reader.join().unwrap_or_default();
+    let stderr = stderr_reader.join().unwrap_or_default();
+    if status.success(){
+        Ok(String::from_utf8_lossy(&stdout).to_string())
+    }else{
+        //a command may fail without writing to stderr. its exit status is all there is to report
+        let stderr = String::from_utf8_lossy(&stderr).trim_end().to_string();
+        if stderr.is_empty(){Err(format!("shell command failed with {}", status))}
+        else{Err(stderr)}
     }
-    fn rest(&self) -> &'a str{self.rest}    //TODO: maybe should return Option<&'a str>, returning None if self.rest.is_empty()
+}
+/// Kills `child_process`, and every process in the process group it leads.
+fn kill_process_group(child_process: &mut std::process::Child){
+    let killed_group = std::process::Command::new("kill")
+        .args(["-s", "KILL", "--", &format!("-{}", child_process.id())])
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success());
+    if !killed_group{let _ = child_process.kill();}
+    //reap the child, so it doesn't linger as a zombie
+    let _ = child_process.wait();
+}
+/// Environment variables describing the editor's state, so that shell commands know what they are operating on.
+fn shell_environment(app: &Application) -> std::collections::HashMap<String, String>{
+    let mut environment_variables = std::collections::HashMap::new();
+    //every option is exported, because the command may run a script that reads them, without naming them in the command text
+    for name in SETTINGS{
+        if let Ok(value) = option_value(app, name){
+            environment_variables.insert(user_options::environment_variable_name(name), value);
+        }
+    }
+    for (name, value) in app.user_options.iter(){
+        environment_variables.insert(user_options::environment_variable_name(name), value.to_string());
+    }
+    let cursor_position = app.selections.primary.selection_to_selection2d(&app.buffer, app.config.semantics.clone()).head().clone();
+    let context = [
+        ("EDIT_FILE_PATH", app.buffer.file_path().unwrap_or_default()),
+        ("EDIT_BUFFER_NAME", app.buffer.file_name().unwrap_or_default()),
+        ("EDIT_SELECTION_COUNT", app.selections.count().to_string()),
+        ("EDIT_PRIMARY_INDEX", app.selections.primary_selection_index().to_string()),
+        ("EDIT_CURSOR_LINE", (cursor_position.y + 1).to_string()),
+        ("EDIT_CURSOR_COLUMN", (cursor_position.x + 1).to_string()),
+        ("EDIT_MODE", format!("{:?}", app.mode())),
+        ("EDIT_SELECTIONS", range_addresses(app).join(" ")),
+    ];
+    for (name, value) in context{
+        environment_variables.insert(name.to_string(), value);
+    }
+    if let Some(instance) = &app.instance{
+        environment_variables.insert(String::from("EDIT_INSTANCE_ID"), instance.id.to_string());
+        environment_variables.insert(String::from("EDIT_SOCKET"), instance.socket_path.to_string_lossy().to_string());
+    }
+    environment_variables
+}
+/// Shell commands run in the directory of the buffer's file, or in the buffer's directory when it lists a directory's contents.
+fn shell_working_directory(buffer: &Buffer) -> Option<PathBuf>{
+    let path = buffer.file_path.as_ref()?;
+    if path.is_dir(){Some(path.clone())}
+    else{path.parent().filter(|parent| parent.is_dir()).map(Path::to_path_buf)}
 }
 
 //at the extreme, i think every action could end up being a command
 //in that sense, the editor is just a command parser, with command specific response behavior
+/// Parses `command` into one or more commands(see [`parse_command`]), and executes them in order.
+/// Expansions are evaluated right before the command containing them executes, so they see the effects of earlier commands.
+//TODO: consider how to handle a failed command in a list of commands. for now, we just error on first failed command
 fn execute_command(app: &mut Application, command: &str) -> Result<(), String>{ //-> Result<Option<Action>, String>?
-    //TODO: this should prob be threaded/async
-    fn run_shell_command(app: &Application, stdin: Option<String>, command: &str) -> Result<String, String>{
-        //TODO: if temp file(has no file path), get terminal current_dir, and pass that as the current dir for command. (and later plumber...)
-        let mut environment_variables = std::collections::HashMap::new();
-        //environment_variables.insert("MY_VAR", "environment variable content");
-        //TODO: maybe options/settings should start with $EDIT_SETTING_
-        if command.contains("$EDIT_OPT_SHOW_LINE_NUMBERS"){  //env vars can also be lower case...
-            environment_variables.insert("EDIT_OPT_SHOW_LINE_NUMBERS", app.ui.document_viewport.line_number_widget.show.to_string());
-        }
-        if command.contains("$EDIT_OPT_SHOW_STATUS_BAR"){
-            environment_variables.insert("EDIT_OPT_SHOW_STATUS_BAR", app.ui.status_bar.show.to_string());
-        }
-        //let output = match std::process::Command::new("bash").arg("-c").arg(command).output(){
-        //    Err(e) => return Err(format!("{e}")),
-        //    Ok(idk) => idk,
-        //};
-        //let output = std::process::Command::new("sh"/*"bash"*/)
-        //    .arg("-c")
-        //    .arg(command)
-        //    //.env("MY_VAR", "environment variable content")
-        //    .envs(&environment_variables)
-        //    //.stdout(std::process::Stdio::piped()) //i think this is the default with .output()
-        //    //.stderr(std::process::Stdio::piped()) //i think this is the default with .output()
-        //    .output()
-        //    .expect("failed to execute process");
-        let mut child_process = std::process::Command::new(SHELL)
-            .arg(SHELL_COMMAND_FLAG)
-            .arg(command)
-            //.env("MY_VAR", "environment variable content")
-            .envs(&environment_variables)
-            .stdin(std::process::Stdio::piped())
-            .stdout(std::process::Stdio::piped())
-            .stderr(std::process::Stdio::piped())
-            .spawn()
-            .expect("failed to execute process");
-        //TODO: use std::process::Command::new(command)
-        //then get PATH env var, and pass it to .env() to call commands directly with resolved paths, skipping "sh" invocation
-        if let Some(stdin_string) = stdin{
-            if let Some(stdin) = child_process.stdin.as_mut(){
-                stdin.write_all(stdin_string.as_bytes()).expect("failed to write to child process' stdin")
-            }
-        }
-        let output = child_process.wait_with_output().expect("failed to execute process");
-        if output.status.success(){
-            Ok(String::from_utf8_lossy(&output.stdout).to_string())
-        }else{
-            Err(String::from_utf8_lossy(&output.stderr).to_string())
+    if command.trim().is_empty(){return Err(String::from("cannot execute empty command string"));}
+    let _ = app.registers.set(COMMAND_REGISTER, command);
+    evaluate_commands(app, command)
+}
+/// Parses and executes each command in `command`, stopping at the first failure. Each command is parsed once the commands
+/// before it have executed. Shell commands are handed the raw text of the rest of their line instead, so that shell syntax
+/// (';', '#', quotes, '%') reaches the shell unchanged.
+fn evaluate_commands(app: &mut Application, command: &str) -> Result<(), String>{
+    evaluate_commands_with_arguments(app, command, None)
+}
+/// Same as [`evaluate_commands`], with `arguments` bound to positional parameters(see [`substitute_parameters`]), for the 
+/// body of a user command.
+fn evaluate_commands_with_arguments(app: &mut Application, command: &str, arguments: Option<&[String]>) -> Result<(), String>{
+    let mut remaining = command;
+    while let Some((mut words, start, end)) = parse_first_command(remaining)?{
+        let line_end = remaining[start..].find('\n').map_or(remaining.len(), |offset| start + offset);
+        if let Some(arguments) = arguments{
+            for word in words.iter_mut(){
+                //arguments to a shell expansion are shell quoted, so each remains a single shell word
+                let quote: fn(&str) -> String = if word.word_type == WordType::Expansion(ExpansionType::Shell){shell_quote}else{str::to_string};
+                word.content = substitute_parameters(&word.content, arguments, quote)?;
+            }
+        }
+        if let Some(prefix) = shell_command_prefix(app, &words){
+            let line = remaining[start..line_end].trim();
+            let shell_command = if prefix.is_empty(){line}else{line.split_once(char::is_whitespace).map_or("", |(_, rest)| rest.trim_start())};
+            match arguments{
+                None => execute_shell_command(app, prefix, shell_command)?,
+                Some(arguments) => execute_shell_command(app, prefix, &substitute_parameters(shell_command, arguments, shell_quote)?)?
+            }
+            remaining = &remaining[line_end..];
+            continue;
+        }
+        let mut resolved_words = Vec::new();
+        for word in words{
+            resolved_words.push(resolve_word(app, word)?);
         }
+        execute_words(app, &resolved_words)?;
+        remaining = &remaining[end..];
     }
-
-    let mut parser = CommandParser::new(command);
-    let first = match parser.next(){
+    Ok(())
+}
+/// Names of the commands handled by [`execute_words`]. Anything else that isn't a user defined command, or an action's name,
+/// is run in the shell.
+const COMMAND_NAMES: &[&str] = &[
+    "diagnostic", "toggle_line_numbers", "ln", "toggle_status_bar", "sb", "quit", "q", "quit!", "q!", "write", "w", 
+    "search", "search_selection", "replace", "Edit", "keep_matching", "remove_matching", "split_selection", 
+    "add_keybind", "remove_keybind", "list_keybinds", "add_command", "add_option", "add_hook", "remove_hooks", "set"
+];
+/// If `words` is a shell command, returns its prefix: "|", "<", ">" or "!", or "" when it is run as is because its first
+/// word isn't a command. A command named by an expansion can't be known until it is resolved, so isn't treated as one here.
+fn shell_command_prefix(app: &Application, words: &[Word]) -> Option<&'static str>{
+    let first = words.first()?;
+    if first.word_type != WordType::Unquoted{return None;}
+    let name = first.content.as_str();
+    if let Some(prefix) = ["|", "<", ">", "!"].into_iter().find(|prefix| *prefix == name){return Some(prefix);}
+    if COMMAND_NAMES.contains(&name) || app.user_commands.get(name).is_some() || Action::from_command_name(name).is_some(){
+        return None;
+    }
+    Some("")
+}
+/// `text` single quoted, so the shell reads it as a single word.
+fn shell_quote(text: &str) -> String{
+    to_shell_string(&[Word::quoted(text.to_string())])
+}
+/// Runs `command` in the shell, as the command with `prefix` does(see [`shell_command_prefix`]).
+fn execute_shell_command(app: &mut Application, prefix: &str, command: &str) -> Result<(), String>{
+    match prefix{
+        "|" => pipe_selections(app, command, true),
+        ">" => {
+            //every selection's text is sent, one selection per line. the buffer is left unmodified
+            let stdin = app.selections.iter().map(|selection| selection.to_string(&app.buffer)).collect::<Vec<String>>().join("\n");
+            start_shell_command(app, command, vec![Some(stdin)], ShellCommandKind::Redirect)
+        }
+        "<" => pipe_selections(app, command, false),
+        "!" => start_shell_command(app, command, vec![None], ShellCommandKind::Bang),
+        _ => start_shell_command(app, command, vec![None], ShellCommandKind::Run)
+    }
+}
+/// Evaluates an expansion into a quoted word holding its result. Other words are returned unchanged.
+fn resolve_word(app: &Application, word: Word) -> Result<Word, String>{
+    match word.word_type{
+        WordType::Unquoted | WordType::Quoted => Ok(word),
+        WordType::Expansion(ExpansionType::Option) => option_value(app, &word.content).map(Word::quoted),
+        WordType::Expansion(ExpansionType::Register) => {
+            match app.registers.get(&word.content){
+                None => Err(format!("register {} does not exist", word.content)),
+                Some(content) => Ok(Word::quoted(content.to_string()))
+            }
+        }
+        WordType::Expansion(ExpansionType::Shell) => {
+            match run_shell_command(app, None, &word.content){
+                Err(error) if error.is_empty() => Err(String::from("shell command failed with empty error string")),
+                Err(error) => Err(error),
+                //trailing newlines are stripped, same as shell command substitution
+                Ok(output) => Ok(Word::quoted(output.trim_end_matches('\n').to_string()))
+            }
+        }
+        WordType::Expansion(ExpansionType::Value) => Err(String::from("value expansion unimplemented")),
+    }
+}
+/// Word contents joined by spaces. Used for arguments that may contain whitespace, such as messages and regexes.
+fn join_words(words: &[Word]) -> String{
+    words.iter().map(|word| word.content.as_str()).collect::<Vec<&str>>().join(" ")
+}
+/// Executes a single parsed command, with expansions already resolved.
+fn execute_words(app: &mut Application, words: &[Word]) -> Result<(), String>{
+    let (first, args) = match words.split_first(){
         None => return Err(String::from("cannot execute empty command string")),
-        Some(first) => first
+        Some((first, args)) => (first.content.as_str(), args)
     };
     match first{
         //"echo" => {       //don't want to use "echo" because it would clash with existing echo program
         "diagnostic" => {   //diagnostic may become an external gui program
-            let args = parser.rest();
-            let mut parser = CommandParser::new(args);
-            let optional_diagnostic_mode = match parser.next(){
+            let optional_diagnostic_mode = match args.first(){
                 None => return Err(String::from("too few arguments: diagnostic [diagnostic_mode] <message>")),
-                Some(mode) => mode
+                Some(mode) => mode.content.as_str()
             };
             let (mode, message) = match optional_diagnostic_mode{
-                "--error" => (DisplayMode::Error, parser.rest()),
-                "--warning" => (DisplayMode::Warning, parser.rest()),
-                "--notify" => (DisplayMode::Notify, parser.rest()),
-                "--info" => (DisplayMode::Info, parser.rest()),
-                _ => (DisplayMode::Info, args) //default to info mode, and display all args as message
+                "--error" => (DisplayMode::Error, join_words(&args[1..])),
+                "--warning" => (DisplayMode::Warning, join_words(&args[1..])),
+                "--notify" => (DisplayMode::Notify, join_words(&args[1..])),
+                "--info" => (DisplayMode::Info, join_words(&args[1..])),
+                _ => (DisplayMode::Info, join_words(args)) //default to info mode, and display all args as message
             };
             if message.trim().is_empty(){
                 return Err(String::from("too few arguments: diagnostic [diagnostic_mode] <message>"));
             }
-            handle_message(app, mode, message);
+            handle_message(app, mode, &message);
         }
 
         //"term" | "t" => app.action(Action::EditorAction(EditorAction::OpenNewTerminalWindow)),
@@ -1842,12 +2895,15 @@ fn execute_command(app: &mut Application, command: &str) -> Result<(), String>{
         "write" | "w" => app.update(Action::EditorAction(EditorAction::Save)),
 
         "search" => {
-            let regex = parser.rest();
+            let (flags, args) = SearchFlags::parse(args)?;
+            let regex = flags.regex(&join_words(args), app.search_options.smart_case);
             if regex.is_empty(){return Err(String::from("too few arguments: search <regex>"));}
-            //search <regex>
-            match search(regex, &app.buffer, app.config.semantics.clone()){
+            //search [flags] <regex>
+            match search(&regex, &app.buffer, app.config.semantics.clone()){
                 Err(_) => return Err(String::from("no matching regex")),
                 Ok(new_selections) => {
+                    //only a search that matched is repeated by next/previous match
+                    let _ = app.registers.set(SEARCH_REGISTER, &regex);
                     app.selections = new_selections;
                     app.checked_scroll_and_update(
                         &app.selections.primary.clone(), 
@@ -1858,12 +2914,15 @@ fn execute_command(app: &mut Application, command: &str) -> Result<(), String>{
             }
         }
         "search_selection" => {
-            let regex = parser.rest();
+            let (flags, args) = SearchFlags::parse(args)?;
+            let regex = flags.regex(&join_words(args), app.search_options.smart_case);
             if regex.is_empty(){return Err(String::from("too few arguments: search_selection <regex>"));}
-            //search_selection <regex>
+            //search_selection [flags] <regex>
             match search_selection(&app.selections, &regex, &app.buffer, app.config.semantics.clone()){
                 Err(_) => return Err(String::from("no matching regex")),
                 Ok(new_selections) => {
+                    //only a search that matched is repeated by next/previous match
+                    let _ = app.registers.set(SEARCH_REGISTER, &regex);
                     app.selections = new_selections;
                     app.checked_scroll_and_update(
                         &app.selections.primary.clone(), 
@@ -1874,8 +2933,47 @@ fn execute_command(app: &mut Application, command: &str) -> Result<(), String>{
             }
         }
         //"split" => {} //split whole buffer
+        "replace" => {
+            //replace <regex> [template]
+            let (regex, template) = match args.split_first(){
+                None => return Err(String::from("too few arguments: replace <regex> [template]")),
+                Some((regex, template)) => (regex.content.clone(), join_words(template))
+            };
+            if replace_matches(app, &regex, &template)?{
+                app.emit_text_events(false);
+                app.checked_scroll_and_update(
+                    &app.selections.primary.clone(), 
+                    Application::update_ui_data_document, 
+                    Application::update_ui_data_document
+                );
+            }else{handle_message(app, SAME_STATE_DISPLAY_MODE, SAME_STATE);}
+        }
+        //acme's Edit. sam structural regular expression commands, run over each selection
+        "Edit" => {
+            let command = join_words(args);
+            if command.is_empty(){return Err(String::from("too few arguments: Edit <sam command>"));}
+            edit_structurally(app, &command)?;
+        }
+        //keep_matching <regex> | remove_matching <regex>
+        "keep_matching" | "remove_matching" => {
+            let regex = join_words(args);
+            if regex.is_empty(){return Err(format!("too few arguments: {} <regex>", first));}
+            let regex = regex::Regex::new(&regex).map_err(|error| format!("{}", error))?;
+            match filter_selections(&app.selections, &regex, &app.buffer, app.config.semantics.clone(), first == "keep_matching"){
+                Err(SelectionsError::ResultsInSameState) => handle_message(app, SAME_STATE_DISPLAY_MODE, SAME_STATE),
+                Err(_) => return Err(String::from("no selections would remain")),
+                Ok(new_selections) => {
+                    app.selections = new_selections;
+                    app.checked_scroll_and_update(
+                        &app.selections.primary.clone(), 
+                        Application::update_ui_data_document, 
+                        Application::update_ui_data_selections
+                    );
+                }
+            }
+        }
         "split_selection" => {
-            let regex = parser.rest();
+            let regex = join_words(args);
             if regex.is_empty(){return Err(String::from("too few arguments: split_selection <regex>"));}
             //split_selection <regex>
             match split_selection(&app.selections, &regex, &app.buffer, app.config.semantics.clone()){
@@ -1891,223 +2989,534 @@ fn execute_command(app: &mut Application, command: &str) -> Result<(), String>{
             }
         }
                 
-        //add_keybind <mode> <keybind> <command>
-        //"add_keybind" => {
-        //    let mode = Mode::Insert;    //get mode from positional args
-        //    let keycode = crossterm::event::KeyCode::Char('n'); //get mode from positional args
-        //    let modifiers = crossterm::event::KeyModifiers::CONTROL;    //get mode from positional args
-        //    let key_event = crossterm::event::KeyEvent::new(keycode, modifiers);
-        //    let _command = "idk some shit".to_string();  //get mode from positional args
-        //    if app.config.keybinds.contains_key(&(mode, key_event)){
-        //        return Err(String::from("this keybind has already been mapped"))
-        //    }else{
-        //        //app.config.keybinds.insert((mode, key_event), Action::EditorAction(EditorAction::EvalCommand(command)));
-        //        handle_message(app, DisplayMode::Info, "keybind added");
-        //    }
-        //}
-        //remove_keybind <keybind>
+        "add_keybind" => {
+            //add_keybind <mode> <key_chord> <action_name | command>
+            if args.len() < 3{return Err(String::from("too few arguments: add_keybind <mode> <key_chord> <action_name | command>"));}
+            let mode: Mode = args[0].content.parse()?;
+            let key_event = string_to_key_event(&args[1].content)?;
+            if app.config.keybinds.contains_key(&(mode.clone(), key_event)){
+                return Err(format!("{} is already mapped in {:?} mode", args[1].content, mode));
+            }
+            //a built in action name binds that action. anything else is bound as a command string
+            let binding = join_words(&args[2..]);
+            let action = match Action::from_command_name(&binding){
+                Some(action) => action,
+                None => Action::EditorAction(EditorAction::EvaluateCommand(binding))
+            };
+            //keybinds are looked up in the current mode, so the action must be able to run there
+            if !action.runs_in(&mode){
+                return Err(format!("{} can't be performed in {:?} mode", action.command_name().replace(' ', "_"), mode));
+            }
+            app.config.keybinds.insert((mode, key_event), action);
+            app.ui.popups = Popups::new(&app.config.keybinds, &app.user_commands);
+            handle_message(app, DisplayMode::Notify, "keybind added");
+        }
+        "remove_keybind" => {
+            //remove_keybind <mode> <key_chord>
+            if args.len() < 2{return Err(String::from("too few arguments: remove_keybind <mode> <key_chord>"));}
+            if args.len() > 2{return Err(String::from("too many arguments: remove_keybind <mode> <key_chord>"));}
+            let mode: Mode = args[0].content.parse()?;
+            let key_event = string_to_key_event(&args[1].content)?;
+            if app.config.keybinds.shift_remove(&(mode.clone(), key_event)).is_none(){
+                return Err(format!("{} is not mapped in {:?} mode", args[1].content, mode));
+            }
+            app.ui.popups = Popups::new(&app.config.keybinds, &app.user_commands);
+            handle_message(app, DisplayMode::Notify, "keybind removed");
+        }
+        "list_keybinds" => {
+            //list_keybinds [mode]
+            if args.len() > 1{return Err(String::from("too many arguments: list_keybinds [mode]"));}
+            let mode_filter: Option<Mode> = match args.first(){
+                None => None,
+                Some(mode) => Some(mode.content.parse()?)
+            };
+            let listing = app.config.keybinds.iter()
+                .filter(|((mode, _), _)| mode_filter.as_ref().is_none_or(|filter| filter == mode))
+                .map(|((mode, key_event), action)| format!("{:?} {} {}", mode, key_event_to_string(key_event), action.command_name()))
+                .collect::<Vec<String>>()
+                .join("\n");
+            if listing.is_empty(){return Err(String::from("no keybinds to list"));}
+            handle_message(app, DisplayMode::Info, &listing);
+        }
+
+        "add_command" => {
+            //add_command <name> <body> [--doc <text>]
+            let usage = "add_command <name> <body> [--doc <text>]";
+            let (name, body, doc) = match args{
+                [name, body] => (name, body, None),
+                [name, body, flag, doc] if flag.content == "--doc" => (name, body, Some(doc.content.clone())),
+                [] | [_] => return Err(format!("too few arguments: {}", usage)),
+                _ => return Err(format!("invalid arguments: {}", usage))
+            };
+            app.user_commands.add(&name.content, UserCommand{body: body.content.clone(), doc})?;
+            app.ui.popups = Popups::new(&app.config.keybinds, &app.user_commands);
+            handle_message(app, DisplayMode::Notify, &format!("command {} added", name.content));
+        }
+
+        "add_option" => {
+            //add_option <name> <type> [value]
+            let usage = "add_option <name> <bool|int|string|regex|list> [value]";
+            let (name, option_type, value) = match args{
+                [name, option_type] => (name.content.as_str(), option_type.content.parse::<OptionType>()?, None),
+                [name, option_type, value @ ..] => (name.content.as_str(), option_type.content.parse::<OptionType>()?, Some(join_words(value))),
+                _ => return Err(format!("too few arguments: {}", usage))
+            };
+            if option_value(app, name).is_ok(){return Err(format!("option {} already exists", name));}
+            let value = match value{
+                None => OptionValue::default_for(option_type),
+                Some(value) => OptionValue::parse(option_type, &value)?
+            };
+            app.user_options.add(name, value)?;
+            handle_message(app, DisplayMode::Notify, &format!("option {} added", name));
+        }
+        "add_hook" => {
+            //add_hook --group <group> --event <event> [--filter <regex>] --command <command>
+            let usage = "add_hook --group <group> --event <event> [--filter <regex>] --command <command>";
+            let (mut group, mut event, mut filter, mut command) = (None, None, None, None);
+            for flag_and_value in args.chunks(2){
+                match flag_and_value{
+                    [flag, value] if flag.content == "--group" => group = Some(value.content.as_str()),
+                    [flag, value] if flag.content == "--event" => event = Some(value.content.parse::<HookEvent>()?),
+                    [flag, value] if flag.content == "--filter" => filter = Some(value.content.as_str()),
+                    [flag, value] if flag.content == "--command" => command = Some(value.content.as_str()),
+                    _ => return Err(format!("invalid arguments: {}", usage))
+                }
+            }
+            match (group, event, command){
+                (Some(group), Some(event), Some(command)) => app.hooks.add(group, event, filter, command)?,
+                _ => return Err(format!("too few arguments: {}", usage))
+            }
+            handle_message(app, DisplayMode::Notify, "hook added");
+        }
+        "remove_hooks" => {
+            //remove_hooks <group>
+            let group = match args{
+                [group] => group.content.as_str(),
+                [] => return Err(String::from("too few arguments: remove_hooks <group>")),
+                _ => return Err(String::from("too many arguments: remove_hooks <group>"))
+            };
+            match app.hooks.remove_group(group){
+                0 => return Err(format!("no hooks in group {}", group)),
+                count => handle_message(app, DisplayMode::Notify, &format!("{} hooks removed", count))
+            }
+        }
 
         //TODO: bug: when these are called from command mode, a success diagnostic is displayed stacked on top of command mode
         //command mode should exit to insert then display the diagnostic...
-        //this will eventually be echo -n <value> > /mnt/edit/<instance_id>/settings/<setting>, when filesystem interface impled
+        //can also be set with echo <value> > /mnt/edit/<instance_id>/settings/<setting>
         //"set_option" => {
         "set" => {
             //set_option <name> <value>
-            let name = match parser.next(){
-                None => return Err(String::from("too few arguments: set <name> <value>")),
-                Some(name) => name,
-            };
-            let value = match parser.next(){
+            let name = match args.first(){
                 None => return Err(String::from("too few arguments: set <name> <value>")),
-                Some(value) => value,
+                Some(name) => name.content.as_str(),
             };
-            if !parser.rest().is_empty(){return Err(String::from("too many arguments: set <name> <value>"));}
-            match name{
-                //NOTE: may not allow setting cursor semantics for TUI, because terminal cannot currently handle multicursor bar cursor display...
-                "cursor_semantics" => { //TODO: maybe return error results in same state if already set to provided value. maybe do that for all options...
-                    match value{
-                        "Bar" | "bar" => {
-                            if app.config.semantics == CursorSemantics::Bar{handle_message(app, SAME_STATE_DISPLAY_MODE, SAME_STATE);}
-                            else{
-                                app.config.semantics = CursorSemantics::Bar;
-                                //TODO: change selections from Block to Bar
-                                handle_message(app, DisplayMode::Notify, &format!("cursor_semantics set to {}", value));
-                            }
-                        }
-                        "Block" | "block" => {
-                            if app.config.semantics == CursorSemantics::Block{handle_message(app, SAME_STATE_DISPLAY_MODE, SAME_STATE);}
-                            else{
-                                app.config.semantics = CursorSemantics::Block;
-                                //TODO: change selections from Bar to Block
-                                handle_message(app, DisplayMode::Notify, &format!("cursor_semantics set to {}", value));
-                            }
-                        }
-                        _ => return Err(format!("{} is not a valid value for cursor_semantics", value))
-                    }
+            //value is the rest of the command, so command valued options(clipboard_set_command, etc.) can take arguments
+            let value = join_words(&args[1..]);
+            if value.is_empty(){return Err(String::from("too few arguments: set <name> <value>"));}
+            set_option(app, name, &value)?;
+        }
+        _ => {
+            //user defined commands, added with add_command
+            if let Some(user_command) = app.user_commands.get(first){
+                let arguments: Vec<String> = args.iter().map(|arg| arg.content.clone()).collect();
+                let body = user_command.body_for(arguments.len())?;
+                app.user_commands.enter()?;
+                let result = evaluate_commands_with_arguments(app, &body, Some(&arguments));
+                app.user_commands.exit();
+                return result;
+            }
+            //built in actions, by name. e.g. move_cursor_down 5
+            if let Some(action) = Action::from_command_name(first){
+                return execute_action(app, action, args);
+            }
+            //run anything else as shell command. commands whose first word is a plain name were already run in the shell(see 
+            //`evaluate_commands`), so this one was named by an expansion
+            start_shell_command(app, &to_shell_string(words), vec![None], ShellCommandKind::Run)?;
+        }
+    }
+    Ok(())
+}
+/// Replaces each selection's text with the output of its own run of `command`, as a single undoable change.
+/// Each run is sent its selection's text as stdin, if `send_selections` is true. Runs happen in parallel.
+fn pipe_selections(app: &mut Application, command: &str, send_selections: bool) -> Result<(), String>{
+    if app.buffer.read_only{return Err(String::from(READ_ONLY_BUFFER));}
+    let selected_texts: Vec<String> = app.selections.iter().map(|selection| selection.to_string(&app.buffer)).collect();
+    let stdins = selected_texts.iter().map(|text| if send_selections{Some(text.clone())}else{None}).collect();
+    let kind = ShellCommandKind::Pipe{selections: app.selections.clone(), selected_texts};
+    start_shell_command(app, command, stdins, kind)
+}
+/// Replaces each selection's text with its run's output, as a single undoable change. Errors if any run failed.
+fn replace_selections_with_outputs(app: &mut Application, selected_texts: &[String], outputs: Vec<Result<String, String>>) -> Result<(), String>{
+    let mut replacements = Vec::with_capacity(outputs.len());
+    for (text, output) in selected_texts.iter().zip(outputs){
+        let output = output?;
+        //commands usually end their output with a newline. it is kept only if the selection's text ended with one
+        let output = if text.ends_with('\n'){output.as_str()}else{output.strip_suffix('\n').unwrap_or(&output)};
+        replacements.push(output.to_string());
+    }
+    if replacements == selected_texts{
+        handle_message(app, SAME_STATE_DISPLAY_MODE, SAME_STATE);
+        return Ok(());
+    }
+    match replace_selections(app, &replacements, app.config.semantics.clone()){
+        Err(e) => handle_application_error(app, e),
+        Ok(()) => {
+            app.emit_text_events(false);
+            app.checked_scroll_and_update(
+                &app.selections.primary.clone(), 
+                Application::update_ui_data_document, 
+                Application::update_ui_data_document
+            );
+        }
+    }
+    Ok(())
+}
+/// Runs a sam command(see [`sam`]) over each selection. Its changes are applied as a single undoable change, and what it 
+/// selected, or changed, becomes the new selections.
+fn edit_structurally(app: &mut Application, command: &str) -> Result<(), String>{
+    let text = app.buffer.to_string();
+    let dots = structural_edit_dots(app);
+    //what the `|` command pipes is collected first, so that its shell command runs like any other(on the worker thread, and
+    //cancellable). once it finishes, the sam command runs again with its outputs. a sam command has at most one `|`
+    let mut piped_inputs: Vec<(String, String)> = Vec::new();
+    let outcome = sam::execute(command, &text, &dots, &mut |shell_command, input|{
+        piped_inputs.push((shell_command.to_string(), input.to_string()));
+        Ok(String::new())
+    })?;
+    match piped_inputs.first(){
+        None => apply_structural_edit(app, outcome),
+        Some((shell_command, _)) => {
+            let shell_command = shell_command.clone();
+            let stdins = piped_inputs.into_iter().map(|(_, input)| Some(input)).collect();
+            let kind = ShellCommandKind::Edit{command: command.to_string(), text, selections: app.selections.clone()};
+            start_shell_command(app, &shell_command, stdins, kind)
+        }
+    }
+}
+/// The selections' ranges, clamped to the buffer's text, for a sam command to run over.
+fn structural_edit_dots(app: &Application) -> Vec<Range>{
+    let len_chars = app.buffer.len_chars();
+    //a block cursor at buffer end extends past the text
+    app.selections.iter()
+        .map(|selection| Range::new(selection.range.start.min(len_chars), selection.range.end.min(len_chars)))
+        .collect()
+}
+/// Applies the changes a sam command made to the buffer's text, and selects what it selected.
+fn apply_structural_edit(app: &mut Application, outcome: sam::Outcome) -> Result<(), String>{
+    if outcome.selections.is_empty(){return Err(String::from("no matches"));}
+    //what the command selected within, or after, the old primary comes first from where it was. the new primary is the first
+    //selection there, or the last selection if there are none
+    let primary_start = outcome.map_position(app.selections.primary.range.start.min(app.buffer.len_chars()));
+    let primary_selection_index = outcome.selections.iter().position(|range| range.start >= primary_start).unwrap_or(outcome.selections.len() - 1);
+    if let Some((range, replacement)) = outcome.replacement(&app.buffer.to_string()){
+        if app.buffer.read_only{return Err(String::from(READ_ONLY_BUFFER));}
+        if replacement != app.buffer.slice(range.start, range.end){
+            if let Err(e) = replace_text_range(app, range, &replacement, app.config.semantics.clone()){
+                handle_application_error(app, e);
+                return Ok(());
+            }
+            app.emit_text_events(false);
+        }
+    }
+    let semantics = app.config.semantics.clone();
+    let selections = outcome.selections.into_iter()
+        .map(|range| selection_from_range(range, &app.buffer, semantics.clone()))
+        .collect();
+    app.selections = Selections::new(selections, primary_selection_index, &app.buffer, semantics);
+    app.checked_scroll_and_update(
+        &app.selections.primary.clone(), 
+        Application::update_ui_data_document, 
+        Application::update_ui_data_document
+    );
+    Ok(())
+}
+/// Replaces matches of `regex` with `template`, which may refer to capture groups as `$1` or `${name}`, as a single undoable
+/// change. Matches are replaced within each selection, or across the whole buffer if no selection is extended.
+/// Returns false, changing nothing, if replacing results in the same text.
+pub fn replace_matches(app: &mut Application, regex: &str, template: &str) -> Result<bool, String>{
+    if app.buffer.read_only{return Err(String::from(READ_ONLY_BUFFER));}
+    if regex.is_empty(){return Err(String::from("no matching regex"));}
+    let regex = regex::Regex::new(regex).map_err(|error| format!("{}", error))?;
+    let result = if app.selections.iter().any(|selection| selection.is_extended()){
+        let selected_texts: Vec<String> = app.selections.iter().map(|selection| selection.to_string(&app.buffer)).collect();
+        if !selected_texts.iter().any(|text| regex.is_match(text)){return Err(String::from("no matching regex"));}
+        let replacements: Vec<String> = selected_texts.iter().map(|text| regex.replace_all(text, template).into_owned()).collect();
+        if replacements == selected_texts{return Ok(false);}
+        replace_selections(app, &replacements, app.config.semantics.clone())
+    }else{
+        //only the span from the first match to the last is replaced, so that undo restores no more text than necessary
+        let text = app.buffer.to_string();
+        let mut replacement = String::new();
+        let mut span: Option<(usize, usize)> = None;
+        for captures in regex.captures_iter(&text){
+            let whole_match = captures.get(0).expect("capture group 0 is always the whole match");
+            match span{
+                None => span = Some((whole_match.start(), whole_match.end())),
+                Some((start, end)) => {
+                    replacement.push_str(&text[end..whole_match.start()]);
+                    span = Some((start, whole_match.end()));
                 }
-                "use_full_file_path" => {
-                    match value.parse::<bool>(){
-                        Err(error) => return Err(format!("{}", error)),
-                        Ok(parsed_value) => {
-                            app.config.use_full_file_path = parsed_value;
-                            if app.config.use_full_file_path{
-                                app.ui.status_bar.file_name_widget.text = app.buffer.file_path().unwrap_or_default();
-                            }else{
-                                app.ui.status_bar.file_name_widget.text = app.buffer.file_name().unwrap_or_default();
-                            }
-                            handle_message(app, DisplayMode::Notify, &format!("{} set to {}", name, parsed_value));
-                        }
+            }
+            captures.expand(template, &mut replacement);
+        }
+        let (start, end) = match span{
+            None => return Err(String::from("no matching regex")),
+            Some(span) => span
+        };
+        if replacement == text[start..end]{return Ok(false);}
+        let range = Range::new(app.buffer.byte_to_char(start), app.buffer.byte_to_char(end));
+        replace_text_range(app, range, &replacement, app.config.semantics.clone())
+    };
+    match result{
+        Ok(()) => Ok(true),
+        Err(ApplicationError::ReadOnlyBuffer) => Err(String::from(READ_ONLY_BUFFER)),
+        Err(_) => Err(String::from(INVALID_INPUT))
+    }
+}
+/// Parses [`Mode::Replace`]'s input, `<regex> [template]`, quoted the same way as the `replace` command's arguments.
+/// Expansions are rejected, because the input is parsed again on every keystroke.
+fn parse_replace_input(input: &str) -> Result<(String, String), String>{
+    let mut commands = parse_command(input.to_string())?;
+    if commands.len() != 1{return Err(String::from("expected <regex> [template]"));}
+    let words = commands.remove(0);
+    if words.iter().any(|word| matches!(word.word_type, WordType::Expansion(_))){
+        return Err(String::from("expansions are not evaluated while replacing"));
+    }
+    match words.split_first(){
+        None => Err(String::from("expected <regex> [template]")),
+        Some((regex, template)) => Ok((regex.content.clone(), join_words(template)))
+    }
+}
+/// Undoes [`Mode::Replace`]'s preview, if applied, without keeping it in the redo history, and restores the selections 
+/// from before the mode was entered.
+fn revert_replace_preview(app: &mut Application){
+    if app.replace_preview_applied{
+        let _ = undo(app, app.config.semantics.clone());
+        app.redo_stack.pop();
+        app.replace_preview_applied = false;
+    }
+    if let Some(selections) = &app.preserved_selections{app.selections = selections.clone();}
+}
+/// Selection over `range`. An empty range becomes a cursor at its start.
+fn selection_from_range(range: Range, buffer: &Buffer, semantics: CursorSemantics) -> Selection{
+    let range = match semantics{
+        CursorSemantics::Block if range.start == range.end => Range::new(range.start, buffer.next_grapheme_char_index(range.start)),
+        _ => range
+    };
+    let extension_direction = match semantics{
+        CursorSemantics::Bar if range.start == range.end => None,
+        CursorSemantics::Block if buffer.next_grapheme_char_index(range.start) == range.end => None,
+        _ => Some(selection::Direction::Forward)
+    };
+    Selection::new_from_range(range, extension_direction, buffer, semantics)
+}
+/// Performs a built in action named on the command line, with an optional count.
+/// Selection actions use the count directly. Other actions are repeated count times.
+fn execute_action(app: &mut Application, action: Action, args: &[Word]) -> Result<(), String>{
+    let usage = format!("{} [count]", action.command_name().replace(' ', "_"));
+    let count = match args{
+        [] => 1,
+        [count] => {
+            match count.content.parse::<usize>(){
+                Ok(count) if count > 0 => count,
+                _ => return Err(format!("{:?} is not a valid count: {}", count.content, usage))
+            }
+        }
+        _ => return Err(format!("too many arguments: {}", usage))
+    };
+    //actions run from whatever mode a hook or 9p client finds us in, if it can perform them. otherwise, modes are exited until one can.
+    //modes holding a pending search, split, filter or replacement aren't exited, because their saved selections would be lost
+    while !action.runs_in(&app.mode()){
+        if matches!(app.mode(), Mode::Find | Mode::Split | Mode::Keep | Mode::Remove | Mode::Replace){
+            return Err(format!("{} can't be performed in {:?} mode", action.command_name().replace(' ', "_"), app.mode()));
+        }
+        app.update(Action::EditorAction(EditorAction::ModePop));
+    }
+    match action{
+        Action::SelectionAction(selection_action, _) => app.update(Action::SelectionAction(selection_action, count)),
+        action => {
+            for _ in 0..count{app.update(action.clone());}
+        }
+    }
+    Ok(())
+}
+
+/// Sets a runtime option to `value`. Shared by the `set` command and writes to the served settings files.
+fn set_option(app: &mut Application, name: &str, value: &str) -> Result<(), String>{
+    match name{
+        //NOTE: may not allow setting cursor semantics for TUI, because terminal cannot currently handle multicursor bar cursor display...
+        "cursor_semantics" => { //TODO: maybe return error results in same state if already set to provided value. maybe do that for all options...
+            match value{
+                "Bar" | "bar" => {
+                    if app.config.semantics == CursorSemantics::Bar{handle_message(app, SAME_STATE_DISPLAY_MODE, SAME_STATE);}
+                    else{
+                        app.config.semantics = CursorSemantics::Bar;
+                        //TODO: change selections from Block to Bar
+                        handle_message(app, DisplayMode::Notify, &format!("cursor_semantics set to {}", value));
                     }
                 }
-                "use_hard_tab" => {
-                    match value.parse::<bool>(){
-                        Err(error) => return Err(format!("{}", error)),
-                        Ok(parsed_value) => {
-                            app.config.use_hard_tab = parsed_value;
-                            handle_message(app, DisplayMode::Notify, &format!("{} set to {}", name, parsed_value));
-                        }
+                "Block" | "block" => {
+                    if app.config.semantics == CursorSemantics::Block{handle_message(app, SAME_STATE_DISPLAY_MODE, SAME_STATE);}
+                    else{
+                        app.config.semantics = CursorSemantics::Block;
+                        //TODO: change selections from Bar to Block
+                        handle_message(app, DisplayMode::Notify, &format!("cursor_semantics set to {}", value));
                     }
                 }
-                "tab_width" => {
-                    match value.parse::<usize>(){
-                        Err(error) => return Err(format!("{}", error)),
-                        Ok(parsed_value) => {
-                            app.config.tab_width = parsed_value;
-                            handle_message(app, DisplayMode::Notify, &format!("{} set to {}", name, parsed_value));
-                        }
+                _ => return Err(format!("{} is not a valid value for cursor_semantics", value))
+            }
+        }
+        "use_full_file_path" => {
+            match value.parse::<bool>(){
+                Err(error) => return Err(format!("{}", error)),
+                Ok(parsed_value) => {
+                    app.config.use_full_file_path = parsed_value;
+                    if app.config.use_full_file_path{
+                        app.ui.status_bar.file_name_widget.text = app.buffer.file_path().unwrap_or_default();
+                    }else{
+                        app.ui.status_bar.file_name_widget.text = app.buffer.file_name().unwrap_or_default();
                     }
+                    handle_message(app, DisplayMode::Notify, &format!("{} set to {}", name, parsed_value));
                 }
-                "view_scroll_amount" => {
-                    match value.parse::<usize>(){
-                        Err(error) => return Err(format!("{}", error)),
-                        Ok(parsed_value) => {
-                            app.config.view_scroll_amount = parsed_value;
-                            handle_message(app, DisplayMode::Notify, &format!("{} set to {}", name, parsed_value));
-                        }
-                    }
+            }
+        }
+        "use_hard_tab" => {
+            match value.parse::<bool>(){
+                Err(error) => return Err(format!("{}", error)),
+                Ok(parsed_value) => {
+                    app.config.use_hard_tab = parsed_value;
+                    handle_message(app, DisplayMode::Notify, &format!("{} set to {}", name, parsed_value));
                 }
-                "show_cursor_column" => {
-                    match value.parse::<bool>(){
-                        Err(error) => return Err(format!("{}", error)),
-                        Ok(parsed_value) => {
-                            app.config.show_cursor_column = parsed_value;
-                            handle_message(app, DisplayMode::Notify, &format!("{} set to {}", name, parsed_value));
-                        }
-                    }
+            }
+        }
+        "tab_width" => {
+            match value.parse::<usize>(){
+                Err(error) => return Err(format!("{}", error)),
+                Ok(parsed_value) => {
+                    app.config.tab_width = parsed_value;
+                    handle_message(app, DisplayMode::Notify, &format!("{} set to {}", name, parsed_value));
                 }
-                "show_cursor_line" => {
-                    match value.parse::<bool>(){
-                        Err(error) => return Err(format!("{}", error)),
-                        Ok(parsed_value) => {
-                            app.config.show_cursor_line = parsed_value;
-                            handle_message(app, DisplayMode::Notify, &format!("{} set to {}", name, parsed_value));
-                        }
-                    }
+            }
+        }
+        "view_scroll_amount" => {
+            match value.parse::<usize>(){
+                Err(error) => return Err(format!("{}", error)),
+                Ok(parsed_value) => {
+                    app.config.view_scroll_amount = parsed_value;
+                    handle_message(app, DisplayMode::Notify, &format!("{} set to {}", name, parsed_value));
                 }
-                "show_line_numbers" => {
-                    match value.parse::<bool>(){
-                        Err(error) => return Err(format!("{}", error)),
-                        Ok(parsed_value) => {
-                            //TODO?: if app.mode() == Mode::Command{app.pop_to_insert()/*although, this fn is scoped within action()...*/}
-                            app.ui.document_viewport.line_number_widget.show = parsed_value;
-                            //
-                            app.layout();
-                            app.update_ui_data_document();
-                            //
-                            handle_message(app, DisplayMode::Notify, &format!("{} set to {}", name, parsed_value));
-                        }
-                    }
+            }
+        }
+        "show_cursor_column" => {
+            match value.parse::<bool>(){
+                Err(error) => return Err(format!("{}", error)),
+                Ok(parsed_value) => {
+                    app.config.show_cursor_column = parsed_value;
+                    handle_message(app, DisplayMode::Notify, &format!("{} set to {}", name, parsed_value));
                 }
-                "show_status_bar" => {
-                    match value.parse::<bool>(){
-                        Err(error) => return Err(format!("{}", error)),
-                        Ok(parsed_value) => {
-                            //TODO?: if app.mode() == Mode::Command{app.pop_to_insert()/*although, this fn is scoped within action()...*/}
-                            app.ui.status_bar.show = parsed_value;
-                            //
-                            app.layout();
-                            app.update_ui_data_document();
-                            //
-                            handle_message(app, DisplayMode::Notify, &format!("{} set to {}", name, parsed_value));
-                        }
-                    }
+            }
+        }
+        "show_cursor_line" => {
+            match value.parse::<bool>(){
+                Err(error) => return Err(format!("{}", error)),
+                Ok(parsed_value) => {
+                    app.config.show_cursor_line = parsed_value;
+                    handle_message(app, DisplayMode::Notify, &format!("{} set to {}", name, parsed_value));
                 }
-                _ => return Err(format!("{:?} is not a valid setting", name))
             }
         }
-        "|" => {
-            let stdin = if app.selections.primary.to_string(&app.buffer).is_empty(){
-                None
-            }else{
-                Some(app.selections.primary.to_string(&app.buffer))
-            };
-            match run_shell_command(app, stdin, parser.rest()){
-                Err(error) => {
-                    if error.is_empty(){
-                        handle_message(app, DisplayMode::Error, "shell command failed with empty error string");
-                    }else{
-                        handle_message(app, DisplayMode::Error, &format!("{error}"));
-                    }
+        "show_line_numbers" => {
+            match value.parse::<bool>(){
+                Err(error) => return Err(format!("{}", error)),
+                Ok(parsed_value) => {
+                    //TODO?: if app.mode() == Mode::Command{app.pop_to_insert()/*although, this fn is scoped within action()...*/}
+                    app.ui.document_viewport.line_number_widget.show = parsed_value;
+                    //
+                    app.layout();
+                    app.update_ui_data_document();
+                    //
+                    handle_message(app, DisplayMode::Notify, &format!("{} set to {}", name, parsed_value));
                 }
-                Ok(output) => {
-                    if output.is_empty(){
-                        //TODO: maybe just do the normal replace, which would replace the selection with ""
-                        handle_message(app, DisplayMode::Warning, "shell command succeeded with empty output string");
-                    }else{
-                        //TODO: this works, but i would like to replace selections in one go...
-                        for char in output.trim().chars(){
-                            app.update(Action::EditAction(EditAction::InsertChar(char)));
-                        }
-                    }
+            }
+        }
+        "show_status_bar" => {
+            match value.parse::<bool>(){
+                Err(error) => return Err(format!("{}", error)),
+                Ok(parsed_value) => {
+                    //TODO?: if app.mode() == Mode::Command{app.pop_to_insert()/*although, this fn is scoped within action()...*/}
+                    app.ui.status_bar.show = parsed_value;
+                    //
+                    app.layout();
+                    app.update_ui_data_document();
+                    //
+                    handle_message(app, DisplayMode::Notify, &format!("{} set to {}", name, parsed_value));
                 }
             }
         }
-        ">" => {return Err(String::from("> not yet supported"));}
-        "<" => {
-            match run_shell_command(app, None, parser.rest()){
-                Err(error) => {
-                    if error.is_empty(){
-                        handle_message(app, DisplayMode::Error, "shell command failed with empty error string");
-                    }else{
-                        handle_message(app, DisplayMode::Error, &format!("{error}"));
-                    }
+        //an empty value unsets the command
+        "clipboard_set_command" => {
+            app.clipboard.set_command = if value.is_empty(){None}else{Some(value.to_string())};
+            handle_message(app, DisplayMode::Notify, &format!("{} set to {}", name, value));
+        }
+        "clipboard_get_command" => {
+            app.clipboard.get_command = if value.is_empty(){None}else{Some(value.to_string())};
+            handle_message(app, DisplayMode::Notify, &format!("{} set to {}", name, value));
+        }
+        //seconds. 0 lets shell commands run indefinitely
+        "shell_command_timeout" => {
+            match value.parse::<u64>(){
+                Err(error) => return Err(format!("{}", error)),
+                Ok(parsed_value) => {
+                    app.shell.timeout = if parsed_value == 0{None}else{Some(std::time::Duration::from_secs(parsed_value))};
+                    handle_message(app, DisplayMode::Notify, &format!("{} set to {}", name, parsed_value));
                 }
-                Ok(output) => {
-                    if output.is_empty(){
-                        handle_message(app, DisplayMode::Warning, "shell command succeeded with empty output string");
-                    }else{
-                        //TODO: this works, but i would like to replace selections in one go...
-                        for char in output.trim().chars(){
-                            app.update(Action::EditAction(EditAction::InsertChar(char)));
-                        }
-                    }
+            }
+        }
+        "smart_case" => {
+            match value.parse::<bool>(){
+                Err(error) => return Err(format!("{}", error)),
+                Ok(parsed_value) => {
+                    app.search_options.smart_case = parsed_value;
+                    handle_message(app, DisplayMode::Notify, &format!("{} set to {}", name, parsed_value));
                 }
             }
         }
-        "!" => {return Err(String::from("! not yet supported"));}
+        //options declared with add_option
         _ => {
-            //run anything else as shell command
-            match run_shell_command(app, None, command){
-                Err(error) => {
-                    if error.is_empty(){
-                        return Err(String::from("shell command failed with empty error string"));
-                    }else{
-                        return Err(error.to_string());
-                    }
-                }
-                Ok(output) => {
-                    if output.is_empty(){
-                        handle_message(app, DisplayMode::Warning, "shell command succeeded with empty output string");
-                    }else{
-                        //TODO: open new edit window with output in temp buffer, don't display in diagnostic panel
-                        handle_message(app, DisplayMode::Info, &format!("unpiped command output \"{}\" will be sent to new window(not yet implemented)", output));
-                    }
-                }
+            match app.user_options.set(name, value){
+                None => return Err(format!("{:?} is not a valid setting", name)),
+                Some(Err(error)) => return Err(error),
+                Some(Ok(())) => handle_message(app, DisplayMode::Notify, &format!("{} set to {}", name, value))
             }
         }
     }
     Ok(())
 }
+/// Returns the current value of a runtime option, formatted the way [`set_option`] accepts it.
+pub fn option_value(app: &Application, name: &str) -> Result<String, String>{
+    match name{
+        "cursor_semantics" => Ok(format!("{:?}", app.config.semantics)),
+        "use_full_file_path" => Ok(app.config.use_full_file_path.to_string()),
+        "use_hard_tab" => Ok(app.config.use_hard_tab.to_string()),
+        "tab_width" => Ok(app.config.tab_width.to_string()),
+        "view_scroll_amount" => Ok(app.config.view_scroll_amount.to_string()),
+        "show_cursor_column" => Ok(app.config.show_cursor_column.to_string()),
+        "show_cursor_line" => Ok(app.config.show_cursor_line.to_string()),
+        "show_line_numbers" => Ok(app.ui.document_viewport.line_number_widget.show.to_string()),
+        "show_status_bar" => Ok(app.ui.status_bar.show.to_string()),
+        "clipboard_set_command" => Ok(app.clipboard.set_command.clone().unwrap_or_default()),
+        "clipboard_get_command" => Ok(app.clipboard.get_command.clone().unwrap_or_default()),
+        "shell_command_timeout" => Ok(app.shell.timeout.map_or(0, |timeout| timeout.as_secs()).to_string()),
+        "smart_case" => Ok(app.search_options.smart_case.to_string()),
+        _ => {
+            match app.user_options.get(name){
+                None => Err(format!("{:?} is not a valid setting", name)),
+                Some(value) => Ok(value.to_string())
+            }
+        }
+    }
+}
 
 /* some example commands to test
 set_option show_status_bar true
@@ -2288,6 +3697,81 @@ pub fn split_selection(
 
     Ok(new_selections)
 }
+#[derive(Clone, Copy, PartialEq, Debug)] pub enum MatchDirection{Next, Previous}
+/// Moves the primary selection to the `count`th match of the regex `input` after(or before) it, wrapping around at buffer 
+/// ends. If `add` is true, the match is added as the new primary selection instead. Matches are those [`search`] would select,
+/// ignoring empty matches.
+pub fn select_match(
+    selections: &Selections, 
+    input: &str, 
+    buffer: &Buffer, 
+    semantics: CursorSemantics, 
+    direction: MatchDirection, 
+    add: bool, 
+    count: usize
+) -> Result<Selections, SelectionsError>{
+    if input.is_empty(){return Err(SelectionsError::NoSearchMatches);}
+    let regex = match regex::Regex::new(input){
+        Err(_) => return Err(SelectionsError::NoSearchMatches),
+        Ok(regex) => regex
+    };
+    let text = buffer.to_string();
+    let matches: Vec<Range> = regex.find_iter(&text)
+        .filter(|search_match| !search_match.is_empty())
+        .map(|search_match| Range::new(buffer.byte_to_char(search_match.start()), buffer.byte_to_char(search_match.end())))
+        .collect();
+    if matches.is_empty(){return Err(SelectionsError::NoSearchMatches);}
+
+    let mut range = selections.primary.range.clone();
+    for _ in 0..count{
+        let index = match direction{
+            MatchDirection::Next => matches.iter().position(|search_match| search_match.start >= range.end).unwrap_or(0),
+            MatchDirection::Previous => matches.iter().rposition(|search_match| search_match.start < range.start).unwrap_or(matches.len() - 1),
+        };
+        range = matches[index].clone();
+    }
+
+    let new_selection = selection_from_range(range, buffer, semantics.clone());
+    let mut new_selections = selections.flatten();
+    let primary_selection_index = if add{
+        new_selections.push(new_selection);
+        new_selections.len() - 1
+    }else{
+        new_selections[selections.primary_selection_index()] = new_selection;
+        selections.primary_selection_index()
+    };
+    let new_selections = Selections::new(new_selections, primary_selection_index, buffer, semantics);
+    if new_selections == *selections{return Err(SelectionsError::ResultsInSameState);}
+
+    Ok(new_selections)
+}
+/// Keeps only the selections whose text matches `regex`, or, if `keep` is false, only those whose text doesn't.
+/// The primary selection stays primary if kept. Otherwise, the nearest kept selection before it becomes primary.
+/// Errors if no selection would remain. The regex is compiled by the caller, so that an invalid regex can be reported.
+pub fn filter_selections(
+    selections: &Selections, 
+    regex: &regex::Regex, 
+    buffer: &Buffer, 
+    semantics: CursorSemantics, 
+    keep: bool
+) -> Result<Selections, SelectionsError>{
+    let mut new_selections = Vec::new();
+    let mut primary_selection_index = 0;
+    for selection in selections.flatten(){
+        let is_primary = selection == selections.primary;
+        if regex.is_match(&selection.to_string(buffer)) == keep{
+            if is_primary{primary_selection_index = new_selections.len();}
+            new_selections.push(selection);
+        }
+        else if is_primary{primary_selection_index = new_selections.len().saturating_sub(1);}
+    }
+    if new_selections.is_empty(){return Err(SelectionsError::NoSearchMatches);}
+
+    let new_selections = Selections::new(new_selections, primary_selection_index, buffer, semantics);
+    if new_selections == *selections{return Err(SelectionsError::ResultsInSameState);}
+
+    Ok(new_selections)
+}
 //TODO: also test Bar semantics
 #[cfg(test)]
 mod search_tests{
@@ -2359,6 +3843,11 @@ mod search_tests{
 use crate::{history::{Change, Operation}};
 /// Inserts provided string into text at each selection.
 pub fn insert_string(app: &mut Application, string: &str, use_hard_tab: bool, tab_width: usize, semantics: CursorSemantics) -> Result<(), ApplicationError>{
+    let strings = vec![string.to_string(); app.selections.count()];
+    insert_strings(app, &strings, use_hard_tab, tab_width, semantics)
+}
+/// Inserts `strings[i]` at selection i, as a single undoable change set. Expects one string per selection.
+pub fn insert_strings(app: &mut Application, strings: &[String], use_hard_tab: bool, tab_width: usize, semantics: CursorSemantics) -> Result<(), ApplicationError>{
     //TODO: string lengths need to use char count, not length in bytes
     fn handle_insert_replace(app: &mut Application, current_selection_index: usize, semantics: CursorSemantics, new_text: &str) -> Change{
         use std::cmp::Ordering;
@@ -2397,12 +3886,14 @@ pub fn insert_string(app: &mut Application, string: &str, use_hard_tab: bool, ta
         change
     }
     if app.buffer.read_only{return Err(ApplicationError::ReadOnlyBuffer);}
-    if string.is_empty(){return Err(ApplicationError::InvalidInput);}
+    assert!(strings.len() == app.selections.count());
+    if strings.iter().any(|string| string.is_empty()){return Err(ApplicationError::InvalidInput);}
     
     let selections_before_changes = app.selections.clone();
     let mut changes = Vec::new();
 
-    for i in 0..app.selections.count(){
+    for (i, string) in strings.iter().enumerate(){
+        let string = string.as_str();
         let selection = app.selections.nth_mut(i);
         let change = match string{
             //"\n" => {}    //handle behavior specific to pressing "enter". auto-indent, etc... //TODO: create tests for newline behavior...
@@ -2546,29 +4037,104 @@ pub fn backspace(app: &mut Application, _use_hard_tab: bool, _tab_width: usize,
     Ok(())
 }
 
-/// Cut single selection.
-/// Copies text to clipboard and removes selected text from document.
-/// Ensure single selection when calling this function.
+/// Cut selected text, one value per selection.
+/// Copies text to clipboard and removes selected text from document, as a single change set.
 pub fn cut(app: &mut Application, semantics: CursorSemantics) -> Result<(), ApplicationError>{
-    if app.selections.count() > 1{
-        return Err(
-            ApplicationError::SelectionsError(
-                //crate::selections::SelectionsError::MultipleSelections
-                SelectionsError::MultipleSelections
-            )
-        )
-    }
-
-    //let selection = app.selections.primary_mut();
-    let selection = &app.selections.primary;
-    // Copy the selected text to the clipboard
-    app.clipboard = app.buffer.slice(selection.range.start, selection.range.end).to_string();
+    // Copy the selected text of each selection to the clipboard, in selection order
+    let values: Vec<String> = app.selections.iter()
+        .map(|selection| app.buffer.slice(selection.range.start, selection.range.end).to_string())
+        .collect();
+    yank_values(app, values);
     delete(app, semantics)   //notice this is returning the result from delete
 }
 
-/// Insert clipboard contents at cursor position(s).
+/// Insert contents of the selected register, or the yank register if none selected, at cursor position(s).
+/// If the register holds one value per selection, value i is inserted at selection i. Otherwise, the values are joined and inserted at each selection.
+/// With no register selected, and a `clipboard_get_command` set, the system clipboard is pasted instead, if it was changed outside this editor.
 pub fn paste(app: &mut Application, use_hard_tab: bool, tab_width: usize, semantics: CursorSemantics) -> Result<(), ApplicationError>{
-    insert_string(app, &app.clipboard.clone(), use_hard_tab, tab_width, semantics)
+    let register = app.selected_register.take();
+    if register.is_none(){
+        if let Some(system_text) = read_system_clipboard(app){
+            let strings = vec![system_text; app.selections.count()];
+            return insert_strings(app, &strings, use_hard_tab, tab_width, semantics);
+        }
+    }
+    let register = register.unwrap_or(YANK_REGISTER.to_string());
+    let values = app.registers.values(&register).unwrap_or_default();
+    let strings = if values.len() == app.selections.count(){
+        values.to_vec()
+    }else{
+        vec![app.registers.get(&register).unwrap_or_default().to_string(); app.selections.count()]
+    };
+    insert_strings(app, &strings, use_hard_tab, tab_width, semantics)
+}
+/// Stores copied or cut text, one value per selection, in the yank register, and in the selected register, if any.
+/// The yank register's text is also sent to the system clipboard.
+fn yank_values(app: &mut Application, values: Vec<String>){
+    if let Some(register) = app.selected_register.take(){
+        //register may have been removed(through the file server) since it was selected. yank register still gets the text
+        let _ = app.registers.set_values(&register, values.clone());
+    }
+    let _ = app.registers.set_values(YANK_REGISTER, values);
+    let text = app.registers.get(YANK_REGISTER).unwrap_or_default().to_string();
+    write_system_clipboard(app, &text);
+}
+/// Sends `text` to `clipboard_set_command`, falling back to OSC 52 if no command is set, or the command fails.
+fn write_system_clipboard(app: &mut Application, text: &str){
+    app.clipboard.last_written = Some(text.to_string());
+    if let Some(command) = app.clipboard.set_command.clone(){
+        if run_clipboard_set_command(app, &command, text).is_ok(){return;}
+    }
+    app.clipboard.queue_osc52(text);
+}
+/// Runs `command` with `text` as its stdin, waiting at most [`CLIPBOARD_COMMAND_TIMEOUT`] for it to exit.
+/// Tools like xclip and wl-copy fork a process that keeps serving the clipboard. Its output isn't captured, because that process
+/// would hold the pipes open until the clipboard is next set.
+fn run_clipboard_set_command(app: &Application, command: &str, text: &str) -> Result<(), String>{
+    let mut shell = std::process::Command::new(SHELL);
+    shell.arg(SHELL_COMMAND_FLAG)
+        .arg(command)
+        .envs(shell_environment(app))
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .process_group(0);
+    if let Some(working_directory) = shell_working_directory(&app.buffer){shell.current_dir(working_directory);}
+    let mut child_process = shell.spawn().map_err(|error| format!("failed to execute process: {}", error))?;
+    if let Some(mut stdin) = child_process.stdin.take(){
+        let text = text.to_string();
+        //the pipe is closed once written, so the command sees the end of its input
+        std::thread::spawn(move ||{let _ = stdin.write_all(text.as_bytes());});
+    }
+    let start = std::time::Instant::now();
+    while start.elapsed() < CLIPBOARD_COMMAND_TIMEOUT{
+        match child_process.try_wait(){
+            Err(error) => return Err(format!("failed to execute process: {}", error)),
+            Ok(Some(status)) if status.success() => return Ok(()),
+            Ok(Some(status)) => return Err(format!("clipboard command failed with {}", status)),
+            Ok(None) => std::thread::sleep(SHELL_COMMAND_POLL_INTERVAL)
+        }
+    }
+    //still running, most likely serving the clipboard itself(wl-copy --foreground, for instance). it is reaped whenever it exits
+    std::thread::spawn(move ||{let _ = child_process.wait();});
+    Ok(())
+}
+/// Returns the output of `clipboard_get_command`, if set and successful, and if the system clipboard was changed outside this
+/// editor: it differs from what was last written to it, or, if nothing was, from the yank register's text. Otherwise, pasting 
+/// from the yank register keeps its per selection values, and any later changes to it.
+fn read_system_clipboard(app: &Application) -> Option<String>{
+    let command = app.clipboard.get_command.as_ref()?;
+    //runs on the UI thread, so it is given less time than other shell commands
+    let timeout = app.shell.timeout.map_or(CLIPBOARD_COMMAND_TIMEOUT, |timeout| timeout.min(CLIPBOARD_COMMAND_TIMEOUT));
+    let text = run_shell_process(command, &shell_environment(app), shell_working_directory(&app.buffer).as_deref(), None, Some(timeout), &AtomicBool::new(false)).ok()?;
+    if text.is_empty(){return None;}
+    let editor_text = match &app.clipboard.last_written{
+        Some(last_written) => last_written.as_str(),
+        None => app.registers.get(YANK_REGISTER).unwrap_or_default()
+    };
+    //clipboard tools may add, or strip(wl-paste -n), a trailing newline
+    if text.trim_end_matches('\n') == editor_text.trim_end_matches('\n'){return None;}
+    Some(text)
 }
 
 use std::cmp::Ordering;
@@ -2886,6 +4452,102 @@ pub fn add_surrounding_pair(app: &mut Application, leading_char: char, trailing_
     Ok(())
 }
 
+/// Replaces the text of each selection with the replacement at the same index, as a single undoable change.
+/// `replacements` must contain one entry per selection.
+pub fn replace_selections(app: &mut Application, replacements: &[String], semantics: CursorSemantics) -> Result<(), ApplicationError>{
+    if app.buffer.read_only{return Err(ApplicationError::ReadOnlyBuffer);}
+    if replacements.len() != app.selections.count(){return Err(ApplicationError::InvalidInput);}
+
+    let selections_before_changes = app.selections.clone();
+    let mut changes = Vec::with_capacity(replacements.len());
+    for (i, replacement) in replacements.iter().enumerate(){
+        let selection = app.selections.nth_mut(i);
+        //a non extended bar selection has no text to replace. apply_replace would delete the char after it
+        if selection.range.start == selection.range.end{
+            let change = app.buffer.apply_insert(replacement, selection, semantics.clone());
+            app.selections.shift_subsequent_selections_forward(i, replacement.chars().count());
+            changes.push(change);
+            continue;
+        }
+        let change = app.buffer.apply_replace(replacement, selection, semantics.clone());
+        if let Operation::Replace{replacement_text} = change.inverse(){
+            match replacement_text.chars().count().cmp(&replacement.chars().count()){    //old selected text vs new text
+                Ordering::Greater => {
+                    app.selections.shift_subsequent_selections_backward(
+                        i, 
+                        replacement_text.chars().count().saturating_sub(replacement.chars().count())
+                    );
+                }
+                Ordering::Less => {
+                    app.selections.shift_subsequent_selections_forward(
+                        i, 
+                        replacement.chars().count().saturating_sub(replacement_text.chars().count())
+                    );
+                }
+                Ordering::Equal => {}   // no change to subsequent selections
+            }
+        }
+        changes.push(change);
+    }
+
+    // push change set to undo stack
+    app.undo_stack.push(ChangeSet::new(changes, selections_before_changes, app.selections.clone()));
+
+    // clear redo stack. new actions invalidate the redo history
+    app.redo_stack.clear();
+
+    Ok(())
+}
+
+/// Replaces the entire buffer text, as a single undoable change.
+/// Existing selections are replaced by a single selection following the new text.
+pub fn replace_buffer_text(app: &mut Application, text: &str, semantics: CursorSemantics) -> Result<(), ApplicationError>{
+    if app.buffer.read_only{return Err(ApplicationError::ReadOnlyBuffer);}
+    if app.buffer.to_string() == text{return Err(ApplicationError::SelectionsError(SelectionsError::ResultsInSameState));}
+    replace_text_range(app, Range::new(0, app.buffer.len_chars()), text, semantics)
+}
+/// Replaces the text in `range`, as a single undoable change.
+/// Existing selections are replaced by a single selection following the new text.
+pub fn replace_text_range(app: &mut Application, range: Range, text: &str, semantics: CursorSemantics) -> Result<(), ApplicationError>{
+    if app.buffer.read_only{return Err(ApplicationError::ReadOnlyBuffer);}
+
+    // select the replaced range, so the change can be undone/redone as a single replace
+    // an empty range is widened to a neighbouring grapheme, kept in the replacement, because a block selection can't be empty,
+    // and replacing an empty bar selection would delete the char after it
+    let len_chars = app.buffer.len_chars();
+    let (range, text) = if range.start < range.end || len_chars == 0{(range, text.to_string())}
+    else if range.end < len_chars{
+        let end = app.buffer.next_grapheme_char_index(range.end).min(len_chars);
+        (Range::new(range.start, end), format!("{}{}", text, app.buffer.slice(range.end, end)))
+    }else{
+        let start = app.buffer.previous_grapheme_char_index(range.start);
+        (Range::new(start, range.end), format!("{}{}", app.buffer.slice(start, range.start), text))
+    };
+    let selection = if range.start == range.end{
+        match semantics{
+            CursorSemantics::Bar => Selection::new_from_range(Range::new(range.start, range.start), None, &app.buffer, semantics.clone()),
+            CursorSemantics::Block => Selection::new_from_range(Range::new(range.start, range.start + 1), None, &app.buffer, semantics.clone()),
+        }
+    }else{
+        let extension_direction = if semantics == CursorSemantics::Block && app.buffer.next_grapheme_char_index(range.start) == range.end{None}
+        else{Some(selection::Direction::Forward)};
+        Selection::new_from_range(range, extension_direction, &app.buffer, semantics.clone())
+    };
+    app.selections = Selections::new(vec![selection], 0, &app.buffer, semantics.clone());
+    let selections_before_changes = app.selections.clone();
+
+    let selection = app.selections.nth_mut(0);
+    let change = app.buffer.apply_replace(&text, selection, semantics);
+
+    // push change set to undo stack
+    app.undo_stack.push(ChangeSet::new(vec![change], selections_before_changes, app.selections.clone()));
+
+    // clear redo stack. new actions invalidate the redo history
+    app.redo_stack.clear();
+
+    Ok(())
+}
+
 //TODO:
 //swap selected text with line above
 //swap selected text with line below
@@ -2895,14 +4557,13 @@ pub fn add_surrounding_pair(app: &mut Application, leading_char: char, trailing_
 
 
 
-/// Copy single selection to clipboard.
-/// Ensure single selection when calling this function.
+/// Copy selected text to clipboard, one value per selection.
 pub fn copy(app: &mut Application) -> Result<(), ApplicationError>{
-    if app.selections.count() > 1{return Err(ApplicationError::SelectionsError(SelectionsError::MultipleSelections));}
-    
-    let selection = app.selections.primary.clone();
-    // Copy the selected text to the clipboard
-    app.clipboard = app.buffer.slice(selection.range.start, selection.range.end).to_string();
+    // Copy the selected text of each selection to the clipboard, in selection order
+    let values: Vec<String> = app.selections.iter()
+        .map(|selection| app.buffer.slice(selection.range.start, selection.range.end).to_string())
+        .collect();
+    yank_values(app, values);
 
     Ok(())
 }
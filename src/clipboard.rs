@@ -0,0 +1,50 @@
+//! Bridges the yank register to the system clipboard.
+//! Clipboard tools(`wl-copy`/`wl-paste`, `xclip`, etc.) are run as shell commands, configured through the `clipboard_set_command`
+//! and `clipboard_get_command` settings. When no set command is configured, or it fails, copied text is sent to the terminal as an
+//! OSC 52 sequence instead, which most terminal emulators forward to the system clipboard.
+
+
+
+const BASE64_ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+#[derive(Default)] pub struct Clipboard{
+    /// Shell command receiving copied text as stdin.
+    pub set_command: Option<String>,
+    /// Shell command whose stdout is pasted, when no register was selected.
+    pub get_command: Option<String>,
+    /// OSC 52 sequence waiting to be written to the terminal.
+    osc52: Option<String>,
+    /// Text this editor last sent to the system clipboard. Pasting prefers the system clipboard only once it holds something else.
+    pub last_written: Option<String>,
+}
+impl Clipboard{
+    /// Queues `text` to be written to the terminal as an OSC 52 sequence.
+    pub fn queue_osc52(&mut self, text: &str){
+        self.osc52 = Some(osc52_sequence(text));
+    }
+    /// Takes the queued OSC 52 sequence, if any. Only the most recent copy is kept.
+    pub fn take_osc52(&mut self) -> Option<String>{
+        self.osc52.take()
+    }
+}
+
+/// Terminal escape sequence setting the system clipboard to `text`.
+pub fn osc52_sequence(text: &str) -> String{
+    format!("\x1b]52;c;{}\x07", base64_encode(text.as_bytes()))
+}
+
+fn base64_encode(bytes: &[u8]) -> String{
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3){
+        let b = [chunk[0], chunk.get(1).copied().unwrap_or(0), chunk.get(2).copied().unwrap_or(0)];
+        let n = (u32::from(b[0]) << 16) | (u32::from(b[1]) << 8) | u32::from(b[2]);
+        for i in 0..4{
+            if i <= chunk.len(){
+                encoded.push(BASE64_ALPHABET[((n >> (18 - 6 * i)) & 0x3f) as usize] as char);
+            }else{
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
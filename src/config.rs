@@ -36,8 +36,20 @@ impl Default for Config{
 pub const START_FILE: &'static str = "/home/j/software/edit_suite/edit/start";
 pub const SHELL: &'static str = "sh";
 pub const SHELL_COMMAND_FLAG: &'static str = "-c";
-pub const EDIT_DIR: &'static str = "home/j/edit";   //maybe create on start, if doesn't exist
-    //TODO: serve 9p socket at EDIT_DIR/<instance_id>.sock
+/// How long shell commands may run before they are killed, until changed with the `shell_command_timeout` setting.
+pub const SHELL_COMMAND_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(60);
+/// How often a running shell command is checked for completion, cancellation, or timeout.
+pub const SHELL_COMMAND_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(10);
+/// Longest wait on a clipboard command, which runs on the UI thread. Kept short, regardless of the `shell_command_timeout` setting.
+pub const CLIPBOARD_COMMAND_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(1);
+/// Max size in bytes of the served events file. Oldest events are discarded once exceeded.
+pub const EVENT_LOG_CAPACITY: usize = 1024 * 1024;
+/// How long to wait for an external handler to acknowledge an intercepted event before handling it internally.
+/// A handler that misses this deadline is unregistered, so a dead handler can't stall every keypress.
+pub const INTERCEPT_TIMEOUT: std::time::Duration = std::time::Duration::from_millis(500);
+/// Directory inside $XDG_RUNTIME_DIR(or $NAMESPACE) holding each instance's 9p socket, and the index of live instances.
+/// See [`crate::instance`].
+pub const EDIT_DIR: &'static str = "edit";
     //TODO: mount 9p fs at EDIT_DIR/<instance_id>/          //maybe have const AUTO_MOUNT: bool = true|false, and init accordingly
 //display_line_numbers_on_startup can be passed to Application::new() separately, since it doesn't need to be stored
 //display_status_bar_on_startup can be passed to Application::new() separately, since it doesn't need to be stored
@@ -84,6 +96,7 @@ pub const EDIT_DIR: &'static str = "home/j/edit";   //maybe create on start, if
     pub const SELECTIONS_WIDGET_FOREGROUND_COLOR: Color = Color::White;//Rgb(100, 255, 100);
     pub const CURSOR_POSITION_WIDGET_FOREGROUND_COLOR: Color = Color::White;
     pub const MODE_WIDGET_FOREGROUND_COLOR: Color = Color::White;//Rgb(100, 0, 0);
+    pub const SHELL_COMMAND_WIDGET_FOREGROUND_COLOR: Color = Color::Yellow;
     
     pub const UTIL_BAR_BACKGROUND_COLOR: Color = Color::Black;
     pub const UTIL_BAR_FOREGROUND_COLOR: Color = Color::White;
@@ -0,0 +1,200 @@
+//! Editor events served to external programs through the 9p `events` file.
+//! Each event is written as a single line: `<EventName> [arguments]`. Text arguments are quoted and escaped,
+//! so that multi line text does not span multiple event lines.
+//! InsertText 4 "some\n"
+//! RemoveText 4 "some\n"
+//! Key and Mouse events may also be intercepted by an external handler, which decides whether the editor handles them.
+
+use crate::{
+    mode::Mode,
+    history::{ChangeSet, Operation},
+    keybind::key_event_to_string,
+};
+
+
+
+//TODO: disambiguate from application::Event
+#[derive(Clone)]
+pub enum EditorEvent{
+    Key(crossterm::event::KeyEvent),
+    Mouse(crossterm::event::MouseEvent),
+    /// `offset` is the char index text was inserted at.
+    InsertText{offset: usize, text: String},
+    /// `offset` is the char index text was removed from.
+    RemoveText{offset: usize, text: String},
+    ModeChange{from: Mode, to: Mode},
+    Error(String),
+    /// `file_path` of the saved buffer.
+    Save(String),
+}
+impl std::fmt::Display for EditorEvent{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result{
+        match self{
+            EditorEvent::Key(key_event) => write!(f, "Key {}", key_event_to_string(key_event)),
+            EditorEvent::Mouse(mouse_event) => {
+                use crossterm::event::{MouseEventKind, KeyModifiers};
+                let mut modifiers = String::new();
+                if mouse_event.modifiers.contains(KeyModifiers::CONTROL){modifiers.push_str("ctrl-");}
+                if mouse_event.modifiers.contains(KeyModifiers::ALT){modifiers.push_str("alt-");}
+                if mouse_event.modifiers.contains(KeyModifiers::SHIFT){modifiers.push_str("shift-");}
+                let kind = match mouse_event.kind{
+                    MouseEventKind::Down(button) => format!("down_{button:?}"),
+                    MouseEventKind::Up(button) => format!("up_{button:?}"),
+                    MouseEventKind::Drag(button) => format!("drag_{button:?}"),
+                    MouseEventKind::Moved => String::from("moved"),
+                    MouseEventKind::ScrollDown => String::from("scroll_down"),
+                    MouseEventKind::ScrollUp => String::from("scroll_up"),
+                };
+                //column and row are terminal coordinates, not buffer coordinates
+                write!(f, "Mouse {}{} {} {}", modifiers, kind.to_lowercase(), mouse_event.column, mouse_event.row)
+            }
+            EditorEvent::InsertText{offset, text} => write!(f, "InsertText {} {:?}", offset, text),
+            EditorEvent::RemoveText{offset, text} => write!(f, "RemoveText {} {:?}", offset, text),
+            EditorEvent::ModeChange{from, to} => write!(f, "ModeChange {:?} {:?}", from, to),
+            EditorEvent::Error(message) => write!(f, "Error {:?}", message),
+            EditorEvent::Save(file_path) => write!(f, "Save {:?}", file_path),
+        }
+    }
+}
+
+/// Kinds of input event an external handler can intercept, before the editor handles them itself.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum EventKind{
+    Key,
+    Mouse,
+}
+impl std::str::FromStr for EventKind{
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err>{
+        match s{
+            "Key" => Ok(EventKind::Key),
+            "Mouse" => Ok(EventKind::Mouse),
+            _ => Err(format!("event kind cannot be intercepted: {s}"))
+        }
+    }
+}
+
+/// A registration to intercept events of `kind` while the editor is in `mode`.
+/// Written to, and read from, the served `intercept/registrations` file as `<EventKind> <Mode>`. For example, `Key Insert`.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Intercept{
+    pub kind: EventKind,
+    pub mode: Mode,
+}
+impl std::str::FromStr for Intercept{
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err>{
+        let mut words = s.split_whitespace();
+        match (words.next(), words.next(), words.next()){
+            (Some(kind), Some(mode), None) => Ok(Intercept{kind: kind.parse()?, mode: mode.parse()?}),
+            _ => Err(format!("expected <EventKind> <Mode>, got: {s}"))
+        }
+    }
+}
+impl std::fmt::Display for Intercept{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result{
+        write!(f, "{:?} {:?}", self.kind, self.mode)
+    }
+}
+
+/// A handler's reply to an intercepted event, written to the served `intercept/pending` file.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Acknowledgement{
+    /// The handler dealt with the event. The editor should do nothing more with it.
+    Consumed,
+    /// The handler ignored the event. The editor should handle it as if it were never intercepted.
+    Pass,
+}
+impl std::str::FromStr for Acknowledgement{
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err>{
+        match s.trim(){
+            "consumed" => Ok(Acknowledgement::Consumed),
+            "pass" => Ok(Acknowledgement::Pass),
+            _ => Err(format!("expected consumed or pass, got: {}", s.trim()))
+        }
+    }
+}
+
+/// Returns the text events caused by applying a [`ChangeSet`]. If `undone` is true, returns the events caused by reverting it.
+pub fn text_events(change_set: &ChangeSet, undone: bool) -> Vec<EditorEvent>{
+    let mut events = Vec::new();
+    // changes are applied/reverted in order, so each offset must account for the length difference from the changes before it
+    // when reverting, earlier changes have already been reverted by the time a later change is
+    let mut preceding_length_difference: isize = 0;
+    for change in change_set.changes(){
+        let (inserted, removed) = match (change.operation(), change.inverse()){
+            (Operation::Insert{inserted_text}, _) => (inserted_text, String::new()),
+            (Operation::Delete, Operation::Insert{inserted_text}) => (String::new(), inserted_text),
+            (Operation::Replace{replacement_text}, Operation::Replace{replacement_text: replaced_text}) => (replacement_text, replaced_text),
+            _ => continue
+        };
+        let offset = change.selection_before_change().range.start;
+        if undone{
+            let offset = offset.saturating_add_signed(-preceding_length_difference);
+            if !inserted.is_empty(){events.push(EditorEvent::RemoveText{offset, text: inserted.clone()});}
+            if !removed.is_empty(){events.push(EditorEvent::InsertText{offset, text: removed.clone()});}
+        }else{
+            if !removed.is_empty(){events.push(EditorEvent::RemoveText{offset, text: removed.clone()});}
+            if !inserted.is_empty(){events.push(EditorEvent::InsertText{offset, text: inserted.clone()});}
+        }
+        preceding_length_difference = preceding_length_difference + inserted.chars().count() as isize - removed.chars().count() as isize;
+    }
+    events
+}
+
+/// Formatted event lines, readable at a byte offset. Oldest events are discarded once `capacity` bytes are exceeded,
+/// but offsets remain absolute, so readers can keep reading from where they left off.
+pub struct EventLog{
+    content: String,
+    /// Number of bytes discarded from the front of the log.
+    discarded: usize,
+    capacity: usize,
+}
+impl EventLog{
+    pub fn new(capacity: usize) -> Self{
+        Self{content: String::new(), discarded: 0, capacity}
+    }
+    pub fn push(&mut self, event: &EditorEvent){
+        self.content.push_str(&format!("{}\n", event));
+        if self.content.len() > self.capacity{
+            // discard whole lines, so readers never receive a partial event
+            let excess = self.content.len() - self.capacity;
+            let discard_to = match self.content[excess..].find('\n'){
+                Some(newline) => excess + newline + 1,
+                None => self.content.len()
+            };
+            self.content.drain(..discard_to);
+            self.discarded = self.discarded + discard_to;
+        }
+    }
+    /// Returns up to `count` bytes starting at `offset`. Offsets before the oldest retained event read from the oldest retained event.
+    pub fn read(&self, offset: usize, count: usize) -> Vec<u8>{
+        let start = offset.saturating_sub(self.discarded).min(self.content.len());
+        let end = start.saturating_add(count).min(self.content.len());
+        self.content.as_bytes()[start..end].to_vec()
+    }
+    /// Absolute offset of the end of the log.
+    pub fn len(&self) -> usize{self.discarded + self.content.len()}
+}
+
+#[cfg(test)]
+mod tests{
+    use crate::event_log::{EventLog, EditorEvent};
+
+    #[test] fn read_at_offset(){
+        let mut log = EventLog::new(1024);
+        log.push(&EditorEvent::Error(String::from("idk")));
+        log.push(&EditorEvent::Save(String::from("/some/shit")));
+        assert_eq!(b"Save \"/some/shit\"\n".to_vec(), log.read("Error \"idk\"\n".len(), 1024));
+    }
+    #[test] fn oldest_events_discarded_when_capacity_exceeded(){
+        let mut log = EventLog::new(16);
+        log.push(&EditorEvent::Error(String::from("idk")));   //12 bytes
+        log.push(&EditorEvent::Error(String::from("some")));  //13 bytes
+        assert_eq!(25, log.len());
+        assert_eq!(b"Error \"some\"\n".to_vec(), log.read(0, 1024));
+        assert_eq!(b"Error \"some\"\n".to_vec(), log.read(12, 1024));
+        assert_eq!(b"some\"\n".to_vec(), log.read(19, 1024));
+    }
+}
@@ -0,0 +1,298 @@
+//! Describes the tree of files served over the 9p file interface.
+//! The content of each file is derived from [`Application`](crate::application::Application) state, so reading and
+//! writing file content is handled in `application.rs`. This module only knows the shape of the tree, 
+//! which only depends on editor state for the set of registers.
+
+use serve9p::file_system::{Qid, Stat, QTDIR, QTFILE, DMDIR};
+use crate::registers::Registers;
+
+
+
+/// Mode bits describing the permissions of a served file. Access is not restricted per user, for now.
+const FILE_PERMISSIONS: u32 = 0o666;
+const READ_ONLY_FILE_PERMISSIONS: u32 = 0o444;
+const DIRECTORY_PERMISSIONS: u32 = 0o555;
+/// Owner name reported in [`Stat`] for every served file.
+const FILE_OWNER: &str = "edit";
+/// Runtime options served as files in the settings directory. Names match those accepted by the `set` command.
+pub const SETTINGS: [&str; 13] = [
+    "cursor_semantics",
+    "use_full_file_path",
+    "use_hard_tab",
+    "tab_width",
+    "view_scroll_amount",
+    "show_cursor_column",
+    "show_cursor_line",
+    "show_line_numbers",
+    "show_status_bar",
+    "clipboard_set_command",
+    "clipboard_get_command",
+    "shell_command_timeout",
+    "smart_case",
+];
+/// Qid path of the first setting file. Each following setting's qid path is offset by its index in [`SETTINGS`].
+const FIRST_SETTING_QID_PATH: u64 = 100;
+/// Qid path of the first register directory. Each register has a directory and a content file, so each register 
+/// takes 2 qid paths, offset by the register's id.
+const FIRST_REGISTER_QID_PATH: u64 = 1000;
+
+//TODO: add remaining files described in README (display/)
+// ~/edit/
+//     |---command
+//     |---events
+//     |---intercept/
+//     |   |---registrations
+//     |   |---pending
+//     |---settings/
+//     |   |---<one file per name in SETTINGS>
+//     |---registers/
+//     |   |---add
+//     |   |---del
+//     |   |---<register name>/
+//     |       |---content
+//     |---buffer/
+//     |   |---raw_text
+//     |---selections/
+//         |---all/
+//         |   |---range_addresses
+//         |   |---content
+//         |---primary_index
+/// A file or directory in the served file tree.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ServedFile{
+    Root,
+    /// Each write is executed as a command, as if typed in command mode. Read to get the result of the last executed command.
+    Command,
+    /// Read only log of editor events, one per line. See [`crate::event_log`].
+    Events,
+    InterceptDirectory,
+    /// One `<EventKind> <Mode>` per line, for each kind of event to intercept in each mode. Write to replace all registrations.
+    InterceptRegistrations,
+    /// Read to get the intercepted event awaiting acknowledgement(empty if none). Write `consumed` or `pass` to acknowledge it.
+    InterceptPending,
+    SettingsDirectory,
+    /// Read to get the current value of the named setting. Write to set it, with the same validation as the `set` command.
+    Setting(&'static str),
+    RegistersDirectory,
+    /// Write a name to add an empty register with that name.
+    RegistersAdd,
+    /// Write a name to remove the register with that name.
+    RegistersDel,
+    /// Directory for the register with this id. Named after the register.
+    Register(u64),
+    /// Read to get the register's content. Write to replace it.
+    RegisterContent(u64),
+    BufferDirectory,
+    /// Read to get the full buffer text. Write to replace the full buffer text.
+    BufferRawText,
+    SelectionsDirectory,
+    SelectionsAllDirectory,
+    /// One `<line>:<column>,<line>:<column>` address per line, for the start and end of each selection's range.
+    /// Lines and columns are 1-based. Write to replace all selections.
+    SelectionsAllRangeAddresses,
+    /// One line per selection, in the order listed by range_addresses, with the selection's text. Backslashes and newlines
+    /// in the text are escaped as `\\` and `\n`. Write in the same format to replace each selection's text with its line, or
+    /// write a single line to replace the text in every selection with it.
+    SelectionsAllContent,
+    /// 0-based index of the primary selection, in the order listed by range_addresses.
+    SelectionsPrimaryIndex,
+}
+impl ServedFile{
+    /// Returns the [`ServedFile`] associated with a qid path, if any.
+    pub fn from_qid_path(qid_path: u64) -> Option<Self>{
+        match qid_path{
+            0 => Some(ServedFile::Root),
+            1 => Some(ServedFile::BufferDirectory),
+            2 => Some(ServedFile::BufferRawText),
+            3 => Some(ServedFile::SelectionsDirectory),
+            4 => Some(ServedFile::SelectionsAllDirectory),
+            5 => Some(ServedFile::SelectionsAllRangeAddresses),
+            6 => Some(ServedFile::SelectionsAllContent),
+            7 => Some(ServedFile::SelectionsPrimaryIndex),
+            8 => Some(ServedFile::Events),
+            9 => Some(ServedFile::InterceptDirectory),
+            10 => Some(ServedFile::InterceptRegistrations),
+            11 => Some(ServedFile::InterceptPending),
+            12 => Some(ServedFile::Command),
+            13 => Some(ServedFile::SettingsDirectory),
+            14 => Some(ServedFile::RegistersDirectory),
+            15 => Some(ServedFile::RegistersAdd),
+            16 => Some(ServedFile::RegistersDel),
+            _ if qid_path >= FIRST_REGISTER_QID_PATH => {
+                let offset = qid_path - FIRST_REGISTER_QID_PATH;
+                match offset % 2{
+                    0 => Some(ServedFile::Register(offset / 2)),
+                    _ => Some(ServedFile::RegisterContent(offset / 2))
+                }
+            }
+            _ => {
+                let index = qid_path.checked_sub(FIRST_SETTING_QID_PATH)?;
+                SETTINGS.get(index as usize).map(|name| ServedFile::Setting(name))
+            }
+        }
+    }
+    /// Unique identifier for this file on the served file system.
+    pub fn qid_path(&self) -> u64{
+        match self{
+            ServedFile::Root => 0,
+            ServedFile::BufferDirectory => 1,
+            ServedFile::BufferRawText => 2,
+            ServedFile::SelectionsDirectory => 3,
+            ServedFile::SelectionsAllDirectory => 4,
+            ServedFile::SelectionsAllRangeAddresses => 5,
+            ServedFile::SelectionsAllContent => 6,
+            ServedFile::SelectionsPrimaryIndex => 7,
+            ServedFile::Events => 8,
+            ServedFile::InterceptDirectory => 9,
+            ServedFile::InterceptRegistrations => 10,
+            ServedFile::InterceptPending => 11,
+            ServedFile::Command => 12,
+            ServedFile::SettingsDirectory => 13,
+            ServedFile::Setting(name) => {
+                //a name not in SETTINGS can only come from constructing a Setting by hand
+                let index = SETTINGS.iter().position(|setting| setting == name).expect("setting name should be in SETTINGS");
+                FIRST_SETTING_QID_PATH + index as u64
+            }
+            ServedFile::RegistersDirectory => 14,
+            ServedFile::RegistersAdd => 15,
+            ServedFile::RegistersDel => 16,
+            ServedFile::Register(id) => FIRST_REGISTER_QID_PATH + (id * 2),
+            ServedFile::RegisterContent(id) => FIRST_REGISTER_QID_PATH + (id * 2) + 1,
+        }
+    }
+    /// Register directories are named after their register. A removed register's directory has an empty name.
+    pub fn name(&self, registers: &Registers) -> String{
+        match self{
+            ServedFile::Root => String::from("/"),
+            ServedFile::BufferDirectory => String::from("buffer"),
+            ServedFile::BufferRawText => String::from("raw_text"),
+            ServedFile::SelectionsDirectory => String::from("selections"),
+            ServedFile::SelectionsAllDirectory => String::from("all"),
+            ServedFile::SelectionsAllRangeAddresses => String::from("range_addresses"),
+            ServedFile::SelectionsAllContent => String::from("content"),
+            ServedFile::SelectionsPrimaryIndex => String::from("primary_index"),
+            ServedFile::Events => String::from("events"),
+            ServedFile::Command => String::from("command"),
+            ServedFile::SettingsDirectory => String::from("settings"),
+            ServedFile::Setting(name) => name.to_string(),
+            ServedFile::RegistersDirectory => String::from("registers"),
+            ServedFile::RegistersAdd => String::from("add"),
+            ServedFile::RegistersDel => String::from("del"),
+            ServedFile::Register(id) => registers.name(*id).unwrap_or_default().to_string(),
+            ServedFile::RegisterContent(_) => String::from("content"),
+            ServedFile::InterceptDirectory => String::from("intercept"),
+            ServedFile::InterceptRegistrations => String::from("registrations"),
+            ServedFile::InterceptPending => String::from("pending"),
+        }
+    }
+    /// Returns the directory containing this file. The root directory is its own parent.
+    pub fn parent(&self) -> Self{
+        match self{
+            ServedFile::Root |
+            ServedFile::Command |
+            ServedFile::Events |
+            ServedFile::InterceptDirectory |
+            ServedFile::SettingsDirectory |
+            ServedFile::RegistersDirectory |
+            ServedFile::BufferDirectory |
+            ServedFile::SelectionsDirectory => ServedFile::Root,
+            ServedFile::Setting(_) => ServedFile::SettingsDirectory,
+            ServedFile::RegistersAdd |
+            ServedFile::RegistersDel |
+            ServedFile::Register(_) => ServedFile::RegistersDirectory,
+            ServedFile::RegisterContent(id) => ServedFile::Register(*id),
+            ServedFile::InterceptRegistrations |
+            ServedFile::InterceptPending => ServedFile::InterceptDirectory,
+            ServedFile::BufferRawText => ServedFile::BufferDirectory,
+            ServedFile::SelectionsAllDirectory |
+            ServedFile::SelectionsPrimaryIndex => ServedFile::SelectionsDirectory,
+            ServedFile::SelectionsAllRangeAddresses |
+            ServedFile::SelectionsAllContent => ServedFile::SelectionsAllDirectory,
+        }
+    }
+    pub fn is_directory(&self) -> bool{
+        match self{
+            ServedFile::Root |
+            ServedFile::InterceptDirectory |
+            ServedFile::SettingsDirectory |
+            ServedFile::RegistersDirectory |
+            ServedFile::Register(_) |
+            ServedFile::BufferDirectory |
+            ServedFile::SelectionsDirectory |
+            ServedFile::SelectionsAllDirectory => true,
+            ServedFile::Setting(_) |
+            ServedFile::RegistersAdd |
+            ServedFile::RegistersDel |
+            ServedFile::RegisterContent(_) |
+            ServedFile::Command |
+            ServedFile::Events |
+            ServedFile::InterceptRegistrations |
+            ServedFile::InterceptPending |
+            ServedFile::BufferRawText |
+            ServedFile::SelectionsAllRangeAddresses |
+            ServedFile::SelectionsAllContent |
+            ServedFile::SelectionsPrimaryIndex => false,
+        }
+    }
+    /// Directories, and files that only report editor state, cannot be written to.
+    pub fn is_read_only(&self) -> bool{
+        self.is_directory() || *self == ServedFile::Events
+    }
+    pub fn children(&self, registers: &Registers) -> Vec<Self>{
+        match self{
+            ServedFile::Root => vec![ServedFile::Command, ServedFile::Events, ServedFile::InterceptDirectory, ServedFile::SettingsDirectory, ServedFile::RegistersDirectory, ServedFile::BufferDirectory, ServedFile::SelectionsDirectory],
+            ServedFile::InterceptDirectory => vec![ServedFile::InterceptRegistrations, ServedFile::InterceptPending],
+            ServedFile::SettingsDirectory => SETTINGS.iter().map(|name| ServedFile::Setting(name)).collect(),
+            ServedFile::RegistersDirectory => {
+                let mut children = vec![ServedFile::RegistersAdd, ServedFile::RegistersDel];
+                children.extend(registers.names().filter_map(|name| registers.id(name)).map(ServedFile::Register));
+                children
+            }
+            ServedFile::Register(id) => vec![ServedFile::RegisterContent(*id)],
+            ServedFile::BufferDirectory => vec![ServedFile::BufferRawText],
+            ServedFile::SelectionsDirectory => vec![ServedFile::SelectionsAllDirectory, ServedFile::SelectionsPrimaryIndex],
+            ServedFile::SelectionsAllDirectory => vec![ServedFile::SelectionsAllRangeAddresses, ServedFile::SelectionsAllContent],
+            ServedFile::Setting(_) |
+            ServedFile::RegistersAdd |
+            ServedFile::RegistersDel |
+            ServedFile::RegisterContent(_) |
+            ServedFile::Command |
+            ServedFile::Events |
+            ServedFile::InterceptRegistrations |
+            ServedFile::InterceptPending |
+            ServedFile::BufferRawText |
+            ServedFile::SelectionsAllRangeAddresses |
+            ServedFile::SelectionsAllContent |
+            ServedFile::SelectionsPrimaryIndex => Vec::new(),
+        }
+    }
+    /// Returns the child of this directory with the provided name, or the parent directory if name is "..".
+    pub fn walk(&self, name: &str, registers: &Registers) -> Option<Self>{
+        if name == ".."{return Some(self.parent());}
+        self.children(registers).into_iter().find(|child| child.name(registers) == name)
+    }
+
+    pub fn qid(&self) -> Qid{
+        Qid{
+            qtype: if self.is_directory(){QTDIR}else{QTFILE},
+            version: 0,
+            path: self.qid_path()
+        }
+    }
+    /// `length` should be the length of the file content in bytes. directories should pass 0.
+    pub fn stat(&self, length: u64, registers: &Registers) -> Stat{
+        Stat{
+            qid: self.qid(),
+            mode: if self.is_directory(){DMDIR | DIRECTORY_PERMISSIONS}
+                else if self.is_read_only(){READ_ONLY_FILE_PERMISSIONS}
+                else{FILE_PERMISSIONS},
+            atime: 0,   //TODO: track access/modification times
+            mtime: 0,
+            length,
+            name: self.name(registers),
+            uid: FILE_OWNER.to_string(),
+            gid: FILE_OWNER.to_string(),
+            muid: FILE_OWNER.to_string(),
+        }
+    }
+}
@@ -0,0 +1,95 @@
+//! Commands run in response to editor events. Added with `add_hook`, and removed by group with `remove_hooks`, so that
+//! workflows like formatting after save, or stripping trailing whitespace before save, can live in the start file.
+//! Each event has a parameter, which a hook's optional filter regex must match for the hook to run.
+
+use regex::Regex;
+
+
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum HookEvent{
+    /// After the start file is evaluated. Parameter is the buffer's file path.
+    Startup,
+    /// Before the buffer is written to disk. Parameter is the buffer's file path.
+    BufferSavePre,
+    /// After the buffer is written to disk. Parameter is the buffer's file path.
+    BufferSaved,
+    /// Parameter is `<from mode> <to mode>`. For example, `Insert Command`.
+    ModeChanged,
+    /// Parameter is the primary selection's text.
+    SelectionsChanged,
+    /// Parameter is the inserted text.
+    TextInserted,
+    /// Parameter is the removed text.
+    TextRemoved,
+    /// Parameter is the error message.
+    ErrorOccurred,
+}
+impl std::str::FromStr for HookEvent{
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err>{
+        match s{
+            "Startup" => Ok(HookEvent::Startup),
+            "BufferSavePre" => Ok(HookEvent::BufferSavePre),
+            "BufferSaved" => Ok(HookEvent::BufferSaved),
+            "ModeChanged" => Ok(HookEvent::ModeChanged),
+            "SelectionsChanged" => Ok(HookEvent::SelectionsChanged),
+            "TextInserted" => Ok(HookEvent::TextInserted),
+            "TextRemoved" => Ok(HookEvent::TextRemoved),
+            "ErrorOccurred" => Ok(HookEvent::ErrorOccurred),
+            _ => Err(format!("{} is not a valid hook event", s))
+        }
+    }
+}
+
+pub struct Hook{
+    pub group: String,
+    pub event: HookEvent,
+    filter: Option<Regex>,
+    pub command: String,
+}
+
+#[derive(Default)] pub struct Hooks{
+    hooks: Vec<Hook>,
+    /// Events, with their parameters, waiting to be handled once the current update completes.
+    pending: Vec<(HookEvent, String)>,
+    /// Set while hook commands run. Events are ignored meanwhile, so that hooks cannot trigger themselves.
+    running: bool,
+}
+impl Hooks{
+    /// Adds a hook running `command` on `event`, when the event's parameter matches `filter`. Errors if `filter` is an invalid regex.
+    pub fn add(&mut self, group: &str, event: HookEvent, filter: Option<&str>, command: &str) -> Result<(), String>{
+        let filter = match filter{
+            None => None,
+            Some(filter) => Some(Regex::new(filter).map_err(|error| format!("{}", error))?)
+        };
+        self.hooks.push(Hook{group: group.to_string(), event, filter, command: command.to_string()});
+        Ok(())
+    }
+    /// Removes every hook in `group`. Returns the number of hooks removed.
+    pub fn remove_group(&mut self, group: &str) -> usize{
+        let count = self.hooks.len();
+        self.hooks.retain(|hook| hook.group != group);
+        count - self.hooks.len()
+    }
+    /// Whether any hook would run on `event`. Lets callers skip work needed only to produce an event's parameter.
+    pub fn handles(&self, event: HookEvent) -> bool{
+        !self.running && self.hooks.iter().any(|hook| hook.event == event)
+    }
+    /// Queues `event` to be handled once the current update completes.
+    pub fn queue(&mut self, event: HookEvent, parameter: String){
+        if self.handles(event){self.pending.push((event, parameter));}
+    }
+    pub fn take_pending(&mut self) -> Vec<(HookEvent, String)>{
+        std::mem::take(&mut self.pending)
+    }
+    /// Commands of the hooks that run on `event` with `parameter`, in the order they were added.
+    pub fn commands(&self, event: HookEvent, parameter: &str) -> Vec<String>{
+        self.hooks.iter()
+            .filter(|hook| hook.event == event && hook.filter.as_ref().is_none_or(|filter| filter.is_match(parameter)))
+            .map(|hook| hook.command.clone())
+            .collect()
+    }
+    pub fn is_running(&self) -> bool{self.running}
+    pub fn set_running(&mut self, running: bool){self.running = running;}
+}
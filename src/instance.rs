@@ -0,0 +1,109 @@
+//! Identity of a running editor, so that multiple editors can each serve their own 9p file system.
+//! Each instance binds a socket at `<runtime_dir>/edit/<instance_id>.sock`, and lists itself in `<runtime_dir>/edit/index`
+//! as `<instance_id> <file_path>`. The file path is omitted for temp buffers.
+
+use std::{
+    path::{Path, PathBuf},
+    os::unix::net::{UnixListener, UnixStream},
+};
+use crate::config::EDIT_DIR;
+
+
+
+const INDEX_FILE_NAME: &str = "index";
+const INDEX_LOCK_FILE_NAME: &str = "index.lock";
+
+/// Returns the directory instance sockets should be created under. `$XDG_RUNTIME_DIR` is preferred, 
+/// then the plan9port style `$NAMESPACE`.
+pub fn runtime_dir() -> Result<PathBuf, String>{
+    for variable in ["XDG_RUNTIME_DIR", "NAMESPACE"]{
+        if let Some(dir) = std::env::var_os(variable){
+            if !dir.is_empty(){return Ok(PathBuf::from(dir));}
+        }
+    }
+    Err(String::from("neither $XDG_RUNTIME_DIR nor $NAMESPACE is set"))
+}
+
+#[derive(Clone, Debug)]
+pub struct Instance{
+    /// Unique among live instances. This is the process id, so a stale socket left by a crashed instance may be reused.
+    pub id: u32,
+    pub socket_path: PathBuf,
+    index_path: PathBuf,
+}
+impl Instance{
+    /// Binds a socket for instance `id` in `runtime_dir`, and adds the instance to the index.
+    /// [`Instance::cleanup`] should be called once the instance exits.
+    pub fn register(runtime_dir: &Path, id: u32, file_path: Option<&Path>) -> Result<(Self, UnixListener), String>{
+        let edit_dir = runtime_dir.join(EDIT_DIR);
+        if let Err(e) = std::fs::create_dir_all(&edit_dir){
+            return Err(format!("edit::instance::register::create_dir_all: {e}"));
+        }
+        let socket_path = edit_dir.join(format!("{id}.sock"));
+        if socket_path.exists(){
+            if let Err(e) = std::fs::remove_file(&socket_path){
+                return Err(format!("edit::instance::register::remove_file: {e}"));
+            }
+        }
+        let listener = match UnixListener::bind(&socket_path){
+            Err(e) => return Err(format!("edit::instance::register::bind: {e}")),
+            Ok(listener) => listener
+        };
+        let instance = Instance{id, socket_path, index_path: edit_dir.join(INDEX_FILE_NAME)};
+        let entry = match file_path{
+            Some(file_path) => format!("{} {}", id, file_path.display()),
+            None => id.to_string()
+        };
+        if let Err(e) = instance.update_index(Some(entry)){
+            instance.cleanup();
+            return Err(e);
+        }
+        Ok((instance, listener))
+    }
+    /// Removes this instance's socket and index entry. Safe to call more than once.
+    pub fn cleanup(&self){
+        let _ = std::fs::remove_file(&self.socket_path);
+        let _ = self.update_index(None);
+    }
+    /// Rewrites the index without this instance's entry, then appends `entry`, if provided.
+    /// When registering, entries for instances that are no longer listening on their socket are dropped as well.
+    fn update_index(&self, entry: Option<String>) -> Result<(), String>{
+        let edit_dir = match self.index_path.parent(){
+            Some(edit_dir) => edit_dir,
+            None => return Err(String::from("edit::instance::update_index: index has no parent directory"))
+        };
+        //the index itself is replaced by rename, so lock a separate file to keep other instances from
+        //reading the index until this update is written. the lock is released when the file is closed
+        let lock_file = match std::fs::OpenOptions::new().create(true).truncate(false).write(true).open(edit_dir.join(INDEX_LOCK_FILE_NAME)){
+            Err(e) => return Err(format!("edit::instance::update_index::open_lock: {e}")),
+            Ok(lock_file) => lock_file
+        };
+        if let Err(e) = lock_file.lock(){
+            return Err(format!("edit::instance::update_index::lock: {e}"));
+        }
+        let prune = entry.is_some();
+        let existing = std::fs::read_to_string(&self.index_path).unwrap_or_default();
+        let mut content = String::new();
+        for line in existing.lines(){
+            let id = line.split(' ').next().unwrap_or_default();
+            if id.is_empty() || id == self.id.to_string(){continue;}
+            //an instance that crashed without cleaning up will have no listener
+            if prune && UnixStream::connect(edit_dir.join(format!("{id}.sock"))).is_err(){continue;}
+            content.push_str(line);
+            content.push('\n');
+        }
+        if let Some(entry) = entry{
+            content.push_str(&entry);
+            content.push('\n');
+        }
+        //write to a temporary file and rename, so other instances never read a partially written index
+        let temp_path = edit_dir.join(format!(".{}.{}", INDEX_FILE_NAME, self.id));
+        if let Err(e) = std::fs::write(&temp_path, content){
+            return Err(format!("edit::instance::update_index::write: {e}"));
+        }
+        if let Err(e) = std::fs::rename(&temp_path, &self.index_path){
+            return Err(format!("edit::instance::update_index::rename: {e}"));
+        }
+        Ok(())
+    }
+}
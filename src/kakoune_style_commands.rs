@@ -1,28 +1,54 @@
-use crate::{
-    action::{Action, EditorAction}, 
-    application::Application, 
-    config::{Command, DisplayMode, OptionType}, 
-    selection::CursorSemantics, 
-    selections
-};
+//! Parses command strings into commands made of words.
+//! Words may be quoted('a word', "a word", %{a word}), or be expansions(%opt{name}, %reg{name}, %sh{command}), which are 
+//! evaluated at execution time, in `application::execute_command`. Commands are separated by ';' or newlines, and '#' starts a comment.
 
 
 
-#[derive(PartialEq, Debug, Clone)] enum ExpansionType{Option, Value, Register, Shell}
-#[derive(PartialEq, Debug, Clone)] enum WordType{
+#[derive(PartialEq, Debug, Clone)] pub enum ExpansionType{Option, Value, Register, Shell}
+#[derive(PartialEq, Debug, Clone)] pub enum WordType{
     Unquoted,                   //word
     Quoted,                     //'a word', "a word", %{a word}
     Expansion(ExpansionType)    //%value{value_name}   //valid types are "shell", "register", "option", "value"
 }
 #[derive(PartialEq, Debug, Clone)] pub struct Word{
-    word_type: WordType,
-    content: String
+    pub word_type: WordType,
+    pub content: String
+}
+impl Word{
+    pub fn quoted(content: String) -> Self{Self{word_type: WordType::Quoted, content}}
+}
+/// Joins words back into a string to be interpreted by the shell.
+/// Quoted and expanded words are single quoted, so they remain a single shell word. Unquoted words are passed through, so 
+/// shell syntax(redirection, globs, $VARIABLES) still works.
+pub fn to_shell_string(words: &[Word]) -> String{
+    words.iter().map(|word|{
+        match word.word_type{
+            WordType::Unquoted => word.content.clone(),
+            WordType::Quoted |
+            WordType::Expansion(_) => format!("'{}'", word.content.replace('\'', "'\\''"))
+        }
+    }).collect::<Vec<String>>().join(" ")
 }
 //at the extreme, i think every action could end up being a command
 //in that sense, the editor is just a command parser, with command specific response behavior
 //NOTE: expansions should be performed at the time of execution. fn execute_command()
 pub fn parse_command(command_string: String) -> Result<Vec<Vec<Word>>, String>{
     if command_string.is_empty(){return Err(String::from("cannot parse empty string"));}
+    let (commands, _, _) = parse(&command_string, false)?;
+    if commands.is_empty(){return Err(String::from("failed to parse string as commands"));}
+    Ok(commands)
+}
+/// Parses only the first command in `command_string`, so that what follows it can be parsed after it executes, or, for 
+/// shell commands, not be parsed at all. Returns its words, the byte offset its first word starts at, and the byte offset 
+/// past its terminating ';' or newline. Returns `None` if there is no command.
+pub fn parse_first_command(command_string: &str) -> Result<Option<(Vec<Word>, usize, usize)>, String>{
+    let (mut commands, start, end) = parse(command_string, true)?;
+    if commands.is_empty(){return Ok(None);}
+    Ok(Some((commands.remove(0), start, end)))
+}
+fn parse(command_string: &str, first_only: bool) -> Result<(Vec<Vec<Word>>, usize, usize), String>{
+    let mut start = 0;
+    let mut end = command_string.len();
     let mut commands = Vec::new();
     let mut command = Vec::new();
     let mut word = String::new();
@@ -33,20 +59,19 @@ pub fn parse_command(command_string: String) -> Result<Vec<Vec<Word>>, String>{
     let mut inside_of_comment = false;
     //let mut escape_next = false;
     let mut follows_percent = false;
-    #[cfg(test)] println!("command string:\n{}\n", command_string);
-    #[cfg(test)] println!("command string length: {}\n", command_string.len());
     //TODO: for grapheme in command_string.graphemes(true){
-    for (_i, char) in command_string.chars().enumerate(){
+    for (index, char) in command_string.char_indices(){
         //TODO: maybe we should push '\' to word, and pop from word if the following char is something we should escape
         //that way we don't have to double escape unquoted strings containg '\'
-        #[cfg(test)] println!("char: {char}, index: {_i}");
+        if command.is_empty() && word.is_empty() && !inside_of_comment && !matches!(char, ' ' | '\t' | '\n' | ';' | '#'){
+            start = index;
+        }
         match char{
             ' ' | '\t' => {
                 if inside_of_comment{
-                    #[cfg(test)] println!("char ignored as comment");
+                    //char ignored as comment
                 }
                 else if inside_of_quotations{   //this may become inside_of_single_quote || inside_of_double_quote || inside_of_percent_quote
-                    #[cfg(test)] println!("char pushed to word: {char}");
                     word.push(char);
                 }
                 //else if escape_next{
@@ -56,7 +81,6 @@ pub fn parse_command(command_string: String) -> Result<Vec<Vec<Word>>, String>{
                 //}
                 else{
                     if !word.is_empty(){
-                        #[cfg(test)] println!("word pushed to command: {:?}", word);
                         command.push(Word{word_type: WordType::Unquoted, content: word});
                         //reset
                         word = String::new();
@@ -68,11 +92,9 @@ pub fn parse_command(command_string: String) -> Result<Vec<Vec<Word>>, String>{
             }
             '\n' => {
                 if inside_of_comment{
-                    #[cfg(test)] println!("end of comment");
                     inside_of_comment = false;
                 }
                 else if inside_of_quotations{
-                    #[cfg(test)] println!("char pushed to word: {char}");
                     word.push(char);
                 }
                 //else if escape_next{
@@ -82,7 +104,6 @@ pub fn parse_command(command_string: String) -> Result<Vec<Vec<Word>>, String>{
                 //}
                 else{
                     if !word.is_empty(){
-                        #[cfg(test)] println!("word pushed to command: {:?}", word);
                         command.push(Word{word_type: WordType::Unquoted, content: word});
                         //reset
                         word = String::new();
@@ -91,22 +112,24 @@ pub fn parse_command(command_string: String) -> Result<Vec<Vec<Word>>, String>{
                         follows_percent = false;
                     }
                     if !command.is_empty(){
-                        #[cfg(test)] println!("command pushed to commands: {:?}", command);
                         commands.push(command);
                         //reset
                         command = Vec::new();
                         inside_of_quotations = false;
                         quote_char = Vec::new();
                         follows_percent = false;
+                        if first_only{
+                            end = index + 1;
+                            break;
+                        }
                     }
                 }
             }
             ';' => {
                 if inside_of_comment{
-                    #[cfg(test)] println!("char ignored as comment");
+                    //char ignored as comment
                 }
                 else if inside_of_quotations{
-                    #[cfg(test)] println!("char pushed to word: {char}");
                     word.push(char);
                 }
                 //else if escape_next{
@@ -116,7 +139,6 @@ pub fn parse_command(command_string: String) -> Result<Vec<Vec<Word>>, String>{
                 //}
                 else{
                     if !word.is_empty(){
-                        #[cfg(test)] println!("word pushed to command: {:?}", word);
                         command.push(Word{word_type: WordType::Unquoted, content: word});
                         //reset
                         word = String::new();
@@ -125,22 +147,24 @@ pub fn parse_command(command_string: String) -> Result<Vec<Vec<Word>>, String>{
                         follows_percent = false;
                     }
                     if !command.is_empty(){
-                        #[cfg(test)] println!("command pushed to commands: {:?}", command);
                         commands.push(command);
                         //reset
                         command = Vec::new();
                         inside_of_quotations = false;
                         quote_char = Vec::new();
                         follows_percent = false;
+                        if first_only{
+                            end = index + 1;
+                            break;
+                        }
                     }
                 }
             }
             '#' => {
                 if inside_of_comment{
-                    #[cfg(test)] println!("char ignored as comment");
+                    //char ignored as comment
                 }
                 else if inside_of_quotations{
-                    #[cfg(test)] println!("char pushed to word: {char}");
                     word.push(char);
                 }
                 //else if escape_next{
@@ -149,7 +173,6 @@ pub fn parse_command(command_string: String) -> Result<Vec<Vec<Word>>, String>{
                 //    escape_next = false;
                 //}
                 else{
-                    #[cfg(test)] println!("comment started");
                     inside_of_comment = true;
                 }
             }
@@ -176,16 +199,14 @@ pub fn parse_command(command_string: String) -> Result<Vec<Vec<Word>>, String>{
 //TODO: support expansion inside double quotes: echo "the date is %sh{date}"
             '\'' | '"' => {
                 if inside_of_comment{
-                    #[cfg(test)] println!("char ignored as comment");
+                    //char ignored as comment
                 }
                 else if inside_of_quotations{
                     if quote_char.last() == Some(&char){    //if same as opening quote char
                         let _ = quote_char.pop();   //remove opening quote char from stack
                         if Option::is_none(&quote_char.last()){ //if quote char stack is empty  //should always be the case for '\'' and '"'
                             let _removed_char = word.remove(0); //remove leading '\'' or '"' from word
-                            #[cfg(test)] println!("leading {} removed", _removed_char);
 
-                            #[cfg(test)] println!("word pushed to command: {:?}", word);
                             command.push(Word{word_type: WordType::Quoted, content: word});
                             //reset necessary variables
                             word = String::new();
@@ -193,7 +214,6 @@ pub fn parse_command(command_string: String) -> Result<Vec<Vec<Word>>, String>{
                             assert!(quote_char.is_empty()); //could prob remove if Option::is_none, and assert after quote_char.pop() above...
                         }
                     }else{  //for all other quote chars than same as opening, push to word
-                        #[cfg(test)] println!("char pushed to word: {char}");
                         word.push(char);
                     }
                 }
@@ -203,7 +223,6 @@ pub fn parse_command(command_string: String) -> Result<Vec<Vec<Word>>, String>{
                 //    escape_next = false;
                 //}
                 else{
-                    #[cfg(test)] println!("char pushed to word: {char}");
                     word.push(char);
                     inside_of_quotations = true;
                     quote_char.push(char);
@@ -211,10 +230,9 @@ pub fn parse_command(command_string: String) -> Result<Vec<Vec<Word>>, String>{
             }
             '%' => {
                 if inside_of_comment{
-                    #[cfg(test)] println!("char ignored as comment");
+                    //char ignored as comment
                 }
                 else if inside_of_quotations{
-                    #[cfg(test)] println!("char pushed to word: {char}");
                     word.push(char);
                 }
                 //else if escape_next{
@@ -224,16 +242,14 @@ pub fn parse_command(command_string: String) -> Result<Vec<Vec<Word>>, String>{
                 //}
                 else{
                     if word.is_empty(){follows_percent = true;}
-                    #[cfg(test)] println!("char pushed to word: {char}");
                     word.push(char);
                 }
             }
             '{' | '[' | '(' => {
                 if inside_of_comment{
-                    #[cfg(test)] println!("char ignored as comment");
+                    //char ignored as comment
                 }
                 else if inside_of_quotations{
-                    #[cfg(test)] println!("char pushed to word: {char}");
                     word.push(char);
                     if quote_char.last() == Some(&char){
                         quote_char.push(char);
@@ -247,16 +263,13 @@ pub fn parse_command(command_string: String) -> Result<Vec<Vec<Word>>, String>{
                 else if follows_percent{
                     expansion_type_string = word.clone();   //copy preceding chars in word as expansion_type
                     expansion_type_string.remove(0);    //remove leading '%'
-                    #[cfg(test)] println!("expansion_type_string: {}", expansion_type_string);
 
-                    #[cfg(test)] println!("char pushed to word: {char}");
                     word.push(char);
                     inside_of_quotations = true;
                     quote_char.push(char);
                     follows_percent = false;
                 }
                 else{
-                    #[cfg(test)] println!("char pushed to word: {char}");
                     word.push(char);
                 }
             }
@@ -268,7 +281,7 @@ pub fn parse_command(command_string: String) -> Result<Vec<Vec<Word>>, String>{
                     else{None}  //or maybe unreachable!
                 }
                 if inside_of_comment{
-                    #[cfg(test)] println!("char ignored as comment");
+                    //char ignored as comment
                 }
                 else if inside_of_quotations{
                     if quote_char.last() == Some(&inverse_brace(char).unwrap()){    //if char matches opening quote char    //ok to unwrap here because inputs are verified by parent match expression
@@ -276,21 +289,15 @@ pub fn parse_command(command_string: String) -> Result<Vec<Vec<Word>>, String>{
                         if Option::is_none(&quote_char.last()){
                             if expansion_type_string.is_empty(){
                                 let _removed_char = word.remove(0); //remove leading '%' from word
-                                #[cfg(test)] println!("leading {} removed", _removed_char);
                                 let _removed_char = word.remove(0); //remove trailing '{', '[', '(', or '<' from word
-                                #[cfg(test)] println!("trailing {} removed", _removed_char);
 
-                                #[cfg(test)] println!("word pushed to command: {:?}", word);
                                 command.push(Word{word_type: WordType::Quoted, content: word});
                             }else{
                                 let _removed_char = word.remove(0); //remove leading '%' from word
-                                #[cfg(test)] println!("leading {} removed", _removed_char);
                                 for _ in 0..expansion_type_string.len(){
                                     let _removed_char = word.remove(0); //remove expansion type chars from word
-                                    #[cfg(test)] println!("expansion string {} removed", _removed_char);
                                 }
                                 let _removed_char = word.remove(0); //remove trailing '{', '[', '(', or '<' from word
-                                #[cfg(test)] println!("trailing {} removed", _removed_char);
 
                                 let expansion_type = match expansion_type_string.as_str(){
                                     "opt" => ExpansionType::Option,
@@ -299,19 +306,16 @@ pub fn parse_command(command_string: String) -> Result<Vec<Vec<Word>>, String>{
                                     "val" => ExpansionType::Value,
                                     _ => return Err(String::from("unsupported expansion type"))
                                 };
-                                #[cfg(test)] println!("word pushed to command: {:?}", word);
                                 command.push(Word{word_type: WordType::Expansion(expansion_type), content: word});
                             }
                             word = String::new();
                             inside_of_quotations = false;
                             assert!(quote_char.is_empty());
                         }else{
-                            #[cfg(test)] println!("char pushed to word: {char}");
                             word.push(char);
                         }
                     }
                     else{
-                        #[cfg(test)] println!("char pushed to word: {char}");
                         word.push(char);
                     }
                 }
@@ -321,17 +325,15 @@ pub fn parse_command(command_string: String) -> Result<Vec<Vec<Word>>, String>{
                 //    escape_next = false;
                 //}
                 else{
-                    #[cfg(test)] println!("char pushed to word: {char}");
                     word.push(char);
                 }
             }
             //| => {}
             _ => {
                 if inside_of_comment{
-                    #[cfg(test)] println!("char ignored as comment");
+                    //char ignored as comment
                 }
                 else if inside_of_quotations{
-                    #[cfg(test)] println!("char pushed to word: {char}");
                     word.push(char);
                 }
                 //else if escape_next{
@@ -340,24 +342,18 @@ pub fn parse_command(command_string: String) -> Result<Vec<Vec<Word>>, String>{
                 //    escape_next = false;
                 //}
                 else{
-                    #[cfg(test)] println!("char pushed to word: {char}");
                     word.push(char);
                 }
             }
         }
     }
     if !word.is_empty(){
-        #[cfg(test)] println!("word pushed to command: {:?}", word);
         command.push(Word{word_type: WordType::Unquoted, content: word});
     }
     if !command.is_empty(){
-        #[cfg(test)] println!("command pushed to commands: {:?}", command);
         commands.push(command);
     }
-    if commands.is_empty(){return Err(String::from("failed to parse string as commands"));}
-    #[cfg(test)] println!("commands: {:?}", commands);
-    #[cfg(test)] println!("");
-    Ok(commands)    
+    Ok((commands, start, end))
 }
 #[test]fn empty_command_string_should_error(){
     assert_eq!(Err(String::from("cannot parse empty string")), parse_command(String::from("")));
@@ -635,13 +631,42 @@ pub fn parse_command(command_string: String) -> Result<Vec<Vec<Word>>, String>{
         parse_command(String::from("echo %val{idk"))
     )
 }
+#[test] fn shell_string_single_quotes_quoted_words(){
+    assert_eq!(
+        "sed 's/a b/c/' > out",
+        to_shell_string(&parse_command(String::from("sed 's/a b/c/' > out")).unwrap()[0])
+    );
+    assert_eq!(
+        "echo 'it'\\''s'",
+        to_shell_string(&parse_command(String::from("echo \"it's\"")).unwrap()[0])
+    );
+}
 
+#[test] fn first_command_reports_where_it_starts_and_ends(){
+    let command_string = "  # comment\n  ! cd /tmp; make\nidk";
+    let (words, start, end) = parse_first_command(command_string).unwrap().unwrap();
+    assert_eq!(vec![Word{word_type: WordType::Unquoted, content: String::from("!")}, Word{word_type: WordType::Unquoted, content: String::from("cd")}, Word{word_type: WordType::Unquoted, content: String::from("/tmp")}], words);
+    assert_eq!("! cd /tmp; make\nidk", &command_string[start..]);
+    assert_eq!(" make\nidk", &command_string[end..]);
+    assert_eq!(Ok(None), parse_first_command(" ; # comment"));
+}
 
+//the command execution this parser was prototyped with. it predates the current config, user command, and option types, 
+//so it isn't compiled. `application::execute_words` is what executes parsed commands now
+#[cfg(any())]
+use crate::{
+    action::{Action, EditorAction}, 
+    application::Application, 
+    config::{Command, DisplayMode, OptionType}, 
+    selection::CursorSemantics, 
+    selections
+};
 //TODO: consider how to handle a failed command in a list of commands. should we just error on first failed command?...
 //TODO: execute_command should return a new instance of Application instead of modifying existing
 //that way, we could apply changes to the new instance, and if an error occurs, default back to the old instance with no changes
 //also, create a successful_commands counter. on each successful command, increment by 1;
 //if unsuccessful command, return "Error: {error} on command {counter + 1}"
+#[cfg(any())]
 fn execute_parsed_commands(app: &mut Application, commands: Vec<Vec<Word>>) -> Result<(), String>{//Result<Application, ApplicationError>{
     fn expand(app: &Application, word_content: &str, expansion_type: &ExpansionType) -> Result<String, String>{
         fn expand_option(app: &Application, option: String) -> Result<String, String>{
@@ -1,6 +1,7 @@
 use crate::{
     mode::Mode,
-    action::{Action, EditorAction, SelectionAction, EditAction, ViewAction, UtilAction}
+    action::{Action, EditorAction, SelectionAction, EditAction, ViewAction, UtilAction},
+    search::SearchFlag,
 };
 use crossterm::event::{KeyCode, KeyModifiers, KeyEvent};
 use indexmap::IndexMap;
@@ -45,10 +46,17 @@ pub fn default_keybinds() -> /*HashMap*/IndexMap<(Mode, KeyEvent), Action>{
     keybinds.insert((Mode::Insert, KeyEvent::new(KeyCode::Char('z'), KeyModifiers::CONTROL)), Action::EditAction(EditAction::Undo));
     keybinds.insert((Mode::Insert, KeyEvent::new(KeyCode::Char('/'), KeyModifiers::CONTROL)), Action::EditorAction(EditorAction::ModePush(Mode::Find, None)));
     keybinds.insert((Mode::Insert, KeyEvent::new(KeyCode::Char(','), KeyModifiers::CONTROL)), Action::EditorAction(EditorAction::ModePush(Mode::Split, None)));
+    keybinds.insert((Mode::Insert, KeyEvent::new(KeyCode::Char('.'), KeyModifiers::CONTROL)), Action::EditorAction(EditorAction::ModePush(Mode::Replace, None)));
     keybinds.insert((Mode::Insert, KeyEvent::new(KeyCode::Char(';'), KeyModifiers::CONTROL)), Action::EditorAction(EditorAction::ModePush(Mode::Command, None)));
         //handled in Application::handle_event()
         //keybinds.insert((Mode::Insert, KeyEvent::new(KeyCode::Char(c), KeyModifiers::SHIFT)), Action::EditAction(EditAction::InsertChar(c)));
     keybinds.insert((Mode::Insert, KeyEvent::new(KeyCode::Char('v'), KeyModifiers::ALT)), Action::ViewAction(ViewAction::CenterVerticallyAroundCursor));
+    keybinds.insert((Mode::Insert, KeyEvent::new(KeyCode::Char('r'), KeyModifiers::ALT)), Action::EditorAction(EditorAction::ModePush(Mode::Register, None)));
+    keybinds.insert((Mode::Insert, KeyEvent::new(KeyCode::Char('k'), KeyModifiers::ALT)), Action::EditorAction(EditorAction::ModePush(Mode::Keep, None)));
+    keybinds.insert((Mode::Insert, KeyEvent::new(KeyCode::Char('n'), KeyModifiers::ALT)), Action::SelectionAction(SelectionAction::SelectNextMatch, 1));
+    keybinds.insert((Mode::Insert, KeyEvent::new(KeyCode::Char('p'), KeyModifiers::ALT)), Action::SelectionAction(SelectionAction::SelectPreviousMatch, 1));
+    keybinds.insert((Mode::Insert, KeyEvent::new(KeyCode::Char('n'), KeyModifiers::CONTROL)), Action::SelectionAction(SelectionAction::AddNextMatch, 1));
+    keybinds.insert((Mode::Insert, KeyEvent::new(KeyCode::Char('d'), KeyModifiers::ALT)), Action::EditorAction(EditorAction::ModePush(Mode::Remove, None)));
         //handled in Application::handle_event()
         //keybinds.insert((Mode::Insert, KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE)), Action::EditAction(EditAction::InsertChar(c)));
     keybinds.insert((Mode::Insert, KeyEvent::new(KeyCode::PageDown, KeyModifiers::SHIFT)), Action::SelectionAction(SelectionAction::ExtendSelectionPageDown, 1));
@@ -147,6 +155,9 @@ pub fn default_keybinds() -> /*HashMap*/IndexMap<(Mode, KeyEvent), Action>{
     keybinds.insert((Mode::Find, KeyEvent::new(KeyCode::Backspace, KeyModifiers::NONE)), Action::UtilAction(UtilAction::Backspace));
     keybinds.insert((Mode::Find, KeyEvent::new(KeyCode::Delete, KeyModifiers::NONE)), Action::UtilAction(UtilAction::Delete));
     keybinds.insert((Mode::Find, KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE)), Action::UtilAction(UtilAction::Exit));
+    keybinds.insert((Mode::Find, KeyEvent::new(KeyCode::Char('l'), KeyModifiers::ALT)), Action::UtilAction(UtilAction::ToggleSearchFlag(SearchFlag::Literal)));
+    keybinds.insert((Mode::Find, KeyEvent::new(KeyCode::Char('i'), KeyModifiers::ALT)), Action::UtilAction(UtilAction::ToggleSearchFlag(SearchFlag::IgnoreCase)));
+    keybinds.insert((Mode::Find, KeyEvent::new(KeyCode::Char('w'), KeyModifiers::ALT)), Action::UtilAction(UtilAction::ToggleSearchFlag(SearchFlag::Word)));
 
     //TODO: set warning if util text invalid
     keybinds.insert((Mode::Split, KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE)), Action::UtilAction(UtilAction::Accept));
@@ -168,6 +179,66 @@ pub fn default_keybinds() -> /*HashMap*/IndexMap<(Mode, KeyEvent), Action>{
     keybinds.insert((Mode::Split, KeyEvent::new(KeyCode::Delete, KeyModifiers::NONE)), Action::UtilAction(UtilAction::Delete));
     keybinds.insert((Mode::Split, KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE)), Action::UtilAction(UtilAction::Exit));
 
+    //TODO: set warning if util text invalid
+    keybinds.insert((Mode::Keep, KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE)), Action::UtilAction(UtilAction::Accept));
+    keybinds.insert((Mode::Keep, KeyEvent::new(KeyCode::Right, KeyModifiers::SHIFT)), Action::UtilAction(UtilAction::ExtendRight));
+    keybinds.insert((Mode::Keep, KeyEvent::new(KeyCode::Right, KeyModifiers::NONE)), Action::UtilAction(UtilAction::MoveRight));
+    keybinds.insert((Mode::Keep, KeyEvent::new(KeyCode::Left, KeyModifiers::SHIFT)), Action::UtilAction(UtilAction::ExtendLeft));
+    keybinds.insert((Mode::Keep, KeyEvent::new(KeyCode::Left, KeyModifiers::NONE)), Action::UtilAction(UtilAction::MoveLeft));
+    keybinds.insert((Mode::Keep, KeyEvent::new(KeyCode::Home, KeyModifiers::SHIFT)), Action::UtilAction(UtilAction::ExtendHome));
+    keybinds.insert((Mode::Keep, KeyEvent::new(KeyCode::Home, KeyModifiers::NONE)), Action::UtilAction(UtilAction::MoveHome));
+    keybinds.insert((Mode::Keep, KeyEvent::new(KeyCode::End, KeyModifiers::SHIFT)), Action::UtilAction(UtilAction::ExtendEnd));
+    keybinds.insert((Mode::Keep, KeyEvent::new(KeyCode::End, KeyModifiers::NONE)), Action::UtilAction(UtilAction::MoveEnd));
+    keybinds.insert((Mode::Keep, KeyEvent::new(KeyCode::Char('x'), KeyModifiers::CONTROL)), Action::UtilAction(UtilAction::Cut));
+    keybinds.insert((Mode::Keep, KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL)), Action::UtilAction(UtilAction::Copy));
+    keybinds.insert((Mode::Keep, KeyEvent::new(KeyCode::Char('v'), KeyModifiers::CONTROL)), Action::UtilAction(UtilAction::Paste));
+        //handled in Application::handle_event()
+        //keybinds.insert((Mode::Keep, KeyEvent::new(KeyCode::Char(c), KeyModifiers::SHIFT)), Action::UtilAction(UtilAction::InsertChar(c)));
+        //keybinds.insert((Mode::Keep, KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE)), Action::UtilAction(UtilAction::InsertChar(c)));
+    keybinds.insert((Mode::Keep, KeyEvent::new(KeyCode::Backspace, KeyModifiers::NONE)), Action::UtilAction(UtilAction::Backspace));
+    keybinds.insert((Mode::Keep, KeyEvent::new(KeyCode::Delete, KeyModifiers::NONE)), Action::UtilAction(UtilAction::Delete));
+    keybinds.insert((Mode::Keep, KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE)), Action::UtilAction(UtilAction::Exit));
+
+    //TODO: set warning if util text invalid
+    keybinds.insert((Mode::Remove, KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE)), Action::UtilAction(UtilAction::Accept));
+    keybinds.insert((Mode::Remove, KeyEvent::new(KeyCode::Right, KeyModifiers::SHIFT)), Action::UtilAction(UtilAction::ExtendRight));
+    keybinds.insert((Mode::Remove, KeyEvent::new(KeyCode::Right, KeyModifiers::NONE)), Action::UtilAction(UtilAction::MoveRight));
+    keybinds.insert((Mode::Remove, KeyEvent::new(KeyCode::Left, KeyModifiers::SHIFT)), Action::UtilAction(UtilAction::ExtendLeft));
+    keybinds.insert((Mode::Remove, KeyEvent::new(KeyCode::Left, KeyModifiers::NONE)), Action::UtilAction(UtilAction::MoveLeft));
+    keybinds.insert((Mode::Remove, KeyEvent::new(KeyCode::Home, KeyModifiers::SHIFT)), Action::UtilAction(UtilAction::ExtendHome));
+    keybinds.insert((Mode::Remove, KeyEvent::new(KeyCode::Home, KeyModifiers::NONE)), Action::UtilAction(UtilAction::MoveHome));
+    keybinds.insert((Mode::Remove, KeyEvent::new(KeyCode::End, KeyModifiers::SHIFT)), Action::UtilAction(UtilAction::ExtendEnd));
+    keybinds.insert((Mode::Remove, KeyEvent::new(KeyCode::End, KeyModifiers::NONE)), Action::UtilAction(UtilAction::MoveEnd));
+    keybinds.insert((Mode::Remove, KeyEvent::new(KeyCode::Char('x'), KeyModifiers::CONTROL)), Action::UtilAction(UtilAction::Cut));
+    keybinds.insert((Mode::Remove, KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL)), Action::UtilAction(UtilAction::Copy));
+    keybinds.insert((Mode::Remove, KeyEvent::new(KeyCode::Char('v'), KeyModifiers::CONTROL)), Action::UtilAction(UtilAction::Paste));
+        //handled in Application::handle_event()
+        //keybinds.insert((Mode::Remove, KeyEvent::new(KeyCode::Char(c), KeyModifiers::SHIFT)), Action::UtilAction(UtilAction::InsertChar(c)));
+        //keybinds.insert((Mode::Remove, KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE)), Action::UtilAction(UtilAction::InsertChar(c)));
+    keybinds.insert((Mode::Remove, KeyEvent::new(KeyCode::Backspace, KeyModifiers::NONE)), Action::UtilAction(UtilAction::Backspace));
+    keybinds.insert((Mode::Remove, KeyEvent::new(KeyCode::Delete, KeyModifiers::NONE)), Action::UtilAction(UtilAction::Delete));
+    keybinds.insert((Mode::Remove, KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE)), Action::UtilAction(UtilAction::Exit));
+
+    //TODO: set warning if util text invalid
+    keybinds.insert((Mode::Replace, KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE)), Action::UtilAction(UtilAction::Accept));
+    keybinds.insert((Mode::Replace, KeyEvent::new(KeyCode::Right, KeyModifiers::SHIFT)), Action::UtilAction(UtilAction::ExtendRight));
+    keybinds.insert((Mode::Replace, KeyEvent::new(KeyCode::Right, KeyModifiers::NONE)), Action::UtilAction(UtilAction::MoveRight));
+    keybinds.insert((Mode::Replace, KeyEvent::new(KeyCode::Left, KeyModifiers::SHIFT)), Action::UtilAction(UtilAction::ExtendLeft));
+    keybinds.insert((Mode::Replace, KeyEvent::new(KeyCode::Left, KeyModifiers::NONE)), Action::UtilAction(UtilAction::MoveLeft));
+    keybinds.insert((Mode::Replace, KeyEvent::new(KeyCode::Home, KeyModifiers::SHIFT)), Action::UtilAction(UtilAction::ExtendHome));
+    keybinds.insert((Mode::Replace, KeyEvent::new(KeyCode::Home, KeyModifiers::NONE)), Action::UtilAction(UtilAction::MoveHome));
+    keybinds.insert((Mode::Replace, KeyEvent::new(KeyCode::End, KeyModifiers::SHIFT)), Action::UtilAction(UtilAction::ExtendEnd));
+    keybinds.insert((Mode::Replace, KeyEvent::new(KeyCode::End, KeyModifiers::NONE)), Action::UtilAction(UtilAction::MoveEnd));
+    keybinds.insert((Mode::Replace, KeyEvent::new(KeyCode::Char('x'), KeyModifiers::CONTROL)), Action::UtilAction(UtilAction::Cut));
+    keybinds.insert((Mode::Replace, KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL)), Action::UtilAction(UtilAction::Copy));
+    keybinds.insert((Mode::Replace, KeyEvent::new(KeyCode::Char('v'), KeyModifiers::CONTROL)), Action::UtilAction(UtilAction::Paste));
+        //handled in Application::handle_event()
+        //keybinds.insert((Mode::Replace, KeyEvent::new(KeyCode::Char(c), KeyModifiers::SHIFT)), Action::UtilAction(UtilAction::InsertChar(c)));
+        //keybinds.insert((Mode::Replace, KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE)), Action::UtilAction(UtilAction::InsertChar(c)));
+    keybinds.insert((Mode::Replace, KeyEvent::new(KeyCode::Backspace, KeyModifiers::NONE)), Action::UtilAction(UtilAction::Backspace));
+    keybinds.insert((Mode::Replace, KeyEvent::new(KeyCode::Delete, KeyModifiers::NONE)), Action::UtilAction(UtilAction::Delete));
+    keybinds.insert((Mode::Replace, KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE)), Action::UtilAction(UtilAction::Exit));
+
     //TODO: set warning if util text invalid
     keybinds.insert((Mode::Command, KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE)), Action::UtilAction(UtilAction::Accept));
     keybinds.insert((Mode::Command, KeyEvent::new(KeyCode::Right, KeyModifiers::SHIFT)), Action::UtilAction(UtilAction::ExtendRight));
@@ -212,6 +283,10 @@ pub fn default_keybinds() -> /*HashMap*/IndexMap<(Mode, KeyEvent), Action>{
     keybinds.insert((Mode::AddSurround, KeyEvent::new(KeyCode::Char('<'), KeyModifiers::NONE)), Action::EditAction(EditAction::AddSurround('<', '>')));
     keybinds.insert((Mode::AddSurround, KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE)), Action::EditorAction(EditorAction::ModePop));
 
+        //handled in Application::handle_event()
+        //keybinds.insert((Mode::Register, KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE)), Action::EditorAction(EditorAction::SelectRegister(c)));
+    keybinds.insert((Mode::Register, KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE)), Action::EditorAction(EditorAction::ModePop));
+
     //for when suggestion mode impled
     //keybinds.insert((Mode::Suggestion, KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE)), Action::EditorAction(EditorAction::ModePop));
     //keybinds.insert((Mode::Suggestion, KeyEvent::new(KeyCode::Tab, KeyModifiers::NONE)), Action::EditAction(EditAction::AcceptSuggestion));
@@ -221,3 +296,80 @@ pub fn default_keybinds() -> /*HashMap*/IndexMap<(Mode, KeyEvent), Action>{
 
     keybinds
 }
+
+/// Formats a [`KeyEvent`] as a key chord string, with modifiers preceding the key name. e.g. "ctrl-shift-enter"
+pub fn key_event_to_string(key_event: &KeyEvent) -> String{
+    let mut chord = String::new();
+    if key_event.modifiers.contains(KeyModifiers::CONTROL){chord.push_str("ctrl-");}
+    if key_event.modifiers.contains(KeyModifiers::ALT){chord.push_str("alt-");}
+    if key_event.modifiers.contains(KeyModifiers::SHIFT){chord.push_str("shift-");}
+    match key_event.code{
+        KeyCode::Char(' ') => chord.push_str("space"),
+        KeyCode::Char('-') => chord.push_str("minus"),  //'-' separates modifiers
+        KeyCode::Char(c) => chord.push(c),
+        KeyCode::F(n) => chord.push_str(&format!("f{n}")),
+        KeyCode::Enter => chord.push_str("enter"),
+        KeyCode::Esc => chord.push_str("esc"),
+        KeyCode::Tab => chord.push_str("tab"),
+        KeyCode::BackTab => chord.push_str("backtab"),
+        KeyCode::Backspace => chord.push_str("backspace"),
+        KeyCode::Delete => chord.push_str("delete"),
+        KeyCode::Insert => chord.push_str("insert"),
+        KeyCode::Home => chord.push_str("home"),
+        KeyCode::End => chord.push_str("end"),
+        KeyCode::PageUp => chord.push_str("pageup"),
+        KeyCode::PageDown => chord.push_str("pagedown"),
+        KeyCode::Up => chord.push_str("up"),
+        KeyCode::Down => chord.push_str("down"),
+        KeyCode::Left => chord.push_str("left"),
+        KeyCode::Right => chord.push_str("right"),
+        other => chord.push_str(&format!("{other:?}").to_lowercase()),
+    }
+    chord
+}
+/// Parses a key chord string, as formatted by [`key_event_to_string`], into a [`KeyEvent`]. e.g. "ctrl-shift-enter" or "alt-r"
+pub fn string_to_key_event(chord: &str) -> Result<KeyEvent, String>{
+    let mut parts: Vec<&str> = chord.split('-').collect();
+    let key = parts.pop().unwrap_or_default();  //split always returns at least one item
+    let mut modifiers = KeyModifiers::NONE;
+    for modifier in parts{
+        match modifier{
+            "ctrl" => modifiers = modifiers | KeyModifiers::CONTROL,
+            "alt" => modifiers = modifiers | KeyModifiers::ALT,
+            "shift" => modifiers = modifiers | KeyModifiers::SHIFT,
+            _ => return Err(format!("{:?} is not a valid modifier in key chord {:?}", modifier, chord))
+        }
+    }
+    let code = match key{
+        "space" => KeyCode::Char(' '),
+        "minus" => KeyCode::Char('-'),
+        "enter" => KeyCode::Enter,
+        "esc" => KeyCode::Esc,
+        "tab" => KeyCode::Tab,
+        "backtab" => KeyCode::BackTab,
+        "backspace" => KeyCode::Backspace,
+        "delete" => KeyCode::Delete,
+        "insert" => KeyCode::Insert,
+        "home" => KeyCode::Home,
+        "end" => KeyCode::End,
+        "pageup" => KeyCode::PageUp,
+        "pagedown" => KeyCode::PageDown,
+        "up" => KeyCode::Up,
+        "down" => KeyCode::Down,
+        "left" => KeyCode::Left,
+        "right" => KeyCode::Right,
+        _ => {
+            let mut chars = key.chars();
+            match (chars.next(), chars.next()){
+                (Some(c), None) => KeyCode::Char(c),
+                _ => {
+                    match key.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()){
+                        Some(n) => KeyCode::F(n),
+                        None => return Err(format!("{:?} is not a valid key in key chord {:?}", key, chord))
+                    }
+                }
+            }
+        }
+    };
+    Ok(KeyEvent::new(code, modifiers))
+}
@@ -24,5 +24,17 @@ pub mod ui;
 pub mod config;
 pub mod tutorial;
 pub mod mode_stack;
+pub mod file_server;
+pub mod event_log;
+pub mod instance;
+pub mod registers;
+pub mod clipboard;
+pub mod user_commands;
+pub mod hooks;
+pub mod user_options;
+pub mod shell;
+pub mod sam;
+pub mod search;
+pub mod kakoune_style_commands;
 
 #[cfg(test)] mod tests;
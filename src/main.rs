@@ -17,6 +17,7 @@ use ratatui::{
 };
 use edit::{
     application::{Application, Event},
+    instance::{self, Instance},
     //config::CURSOR_STYLE
 };
 
@@ -265,6 +266,7 @@ fn main() -> Result<(), String>{
 
     //TODO: could pass column_number, line_number and regex here, returning Err() if invalid positions...
     //enum OpenPosition{Point{line: u16, column: u16}, Regex{regex: String}},   open_position: Option<OpenPosition>
+    let instance_file_path = file_path.clone();
     let mut app = match Application::new(config, &buffer_text, file_path, read_only, &terminal){
         Err(e) => return post_terminal_setup_error(&e, false, &mut terminal),
         Ok(app) => app
@@ -276,6 +278,8 @@ fn main() -> Result<(), String>{
     // maybe in new and hold handles in app struct, or in app.run before the loop
     // then app can check handle status in the run loop, and handle panic/errors?...
     let (event_tx, event_rx) = mpsc::channel::<Event>();
+    //shell commands run on worker threads, which send their results back through this channel
+    app.shell.set_event_sender(event_tx.clone());
     
     //input thread
     let input_event_tx = event_tx.clone();
@@ -284,14 +288,22 @@ fn main() -> Result<(), String>{
     });
 
     //9p thread
-//    let ninep_event_tx = event_tx.clone();
-//    let listener = match setup_listener(){
-//        Err(e) => return post_terminal_setup_error(&format!("{e}"), false, &mut terminal),
-//        Ok(listener) => listener
-//    };
-//    let ninep_thread_handle = thread::spawn(||{
-//        handle_ninep_events(listener, ninep_event_tx);
-//    });
+    let ninep_event_tx = event_tx.clone();
+    let runtime_dir = match instance::runtime_dir(){
+        Err(e) => return post_terminal_setup_error(&format!("could not serve 9p file system: {e}"), false, &mut terminal),
+        Ok(runtime_dir) => runtime_dir
+    };
+    let (instance, listener) = match Instance::register(&runtime_dir, std::process::id(), instance_file_path.as_deref()){
+        Err(e) => return post_terminal_setup_error(&e, false, &mut terminal),
+        Ok(registered) => registered
+    };
+    //socket and index entry are removed when this goes out of scope, on quit or early return
+    let _instance_cleanup = InstanceCleanup(instance.clone());
+    app.instance = Some(instance.clone());
+    set_instance_cleanup_panic_hook(instance);
+    let _ninep_thread_handle = thread::spawn(||{
+        handle_ninep_events(listener, ninep_event_tx);
+    });
     
     if let Err(e) = app.run(&mut terminal, event_rx){
         return post_terminal_setup_error(&e, false, &mut terminal);
@@ -304,20 +316,9 @@ fn main() -> Result<(), String>{
 }
 
 use std::os::unix::net::UnixListener;
-use std::path::Path;
-fn setup_listener() -> Result<UnixListener, String>{
-    //let socket_path = "/tmp/ns.j.:1/edit9p.sock";
-    let socket_path = "/tmp/ns.j.:0/edit9p.sock";
-    //let socket_path = "/tmp/edit9p.sock";
-    if Path::new(socket_path).exists(){
-        if let Err(e) = std::fs::remove_file(socket_path){
-            return Err(format!("edit::main.rs::setup_listener::remove_file: {e}"));
-        }
-    }
-    match UnixListener::bind(socket_path){
-        Err(e) => Err(format!("edit::main.rs::setup_listener::bind {e}")),
-        Ok(listener) => Ok(listener)
-    }
+struct InstanceCleanup(Instance);
+impl Drop for InstanceCleanup{
+    fn drop(&mut self){self.0.cleanup();}
 }
 
 //TODO: blocking read in the terminal_events thread meant we had a signifigant lag when main thread exits.
@@ -347,11 +348,34 @@ fn handle_terminal_events(event_tx: mpsc::Sender<Event>){
 fn handle_ninep_events(listener: UnixListener, event_tx: mpsc::Sender<Event>){
     use serve9p::file_system::FsRequest;
 
-    let (fs_tx, fs_rx) = mpsc::channel::<FsRequest>();
+    let mut next_connection_id: u64 = 0;
     loop{
         //serve9p handler
-        let (stream, _) = listener.accept().unwrap();//map_err(|e| e.to_string())?;
-        let connection_tx = fs_tx.clone();
+        let stream = match listener.accept(){
+            //errors like running out of file descriptors can persist. wait before retrying, instead of spinning
+            Err(_) => {
+                thread::sleep(std::time::Duration::from_millis(100));
+                continue;
+            }
+            Ok((stream, _)) => stream
+        };
+        let connection_id = next_connection_id;
+        next_connection_id += 1;
+        let (connection_tx, fs_rx) = mpsc::channel::<FsRequest>();
+
+        //intermediate handler
+        //receive fs_request from serve9p, and forward to editor, tagged with the connection it arrived on.
+        //the reply channel travels with the request, so the editor responds to serve9p directly
+        let event_tx = event_tx.clone();
+        thread::spawn(move || {
+            while let Ok(fs_request) = fs_rx.recv(){
+                if event_tx.send(Event::NineP(connection_id, fs_request)).is_err(){
+                    break;  //editor has exited
+                }
+            }
+            //the connection has closed(its request sender was dropped), or the editor has exited
+            let _ = event_tx.send(Event::NinePDisconnected(connection_id));
+        });
 
         //thread per connection (or async in future?...)
         thread::spawn(move || {
@@ -375,10 +399,10 @@ fn handle_ninep_events(listener: UnixListener, event_tx: mpsc::Sender<Event>){
                     Some(framed_message) => {
                         let t_msg = Tmessage::decode(&framed_message)?;
                         let r_msg = connection.handle(t_msg, &connection_tx);
-                        let reply = r_msg.clone().encode();
-                        //log response
-                        println!("Server responded: \n{:?}", reply);
-                        println!("{:#?}\n", r_msg);
+                        let reply = r_msg.encode();
+                        //log response  //printing here would draw over the tui
+                        //println!("Server responded: \n{:?}", reply);
+                        //println!("{:#?}\n", r_msg);
                         match transport.write_from(&reply){
                             Err(e) => return Err(e.to_string()),
                             Ok(_bytes_written) => {}
@@ -387,29 +411,6 @@ fn handle_ninep_events(listener: UnixListener, event_tx: mpsc::Sender<Event>){
                 }
             }
         });
-
-        //intermediate handler
-        //receive fs_request from serve9p
-        match fs_rx.recv().unwrap(){
-            FsRequest::Attach{uname, aname, reply} => {
-                //create intermediate channel
-                let (intermediate_tx, intermediate_rx) = mpsc::channel();
-                //wrap in event
-                let fs_request_event = Event::NineP(FsRequest::Attach{uname, aname, reply: intermediate_tx});
-                //send to editor
-                event_tx.send(fs_request_event).unwrap();
-                //get result from editor
-                let result = intermediate_rx.recv().unwrap();
-                //send result back to serve9p for writing to transport
-                reply.send(result).unwrap();
-            }
-            FsRequest::Walk{user, qid_path, wnames, reply} => {}
-            FsRequest::Open{user, qid_path, mode, reply} => {}
-            FsRequest::Read{qid_path, offset, count, reply} => {}
-            FsRequest::Write{qid_path, offset, data, reply} => {}
-            FsRequest::Clunk{qid_path, reply} => {}
-            FsRequest::Stat{qid_path, reply} => {}
-        };
     }
 }
 
@@ -426,6 +427,16 @@ fn set_panic_hook(){
     }));
 }
 
+fn set_instance_cleanup_panic_hook(instance: Instance){
+    //InstanceCleanup's drop doesn't run if built with panic = "abort", so clean up here as well
+    //panics on other threads(such as a 9p connection) don't end the editor, so the socket should stay
+    let previous_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        if thread::current().name() == Some("main"){instance.cleanup();}
+        previous_hook(info);
+    }));
+}
+
 fn setup_terminal() -> Result<Terminal<CrosstermBackend<Stdout>>, Error>{
     let mut stdout = io::stdout();
     terminal::enable_raw_mode()?;
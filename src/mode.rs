@@ -40,6 +40,15 @@ pub enum Mode{
     
     /// for retaining everything within selections that isn't a matching regex pattern
     Split,
+
+    /// for keeping only the selections whose text matches a regex pattern
+    Keep,
+
+    /// for removing the selections whose text matches a regex pattern
+    Remove,
+
+    /// for replacing regex matches within selections(or the whole buffer) with a template, previewing the result as it is typed
+    Replace,
     
     /// for selecting text objects
     Object,
@@ -47,11 +56,10 @@ pub enum Mode{
     /// for inserting bracket pairs around selection(s) contents
     AddSurround,    //maybe change to AddSurroundingPair or AddBracketPair
 
+    /// for choosing the register used by the next copy, cut or paste
+    Register,
+
     // NOTE: may not ever implement the following, but good to think about...
-    //select the next occurring instance of a search pattern
-    //SearchNextAhead,
-    //select the prev occurring instance of a search pattern
-    //SearchPrevBehind
     //select until the next occuring instance of a search pattern
     //SelectUntilNext,
     //select until the prev occuring instance of a search pattern
@@ -60,3 +68,28 @@ pub enum Mode{
     //ReplaceSelectionWithShellCommandOutputNoContext,
     //ReplaceSelectionWithShellCommandOutputWithContext,
 }
+impl std::str::FromStr for Mode{
+    type Err = String;
+    /// Parses a mode from its name, as displayed by [`Debug`]. For example, `Insert` or `Command`.
+    fn from_str(s: &str) -> Result<Self, Self::Err>{
+        match s{
+            "Insert" => Ok(Mode::Insert),
+            "Error" => Ok(Mode::Error),
+            "Warning" => Ok(Mode::Warning),
+            "Notify" => Ok(Mode::Notify),
+            "Info" => Ok(Mode::Info),
+            "View" => Ok(Mode::View),
+            "Goto" => Ok(Mode::Goto),
+            "Command" => Ok(Mode::Command),
+            "Find" => Ok(Mode::Find),
+            "Split" => Ok(Mode::Split),
+            "Keep" => Ok(Mode::Keep),
+            "Remove" => Ok(Mode::Remove),
+            "Replace" => Ok(Mode::Replace),
+            "Object" => Ok(Mode::Object),
+            "AddSurround" => Ok(Mode::AddSurround),
+            "Register" => Ok(Mode::Register),
+            _ => Err(format!("unknown mode: {s}"))
+        }
+    }
+}
@@ -0,0 +1,102 @@
+//! Named storage for text, to be copied into, and pasted from.
+//! Registers `a` through `z` always exist. More can be added, and removed, at runtime(such as through the served `registers/add` and 
+//! `registers/del` files). Special registers are updated by the editor itself, and cannot be removed.
+
+use indexmap::IndexMap;
+
+
+
+/// Holds the most recently copied or cut text. Used by copy, cut and paste when no register is specified.
+pub const YANK_REGISTER: &str = "yank";
+/// Holds the most recently searched regex.
+pub const SEARCH_REGISTER: &str = "search";
+/// Holds the most recently executed command.
+pub const COMMAND_REGISTER: &str = "command";
+const SPECIAL_REGISTERS: [&str; 3] = [YANK_REGISTER, SEARCH_REGISTER, COMMAND_REGISTER];
+
+struct Register{
+    /// Unique for the lifetime of the editor, even if the register is removed. Used to identify the register's served files.
+    id: u64,
+    /// One value per selection it was copied from. Usually just one.
+    values: Vec<String>,
+    /// `values` joined by newlines, for when they can't be used individually.
+    content: String,
+}
+pub struct Registers{
+    /// Keyed by register name, in the order registers were added.
+    registers: IndexMap<String, Register>,
+    next_id: u64,
+}
+impl Default for Registers{
+    fn default() -> Self{
+        let mut registers = Self{registers: IndexMap::new(), next_id: 0};
+        for name in ('a'..='z').map(|c| c.to_string()).chain(SPECIAL_REGISTERS.iter().map(|name| name.to_string())){
+            registers.insert(name);
+        }
+        registers
+    }
+}
+impl Registers{
+    fn insert(&mut self, name: String){
+        self.registers.insert(name, Register{id: self.next_id, values: vec![String::new()], content: String::new()});
+        self.next_id = self.next_id + 1;
+    }
+    pub fn get(&self, name: &str) -> Option<&str>{
+        self.registers.get(name).map(|register| register.content.as_str())
+    }
+    /// Replaces the content of an existing register.
+    pub fn set(&mut self, name: &str, content: &str) -> Result<(), String>{
+        match self.registers.get_mut(name){
+            None => Err(format!("register {} does not exist", name)),
+            Some(register) => {
+                register.values = vec![content.to_string()];
+                register.content = content.to_string();
+                Ok(())
+            }
+        }
+    }
+    /// Per selection values stored in the register. A register set with a single value returns a single element slice.
+    pub fn values(&self, name: &str) -> Option<&[String]>{
+        self.registers.get(name).map(|register| register.values.as_slice())
+    }
+    /// Replaces the content of an existing register with one value per selection.
+    pub fn set_values(&mut self, name: &str, values: Vec<String>) -> Result<(), String>{
+        match self.registers.get_mut(name){
+            None => Err(format!("register {} does not exist", name)),
+            Some(register) => {
+                register.content = values.join("\n");
+                register.values = values;
+                Ok(())
+            }
+        }
+    }
+    /// Adds an empty register. Names may not be empty, or contain whitespace or '/', so they can be served as directory names.
+    pub fn add(&mut self, name: &str) -> Result<(), String>{
+        if name.is_empty() || name.contains('/') || name.chars().any(|c| c.is_whitespace()){
+            return Err(format!("{:?} is not a valid register name", name));
+        }
+        if self.registers.contains_key(name){return Err(format!("register {} already exists", name));}
+        self.insert(name.to_string());
+        Ok(())
+    }
+    /// Removes an added register. Registers `a` through `z`, and special registers, cannot be removed.
+    pub fn remove(&mut self, name: &str) -> Result<(), String>{
+        if !self.registers.contains_key(name){return Err(format!("register {} does not exist", name));}
+        let is_lettered = name.len() == 1 && name.chars().all(|c| c.is_ascii_lowercase());
+        if is_lettered || SPECIAL_REGISTERS.contains(&name){return Err(format!("register {} cannot be removed", name));}
+        self.registers.shift_remove(name);
+        Ok(())
+    }
+    /// Register names, in the order registers were added.
+    pub fn names(&self) -> impl Iterator<Item = &str>{
+        self.registers.keys().map(|name| name.as_str())
+    }
+    pub fn contains(&self, name: &str) -> bool{self.registers.contains_key(name)}
+    pub fn id(&self, name: &str) -> Option<u64>{
+        self.registers.get(name).map(|register| register.id)
+    }
+    /// Returns the name of the register with `id`, if it has not been removed.
+    pub fn name(&self, id: u64) -> Option<&str>{
+        self.registers.iter().find(|(_, register)| register.id == id).map(|(name, _)| name.as_str())
+    }
+}
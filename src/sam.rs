@@ -0,0 +1,630 @@
+//! Sam style structural regular expressions, run with the `Edit` command. See sam(1), and Rob Pike's "Structural Regular Expressions".
+//! A command is an optional address, followed by a command, which may itself be an address and a command, and so on.
+//!     addresses:  `.`(dot) `$`(end) `n`(line n) `#n`(char n) `/re/`(next match) `?re?`(previous match)
+//!                 `a+b` `a-b`(b, relative to a) `a,b`(from a to b) `a;b`(from a to b, with b relative to a)
+//!     commands:   `x/re/ cmd` `y/re/ cmd` `g/re/ cmd` `v/re/ cmd` `s/re/text/[g]` `a/text/` `i/text/` `c/text/` `d` `|shell command`
+//! The command runs once per selection, with dot set to the selection. Commands never modify the text directly. Their changes are
+//! collected as [`Edit`]s, all relative to the unchanged text, so that the caller can apply them as a single change.
+
+use regex::{Captures, Regex, RegexBuilder};
+use crate::range::Range;
+
+
+
+const ADDRESS_OUT_OF_RANGE: &str = "address out of range";
+
+/// Replacement of the text in `range`(char indices of the unchanged text) with `text`.
+#[derive(Clone, PartialEq, Debug)] pub struct Edit{
+    pub range: Range,
+    pub text: String,
+}
+
+/// What a command did.
+#[derive(Default, PartialEq, Debug)] pub struct Outcome{
+    /// Changes to make, sorted by position. They never overlap.
+    pub edits: Vec<Edit>,
+    /// Ranges to select once the edits are made, in char indices of the changed text. Sorted by position.
+    pub selections: Vec<Range>,
+}
+impl Outcome{
+    /// The smallest range of `text` containing every edit, and the text replacing it once the edits are made.
+    /// None if there are no edits.
+    pub fn replacement(&self, text: &str) -> Option<(Range, String)>{
+        let start = self.edits.first()?.range.start;
+        let end = self.edits.last()?.range.end;
+        let offsets = char_offsets(text);
+        let mut replacement = String::new();
+        let mut position = start;
+        for edit in &self.edits{
+            replacement.push_str(&text[offsets[position]..offsets[edit.range.start]]);
+            replacement.push_str(&edit.text);
+            position = edit.range.end;
+        }
+        Some((Range::new(start, end), replacement))
+    }
+    /// Maps `position`, a char index of the unchanged text, into the changed text. A position within an edit's range maps
+    /// to the start of the edit's text.
+    pub fn map_position(&self, position: usize) -> usize{
+        let mut mapped = position;
+        for edit in &self.edits{
+            if edit.range.start >= position{break;}
+            let end = edit.range.end.min(position);
+            mapped -= end - edit.range.start;
+            if edit.range.end <= position{mapped += edit.text.chars().count();}
+        }
+        mapped
+    }
+}
+
+/// Runs `command` over `text`, once per range in `dots`. Ranges are char indices.
+/// `pipe` runs a shell command with the given stdin, returning its stdout. It is used by the `|` command.
+pub fn execute(command: &str, text: &str, dots: &[Range], pipe: &mut dyn FnMut(&str, &str) -> Result<String, String>) -> Result<Outcome, String>{
+    let command = Parser::new(command).parse()?;
+    let mut evaluator = Evaluator::new(text, pipe);
+    //selections resolving to the same address(with an absolute address, like `,`) would otherwise make the same changes repeatedly
+    let mut evaluated: Vec<Range> = Vec::new();
+    for dot in dots{
+        let dot = match &command.address{
+            None => dot.clone(),
+            Some(address) => evaluator.address(address, dot)?
+        };
+        if evaluated.contains(&dot){continue;}
+        evaluated.push(dot.clone());
+        evaluator.run_kind(&command.kind, dot)?;
+    }
+    evaluator.finish()
+}
+
+/// Byte index of each char in `text`, followed by the length of `text` in bytes.
+fn char_offsets(text: &str) -> Vec<usize>{
+    text.char_indices().map(|(byte_index, _)| byte_index).chain(std::iter::once(text.len())).collect()
+}
+/// Multi line mode makes `^` and `$` match at line boundaries, as they do in sam.
+fn compile(pattern: &str) -> Result<Regex, String>{
+    if pattern.is_empty(){return Err(String::from("empty regex"));}
+    RegexBuilder::new(pattern).multi_line(true).build().map_err(|error| format!("{}", error))
+}
+/// Expands `template` for a match. `&` is the whole match, `\1` through `\9` are capture groups, and `\n` is a newline.
+/// `\&` and `\\` are a literal `&` and `\`.
+fn expand(template: &str, captures: &Captures) -> String{
+    let mut expanded = String::new();
+    let mut chars = template.chars();
+    while let Some(char) = chars.next(){
+        match char{
+            '&' => expanded.push_str(captures.get(0).map_or("", |found| found.as_str())),
+            '\\' => {
+                match chars.next(){
+                    None => expanded.push('\\'),
+                    Some('n') => expanded.push('\n'),
+                    Some(digit) if digit.is_ascii_digit() => {
+                        let group = digit.to_digit(10).unwrap_or(0) as usize;
+                        expanded.push_str(captures.get(group).map_or("", |found| found.as_str()));
+                    }
+                    Some(other) => expanded.push(other),
+                }
+            }
+            _ => expanded.push(char)
+        }
+    }
+    expanded
+}
+
+enum Address{
+    Dot,
+    End,
+    Line(usize),
+    Char(usize),
+    Search{regex: Regex, forward: bool},
+    /// `offset` evaluated forward from the end of `base`, or backward from its start.
+    Relative{base: Box<Address>, forward: bool, offset: Box<Address>},
+    /// From the start of `left` to the end of `right`. `right` is evaluated with dot set to `left` if `right_follows_left`.
+    Span{left: Box<Address>, right: Box<Address>, right_follows_left: bool},
+}
+
+enum CommandKind{
+    /// An address on its own selects it.
+    Select,
+    /// `x` runs `body` for each match. `y` runs it for the text between matches. Without a body, the ranges are selected.
+    Loop{regex: Regex, between_matches: bool, body: Option<Box<Command>>},
+    /// `g` runs `body` if dot contains a match. `v` runs it if dot does not. Without a body, dot is selected.
+    Guard{regex: Regex, if_matching: bool, body: Option<Box<Command>>},
+    Substitute{regex: Regex, template: String, global: bool},
+    Append(String),
+    Insert(String),
+    Change(String),
+    Delete,
+    Pipe(String),
+}
+
+struct Command{
+    address: Option<Address>,
+    kind: CommandKind,
+}
+
+struct Parser{
+    chars: Vec<char>,
+    position: usize,
+}
+impl Parser{
+    fn new(command: &str) -> Self{
+        Self{chars: command.chars().collect(), position: 0}
+    }
+    fn peek(&self) -> Option<char>{
+        self.chars.get(self.position).copied()
+    }
+    fn next(&mut self) -> Option<char>{
+        let char = self.peek();
+        if char.is_some(){self.position = self.position + 1;}
+        char
+    }
+    fn skip_whitespace(&mut self){
+        while self.peek().is_some_and(char::is_whitespace){self.position = self.position + 1;}
+    }
+    fn parse(mut self) -> Result<Command, String>{
+        let command = self.command()?;
+        self.skip_whitespace();
+        match self.peek(){
+            None => Ok(command),
+            Some(char) => Err(format!("unexpected {:?} after command", char))
+        }
+    }
+    fn command(&mut self) -> Result<Command, String>{
+        self.skip_whitespace();
+        let address = self.address()?;
+        self.skip_whitespace();
+        let kind = match self.next(){
+            None => {
+                if address.is_none(){return Err(String::from("missing command"));}
+                CommandKind::Select
+            }
+            Some(name @ ('x' | 'y')) => {
+                let regex = compile(&self.delimited(name)?)?;
+                CommandKind::Loop{regex, between_matches: name == 'y', body: self.body()?}
+            }
+            Some(name @ ('g' | 'v')) => {
+                let regex = compile(&self.delimited(name)?)?;
+                CommandKind::Guard{regex, if_matching: name == 'g', body: self.body()?}
+            }
+            Some('s') => {
+                let delimiter = self.delimiter('s')?;
+                let regex = compile(&self.until(delimiter))?;
+                let template = self.until(delimiter);
+                let global = self.peek() == Some('g');
+                if global{self.position = self.position + 1;}
+                CommandKind::Substitute{regex, template, global}
+            }
+            Some(name @ ('a' | 'i' | 'c')) => {
+                let text = unescape(&self.delimited(name)?);
+                match name{
+                    'a' => CommandKind::Append(text),
+                    'i' => CommandKind::Insert(text),
+                    _ => CommandKind::Change(text)
+                }
+            }
+            Some('d') => CommandKind::Delete,
+            Some('|') => {
+                //the shell command is the rest of the command text
+                let command: String = self.chars[self.position..].iter().collect();
+                self.position = self.chars.len();
+                if command.trim().is_empty(){return Err(String::from("missing shell command after |"));}
+                CommandKind::Pipe(command.trim().to_string())
+            }
+            Some(other) => return Err(format!("unknown command {:?}", other))
+        };
+        Ok(Command{address, kind})
+    }
+    /// The command run by a loop or guard, if any follows it.
+    fn body(&mut self) -> Result<Option<Box<Command>>, String>{
+        self.skip_whitespace();
+        if self.peek().is_none(){return Ok(None);}
+        Ok(Some(Box::new(self.command()?)))
+    }
+    fn delimiter(&mut self, name: char) -> Result<char, String>{
+        match self.next(){
+            Some(delimiter) if !delimiter.is_alphanumeric() && !delimiter.is_whitespace() && delimiter != '\\' => Ok(delimiter),
+            _ => Err(format!("expected a delimiter after {}", name))
+        }
+    }
+    fn delimited(&mut self, name: char) -> Result<String, String>{
+        let delimiter = self.delimiter(name)?;
+        Ok(self.until(delimiter))
+    }
+    /// Text up to the next unescaped `delimiter`, or the end of the command. An escaped delimiter becomes the delimiter.
+    /// Other escapes are kept, to be interpreted by the regex, or the substitution template.
+    fn until(&mut self, delimiter: char) -> String{
+        let mut text = String::new();
+        while let Some(char) = self.next(){
+            if char == delimiter{break;}
+            if char == '\\'{
+                match self.next(){
+                    None => text.push('\\'),
+                    Some(escaped) if escaped == delimiter => text.push(escaped),
+                    Some(escaped) => {
+                        text.push('\\');
+                        text.push(escaped);
+                    }
+                }
+            }else{
+                text.push(char);
+            }
+        }
+        text
+    }
+    fn address(&mut self) -> Result<Option<Address>, String>{
+        let left = self.simple_address()?;
+        match self.peek(){
+            Some(separator @ (',' | ';')) => {
+                self.position = self.position + 1;
+                let right = self.address()?;
+                Ok(Some(Address::Span{
+                    left: Box::new(left.unwrap_or(Address::Line(0))),
+                    right: Box::new(right.unwrap_or(Address::End)),
+                    right_follows_left: separator == ';'
+                }))
+            }
+            _ => Ok(left)
+        }
+    }
+    fn simple_address(&mut self) -> Result<Option<Address>, String>{
+        let mut address = self.primary_address()?;
+        loop{
+            match self.peek(){
+                Some(sign @ ('+' | '-')) => {
+                    self.position = self.position + 1;
+                    let offset = self.primary_address()?.unwrap_or(Address::Line(1));
+                    address = Some(Address::Relative{base: Box::new(address.unwrap_or(Address::Dot)), forward: sign == '+', offset: Box::new(offset)});
+                }
+                //a search directly following an address is relative to it. `3/idk/` is `3+/idk/`
+                Some('/' | '?') if address.is_some() => {
+                    let offset = self.primary_address()?.unwrap_or(Address::Dot);
+                    address = Some(Address::Relative{base: Box::new(address.unwrap_or(Address::Dot)), forward: true, offset: Box::new(offset)});
+                }
+                _ => break
+            }
+        }
+        Ok(address)
+    }
+    fn primary_address(&mut self) -> Result<Option<Address>, String>{
+        match self.peek(){
+            Some('.') => {
+                self.position = self.position + 1;
+                Ok(Some(Address::Dot))
+            }
+            Some('$') => {
+                self.position = self.position + 1;
+                Ok(Some(Address::End))
+            }
+            Some(char) if char.is_ascii_digit() => Ok(Some(Address::Line(self.number()?))),
+            Some('#') => {
+                self.position = self.position + 1;
+                if !self.peek().is_some_and(|char| char.is_ascii_digit()){return Err(String::from("expected a number after #"));}
+                Ok(Some(Address::Char(self.number()?)))
+            }
+            Some(delimiter @ ('/' | '?')) => {
+                self.position = self.position + 1;
+                let regex = compile(&self.until(delimiter))?;
+                Ok(Some(Address::Search{regex, forward: delimiter == '/'}))
+            }
+            _ => Ok(None)
+        }
+    }
+    fn number(&mut self) -> Result<usize, String>{
+        let mut digits = String::new();
+        while let Some(digit) = self.peek().filter(char::is_ascii_digit){
+            digits.push(digit);
+            self.position = self.position + 1;
+        }
+        digits.parse::<usize>().map_err(|error| format!("{}", error))
+    }
+}
+/// Interprets `\n` as a newline in `a`, `i` and `c` text. `\\` is a literal `\`.
+fn unescape(text: &str) -> String{
+    let mut unescaped = String::new();
+    let mut chars = text.chars();
+    while let Some(char) = chars.next(){
+        if char != '\\'{
+            unescaped.push(char);
+            continue;
+        }
+        match chars.next(){
+            None => unescaped.push('\\'),
+            Some('n') => unescaped.push('\n'),
+            Some('\\') => unescaped.push('\\'),
+            Some(other) => {
+                unescaped.push('\\');
+                unescaped.push(other);
+            }
+        }
+    }
+    unescaped
+}
+
+/// What to select once a command completes.
+enum Dot{
+    /// A range of the unchanged text.
+    Range(Range),
+    /// The text an edit replaced its range with.
+    Edit(usize),
+}
+
+struct Evaluator<'a>{
+    text: &'a str,
+    /// See [`char_offsets`].
+    offsets: Vec<usize>,
+    pipe: &'a mut dyn FnMut(&str, &str) -> Result<String, String>,
+    edits: Vec<Edit>,
+    dots: Vec<Dot>,
+}
+impl<'a> Evaluator<'a>{
+    fn new(text: &'a str, pipe: &'a mut dyn FnMut(&str, &str) -> Result<String, String>) -> Self{
+        Self{text, offsets: char_offsets(text), pipe, edits: Vec::new(), dots: Vec::new()}
+    }
+    fn len_chars(&self) -> usize{
+        self.offsets.len() - 1
+    }
+    fn char_index(&self, byte_index: usize) -> usize{
+        self.offsets.binary_search(&byte_index).unwrap_or_else(|index| index)
+    }
+    fn is_newline(&self, char_index: usize) -> bool{
+        self.text.as_bytes()[self.offsets[char_index]] == b'\n'
+    }
+    fn slice(&self, range: &Range) -> &'a str{
+        &self.text[self.offsets[range.start]..self.offsets[range.end]]
+    }
+    fn address(&self, address: &Address, dot: &Range) -> Result<Range, String>{
+        match address{
+            Address::Dot => Ok(dot.clone()),
+            Address::End => Ok(Range::new(self.len_chars(), self.len_chars())),
+            Address::Line(line) => self.line(*line, &Range::new(0, 0), None),
+            Address::Char(char) => {
+                if *char > self.len_chars(){return Err(String::from(ADDRESS_OUT_OF_RANGE));}
+                Ok(Range::new(*char, *char))
+            }
+            Address::Search{regex, forward} => self.search(regex, dot, *forward),
+            Address::Relative{base, forward, offset} => {
+                let base = self.address(base, dot)?;
+                match offset.as_ref(){
+                    Address::Line(line) => self.line(*line, &base, Some(*forward)),
+                    Address::Char(char) => {
+                        let position = if *forward{Some(base.end + char).filter(|position| *position <= self.len_chars())}
+                        else{base.start.checked_sub(*char)};
+                        match position{
+                            None => Err(String::from(ADDRESS_OUT_OF_RANGE)),
+                            Some(position) => Ok(Range::new(position, position))
+                        }
+                    }
+                    //a backward relative search reverses the search's direction
+                    Address::Search{regex, forward: search_forward} => self.search(regex, &base, forward == search_forward),
+                    offset => self.address(offset, &base)
+                }
+            }
+            Address::Span{left, right, right_follows_left} => {
+                let left = self.address(left, dot)?;
+                let right = self.address(right, if *right_follows_left{&left}else{dot})?;
+                if right.end < left.start{return Err(String::from("addresses out of order"));}
+                Ok(Range::new(left.start, right.end))
+            }
+        }
+    }
+    /// Line `line`, counted forward from the end of `base`, backward from its start, or from the start of the text if `forward` is None.
+    /// Line 0 is the empty range where counting starts.
+    fn line(&self, line: usize, base: &Range, forward: Option<bool>) -> Result<Range, String>{
+        let len_chars = self.len_chars();
+        if forward != Some(false){
+            let start;
+            let mut position;
+            if line == 0{
+                if forward.is_none() || base.end == 0{return Ok(Range::new(0, 0));}
+                start = base.end;
+                position = base.end - 1;
+            }else{
+                let mut count;
+                if forward.is_none() || base.end == 0{
+                    position = 0;
+                    count = 1;
+                }else{
+                    //counting from the end of a line starts at the next line
+                    position = base.end - 1;
+                    count = if self.is_newline(position){1}else{0};
+                    position = position + 1;
+                }
+                while count < line{
+                    if position >= len_chars{return Err(String::from(ADDRESS_OUT_OF_RANGE));}
+                    if self.is_newline(position){count = count + 1;}
+                    position = position + 1;
+                }
+                start = position;
+            }
+            while position < len_chars{
+                let newline = self.is_newline(position);
+                position = position + 1;
+                if newline{break;}
+            }
+            Ok(Range::new(start, position))
+        }else{
+            let mut position = base.start;
+            let end;
+            if line == 0{
+                end = base.start;
+            }else{
+                let mut count = 0;
+                while count < line{
+                    if position == 0{
+                        count = count + 1;
+                        if count != line{return Err(String::from(ADDRESS_OUT_OF_RANGE));}
+                    }else if !self.is_newline(position - 1){
+                        position = position - 1;
+                    }else{
+                        count = count + 1;
+                        if count != line{position = position - 1;}
+                    }
+                }
+                end = position;
+                position = position.saturating_sub(1);
+            }
+            while position > 0 && !self.is_newline(position - 1){position = position - 1;}
+            Ok(Range::new(position, end))
+        }
+    }
+    /// Next match after `base`, or previous match before it, wrapping around the text's ends.
+    /// An empty match where the search starts is skipped, so that repeating a search moves forward.
+    fn search(&self, regex: &Regex, base: &Range, forward: bool) -> Result<Range, String>{
+        let found = if forward{
+            let start = self.offsets[base.end];
+            let not_empty_at_start = |found: &regex::Match| !(found.start() == start && found.end() == start);
+            regex.find_at(self.text, start).filter(not_empty_at_start)
+                .or_else(|| self.text[start..].chars().next().and_then(|char| regex.find_at(self.text, start + char.len_utf8())))
+                .or_else(|| regex.find(self.text))
+        }else{
+            let end = self.offsets[base.start];
+            regex.find_iter(&self.text[..end]).filter(|found| !(found.start() == end && found.end() == end)).last()
+                .or_else(|| regex.find_iter(self.text).last())
+        };
+        match found{
+            None => Err(format!("no match for {}", regex.as_str())),
+            Some(found) => Ok(Range::new(self.char_index(found.start()), self.char_index(found.end())))
+        }
+    }
+    /// Matches of `regex` inside `dot`. Only the first, unless `all`.
+    fn matches(&self, regex: &Regex, dot: &Range, all: bool) -> Vec<Captures<'a>>{
+        let haystack = &self.text[..self.offsets[dot.end]];
+        let mut matches = Vec::new();
+        let mut position = self.offsets[dot.start];
+        let mut previous_end = None;
+        while let Some(captures) = regex.captures_at(haystack, position){
+            let (start, end) = captures.get(0).map_or((position, position), |found| (found.start(), found.end()));
+            //an empty match directly after the previous match is not a separate match(`x/a*/` over "aab")
+            let skip = start == end && previous_end == Some(start);
+            if start == end{
+                match haystack[end..].chars().next(){
+                    None => position = haystack.len() + 1,
+                    Some(char) => position = end + char.len_utf8()
+                }
+            }else{
+                position = end;
+            }
+            if !skip{
+                previous_end = Some(end);
+                matches.push(captures);
+                if !all{break;}
+            }
+            if position > haystack.len(){break;}
+        }
+        matches
+    }
+    fn captures_range(&self, captures: &Captures) -> Range{
+        captures.get(0).map_or(Range::new(0, 0), |found| Range::new(self.char_index(found.start()), self.char_index(found.end())))
+    }
+    fn run(&mut self, command: &Command, dot: Range) -> Result<(), String>{
+        let dot = match &command.address{
+            None => dot,
+            Some(address) => self.address(address, &dot)?
+        };
+        self.run_kind(&command.kind, dot)
+    }
+    fn run_body(&mut self, body: &Option<Box<Command>>, dot: Range) -> Result<(), String>{
+        match body{
+            None => {
+                self.dots.push(Dot::Range(dot));
+                Ok(())
+            }
+            Some(command) => self.run(command, dot)
+        }
+    }
+    fn edit(&mut self, range: Range, text: String){
+        self.dots.push(Dot::Edit(self.edits.len()));
+        self.edits.push(Edit{range, text});
+    }
+    fn run_kind(&mut self, kind: &CommandKind, dot: Range) -> Result<(), String>{
+        match kind{
+            CommandKind::Select => self.dots.push(Dot::Range(dot)),
+            CommandKind::Loop{regex, between_matches, body} => {
+                let matches: Vec<Range> = self.matches(regex, &dot, true).iter().map(|captures| self.captures_range(captures)).collect();
+                let ranges = if *between_matches{
+                    let mut start = dot.start;
+                    let mut ranges = Vec::with_capacity(matches.len() + 1);
+                    for found in matches{
+                        ranges.push(Range::new(start, found.start));
+                        start = found.end;
+                    }
+                    ranges.push(Range::new(start, dot.end));
+                    ranges
+                }else{matches};
+                for range in ranges{
+                    self.run_body(body, range)?;
+                }
+            }
+            CommandKind::Guard{regex, if_matching, body} => {
+                if self.matches(regex, &dot, false).is_empty() != *if_matching{
+                    self.run_body(body, dot)?;
+                }
+            }
+            CommandKind::Substitute{regex, template, global} => {
+                let substitutions: Vec<(Range, String)> = self.matches(regex, &dot, *global).iter()
+                    .map(|captures| (self.captures_range(captures), expand(template, captures)))
+                    .collect();
+                if substitutions.is_empty(){return Ok(());}
+                for (range, text) in substitutions{
+                    self.edits.push(Edit{range, text});
+                }
+                self.dots.push(Dot::Range(dot));
+            }
+            CommandKind::Append(text) => self.edit(Range::new(dot.end, dot.end), text.clone()),
+            CommandKind::Insert(text) => self.edit(Range::new(dot.start, dot.start), text.clone()),
+            CommandKind::Change(text) => self.edit(dot, text.clone()),
+            CommandKind::Delete => self.edit(dot, String::new()),
+            CommandKind::Pipe(command) => {
+                let input = self.slice(&dot);
+                let output = (self.pipe)(command, input)?;
+                //commands usually end their output with a newline. it is kept only if dot's text ended with one
+                let output = if input.ends_with('\n'){output.as_str()}else{output.strip_suffix('\n').unwrap_or(&output)};
+                self.edit(dot, output.to_string());
+            }
+        }
+        Ok(())
+    }
+    /// Sorts the edits, and maps the ranges to select into the changed text.
+    fn finish(self) -> Result<Outcome, String>{
+        let mut order: Vec<usize> = (0..self.edits.len()).collect();
+        order.sort_by_key(|index| (self.edits[*index].range.start, self.edits[*index].range.end));
+        let edits: Vec<Edit> = order.iter().map(|index| self.edits[*index].clone()).collect();
+        let mut sorted_indices = vec![0; order.len()];
+        for (sorted_index, index) in order.iter().enumerate(){sorted_indices[*index] = sorted_index;}
+        for pair in edits.windows(2){
+            if pair[1].range.start < pair[0].range.end{return Err(String::from("changes overlap"));}
+        }
+        //change in length caused by the edits before each edit
+        let mut offsets = Vec::with_capacity(edits.len() + 1);
+        let mut offset: isize = 0;
+        for edit in &edits{
+            offsets.push(offset);
+            offset = offset + edit.text.chars().count() as isize - (edit.range.end - edit.range.start) as isize;
+        }
+        offsets.push(offset);
+        //maps a position in the unchanged text to the changed text. `after` places it after any text inserted at the position
+        let map = |position: usize, after: bool| -> usize{
+            let index = edits.partition_point(|edit| edit.range.end < position || (edit.range.end == position && (edit.range.start < position || after)));
+            match edits.get(index){
+                Some(edit) if edit.range.start < position && position < edit.range.end => {
+                    edit.range.start.saturating_add_signed(offsets[index]) + (position - edit.range.start).min(edit.text.chars().count())
+                }
+                _ => position.saturating_add_signed(offsets[index])
+            }
+        };
+        let mut selections: Vec<Range> = self.dots.iter()
+            .map(|dot|{
+                match dot{
+                    Dot::Range(range) => Range::new(map(range.start, false), map(range.end, true).max(map(range.start, false))),
+                    Dot::Edit(index) => {
+                        let edit = &self.edits[*index];
+                        let start = edit.range.start.saturating_add_signed(offsets[sorted_indices[*index]]);
+                        Range::new(start, start + edit.text.chars().count())
+                    }
+                }
+            })
+            .collect();
+        selections.sort_by_key(|range| (range.start, range.end));
+        selections.dedup();
+        Ok(Outcome{edits, selections})
+    }
+}
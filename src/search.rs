@@ -0,0 +1,83 @@
+//! Options applied to search patterns before they are compiled as regexes, so that text containing regex syntax can be
+//! searched for without escaping, and matches can ignore case, or be restricted to whole words.
+//! Flags are passed to `search`/`search_selection` as `--literal`, `--ignore-case` and `--word`, or toggled in [`Mode::Find`].
+//!
+//! [`Mode::Find`]: crate::mode::Mode::Find
+
+use crate::kakoune_style_commands::Word;
+
+
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SearchFlag{
+    /// Match the pattern as literal text, instead of as a regex.
+    Literal,
+    IgnoreCase,
+    /// Only match whole words.
+    Word,
+}
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)] pub struct SearchFlags{
+    pub literal: bool,
+    pub ignore_case: bool,
+    pub word: bool,
+}
+impl SearchFlags{
+    /// Parses leading `--literal`, `--ignore-case` and `--word` flags from `args`, returning the flags and the remaining
+    /// args. A `--` arg ends the flags, so that patterns starting with `--` can be searched for.
+    pub fn parse(args: &[Word]) -> Result<(Self, &[Word]), String>{
+        let mut flags = Self::default();
+        for (i, arg) in args.iter().enumerate(){
+            match arg.content.as_str(){
+                "--literal" => flags.literal = true,
+                "--ignore-case" => flags.ignore_case = true,
+                "--word" => flags.word = true,
+                "--" => return Ok((flags, &args[i + 1..])),
+                flag if flag.starts_with("--") => return Err(format!("{} is not a valid search flag. expected --literal, --ignore-case, --word or --", flag)),
+                _ => return Ok((flags, &args[i..]))
+            }
+        }
+        Ok((flags, &[]))
+    }
+    pub fn toggle(&mut self, flag: SearchFlag){
+        match flag{
+            SearchFlag::Literal => self.literal = !self.literal,
+            SearchFlag::IgnoreCase => self.ignore_case = !self.ignore_case,
+            SearchFlag::Word => self.word = !self.word,
+        }
+    }
+    /// Names of the set flags, for display.
+    pub fn names(&self) -> Vec<&'static str>{
+        let mut names = Vec::new();
+        if self.literal{names.push("literal");}
+        if self.ignore_case{names.push("ignore case");}
+        if self.word{names.push("word");}
+        names
+    }
+    /// Regex source matching `pattern` with these flags applied. With `smart_case`, a pattern without uppercase chars
+    /// ignores case. An empty pattern stays empty, so callers still treat it as having no matches.
+    pub fn regex(&self, pattern: &str, smart_case: bool) -> String{
+        if pattern.is_empty(){return String::new();}
+        let mut regex = if self.literal{regex::escape(pattern)}else{pattern.to_string()};
+        if self.word{regex = format!(r"\b(?:{})\b", regex);}
+        if self.ignore_case || (smart_case && !pattern.chars().any(char::is_uppercase)){regex = format!("(?i){}", regex);}
+        regex
+    }
+}
+
+#[derive(Default)] pub struct SearchOptions{
+    /// Whether patterns without uppercase chars ignore case. Set with the `smart_case` setting.
+    pub smart_case: bool,
+    /// Flags toggled in [`Mode::Find`]. Kept between uses of the mode, and displayed in its prompt.
+    ///
+    /// [`Mode::Find`]: crate::mode::Mode::Find
+    pub find_flags: SearchFlags,
+}
+impl SearchOptions{
+    /// Regex source for `pattern` typed in [`Mode::Find`].
+    ///
+    /// [`Mode::Find`]: crate::mode::Mode::Find
+    pub fn find_regex(&self, pattern: &str) -> String{
+        self.find_flags.regex(pattern, self.smart_case)
+    }
+}
@@ -0,0 +1,87 @@
+//! State of shell commands run from the command line(`|`, `<`, `>`, `!`, and unrecognized commands).
+//! When the editor's event loop is running, these commands run on a worker thread, which sends their results back as an
+//! [`Event::ShellCommandFinished`], so that long running commands don't freeze the editor. Only one may run at a time.
+//! A running command can be cancelled, and every shell command is killed once it exceeds the `shell_command_timeout` setting.
+
+use std::sync::{Arc, atomic::{AtomicBool, Ordering}, mpsc::Sender};
+use std::time::Duration;
+use crate::{
+    application::Event,
+    config::SHELL_COMMAND_TIMEOUT,
+    selections::Selections,
+};
+
+
+
+/// What to do with a shell command's output once it finishes.
+pub enum ShellCommandKind{
+    /// Replace each selection's text with the output of its own run. `|` and `<`.
+    /// Holds the selections, and their text, at the time the command started. Output is discarded if either has changed since.
+    Pipe{selections: Selections, selected_texts: Vec<String>},
+    /// Run the `Edit` command's sam command again, with the output of each run of its `|` command.
+    /// Holds the sam command, and the buffer text and selections at the time it started. Output is discarded if either has changed since.
+    Edit{command: String, text: String, selections: Selections},
+    /// Display the output. `>`.
+    Redirect,
+    /// Discard the output, and report the exit status. `!`.
+    Bang,
+    /// Unrecognized commands, run as is.
+    Run,
+}
+
+/// A shell command running on a worker thread.
+pub struct RunningShellCommand{
+    pub command: String,
+    pub kind: ShellCommandKind,
+    cancel: Arc<AtomicBool>,
+}
+impl RunningShellCommand{
+    pub fn is_cancelled(&self) -> bool{
+        self.cancel.load(Ordering::Relaxed)
+    }
+}
+
+pub struct ShellCommands{
+    /// Shell commands are killed once they run this long. None if commands may run indefinitely.
+    pub timeout: Option<Duration>,
+    /// Sends finished commands' results to the event loop. None if there is no event loop to send to(in tests, for instance),
+    /// in which case shell commands run to completion before returning.
+    event_tx: Option<Sender<Event>>,
+    running: Option<RunningShellCommand>,
+}
+impl Default for ShellCommands{
+    fn default() -> Self{
+        Self{timeout: Some(SHELL_COMMAND_TIMEOUT), event_tx: None, running: None}
+    }
+}
+impl ShellCommands{
+    pub fn set_event_sender(&mut self, event_tx: Sender<Event>){
+        self.event_tx = Some(event_tx);
+    }
+    pub fn event_sender(&self) -> Option<Sender<Event>>{
+        self.event_tx.clone()
+    }
+    pub fn running(&self) -> Option<&RunningShellCommand>{
+        self.running.as_ref()
+    }
+    /// Records `command` as running. Returns the flag that its worker checks for cancellation.
+    pub fn start(&mut self, command: &str, kind: ShellCommandKind) -> Arc<AtomicBool>{
+        let cancel = Arc::new(AtomicBool::new(false));
+        self.running = Some(RunningShellCommand{command: command.to_string(), kind, cancel: Arc::clone(&cancel)});
+        cancel
+    }
+    /// Takes the running command, once its worker has sent its result.
+    pub fn finish(&mut self) -> Option<RunningShellCommand>{
+        self.running.take()
+    }
+    /// Asks the running command's worker to kill it. Returns false if no command is running.
+    pub fn cancel(&self) -> bool{
+        match &self.running{
+            None => false,
+            Some(running) => {
+                running.cancel.store(true, Ordering::Relaxed);
+                true
+            }
+        }
+    }
+}
@@ -0,0 +1,126 @@
+use std::path::PathBuf;
+use crate::{
+    action::{Action, EditAction, EditorAction},
+    clipboard::osc52_sequence,
+    registers::YANK_REGISTER,
+};
+use crate::tests::common::{test_application, execute};
+
+/// Stands in for a clipboard tool like wl-copy/wl-paste. Stores copied text in a file next to the script.
+const STUB_CLIPBOARD_SCRIPT: &str = r#"
+case "$1" in
+    copy) cat > "$(dirname "$0")/clipboard";;
+    paste) cat "$(dirname "$0")/clipboard";;
+    *) exit 1;;
+esac
+"#;
+
+/// Returns a directory, unique to the calling test, containing the stub clipboard script.
+fn stub_clipboard_dir(test_name: &str) -> PathBuf{
+    let dir = std::env::temp_dir().join(format!("edit_clipboard_{}_{}", test_name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("clip"), STUB_CLIPBOARD_SCRIPT).unwrap();
+    dir
+}
+
+#[test] fn osc52_sequence_is_base64_encoded(){
+    assert_eq!("\x1b]52;c;aWRr\x07", osc52_sequence("idk"));
+    assert_eq!("\x1b]52;c;aWRrCg==\x07", osc52_sequence("idk\n"));
+    assert_eq!("\x1b]52;c;c29tZQ==\x07", osc52_sequence("some"));
+    assert_eq!("\x1b]52;c;\x07", osc52_sequence(""));
+}
+#[test] fn copy_sends_text_to_clipboard_set_command(){
+    let dir = stub_clipboard_dir("set");
+    let mut app = test_application("idk\nsome\nshit\n");
+    execute(&mut app, &format!("set clipboard_set_command sh {} copy", dir.join("clip").display()));
+    assert_eq!(Some(format!("sh {} copy", dir.join("clip").display())), app.clipboard.set_command);
+    app.update(Action::EditorAction(EditorAction::Copy));
+    assert_eq!("i", std::fs::read_to_string(dir.join("clipboard")).unwrap());
+    assert_eq!(None, app.clipboard.take_osc52());
+}
+#[test] fn copy_without_clipboard_set_command_falls_back_to_osc52(){
+    let mut app = test_application("idk\nsome\nshit\n");
+    app.update(Action::EditorAction(EditorAction::Copy));
+    assert_eq!(Some(osc52_sequence("i")), app.clipboard.take_osc52());
+}
+#[test] fn copy_with_failing_clipboard_set_command_falls_back_to_osc52(){
+    let dir = stub_clipboard_dir("failing_set");
+    let mut app = test_application("idk\nsome\nshit\n");
+    app.clipboard.set_command = Some(format!("sh {} idk", dir.join("clip").display()));
+    app.update(Action::EditorAction(EditorAction::Copy));
+    assert_eq!(Some(osc52_sequence("i")), app.clipboard.take_osc52());
+    assert_eq!(Some("i"), app.registers.get(YANK_REGISTER));
+}
+#[test] fn paste_uses_clipboard_get_command_output(){
+    let dir = stub_clipboard_dir("get");
+    std::fs::write(dir.join("clipboard"), "other").unwrap();
+    let mut app = test_application("idk\nsome\nshit\n");
+    app.clipboard.get_command = Some(format!("sh {} paste", dir.join("clip").display()));
+    app.registers.set(YANK_REGISTER, "yanked").unwrap();
+    app.update(Action::EditAction(EditAction::Paste));
+    assert_eq!("otheridk\nsome\nshit\n", app.buffer.to_string());
+}
+#[test] fn copy_doesnt_wait_on_process_forked_by_clipboard_set_command(){
+    let dir = stub_clipboard_dir("forking_set");
+    let mut app = test_application("idk\nsome\nshit\n");
+    // like xclip/wl-copy, leaves a process running after the command exits
+    app.clipboard.set_command = Some(format!("sh {} copy; sleep 10 &", dir.join("clip").display()));
+    let start = std::time::Instant::now();
+    app.update(Action::EditorAction(EditorAction::Copy));
+    assert!(start.elapsed() < std::time::Duration::from_secs(5));
+    assert_eq!("i", std::fs::read_to_string(dir.join("clipboard")).unwrap());
+    assert_eq!(None, app.clipboard.take_osc52());
+}
+#[test] fn paste_gives_up_on_clipboard_get_command_holding_its_output_open(){
+    let mut app = test_application("idk\nsome\nshit\n");
+    app.clipboard.get_command = Some(String::from("echo other; sleep 10 &"));
+    app.registers.set(YANK_REGISTER, "yanked").unwrap();
+    let start = std::time::Instant::now();
+    app.update(Action::EditAction(EditAction::Paste));
+    assert!(start.elapsed() < std::time::Duration::from_secs(5));
+    assert_eq!("yankedidk\nsome\nshit\n", app.buffer.to_string());
+}
+#[test] fn paste_with_register_prefix_ignores_clipboard_get_command(){
+    let dir = stub_clipboard_dir("get_with_register");
+    std::fs::write(dir.join("clipboard"), "other").unwrap();
+    let mut app = test_application("idk\nsome\nshit\n");
+    app.clipboard.get_command = Some(format!("sh {} paste", dir.join("clip").display()));
+    app.registers.set("a", "lettered").unwrap();
+    app.selected_register = Some(String::from("a"));
+    app.update(Action::EditAction(EditAction::Paste));
+    assert_eq!("letteredidk\nsome\nshit\n", app.buffer.to_string());
+}
+#[test] fn copy_then_paste_through_clipboard_commands_round_trips(){
+    let dir = stub_clipboard_dir("round_trip");
+    let mut app = test_application("idk\nsome\nshit\n");
+    app.clipboard.set_command = Some(format!("sh {} copy", dir.join("clip").display()));
+    app.clipboard.get_command = Some(format!("sh {} paste", dir.join("clip").display()));
+    app.update(Action::EditorAction(EditorAction::Copy));
+    app.update(Action::EditAction(EditAction::Paste));
+    assert_eq!("iidk\nsome\nshit\n", app.buffer.to_string());
+}
+#[test] fn paste_keeps_value_per_selection_when_clipboard_differs_only_by_trailing_newline(){
+    let dir = stub_clipboard_dir("trailing_newline");
+    let mut app = test_application("idk\nsome\nshit\n");
+    app.clipboard.set_command = Some(format!("sh {} copy", dir.join("clip").display()));
+    app.clipboard.get_command = Some(format!("sh {} paste; echo", dir.join("clip").display()));
+    execute(&mut app, "search '(?m)^.'");
+    app.update(Action::EditorAction(EditorAction::Copy));
+    app.update(Action::EditAction(EditAction::Paste));
+    assert_eq!("iidk\nssome\nsshit\n", app.buffer.to_string());
+}
+#[test] fn paste_prefers_yank_register_while_clipboard_holds_what_was_last_copied(){
+    let dir = stub_clipboard_dir("last_written");
+    let mut app = test_application("idk\nsome\nshit\n");
+    app.clipboard.set_command = Some(format!("sh {} copy", dir.join("clip").display()));
+    app.clipboard.get_command = Some(format!("sh {} paste", dir.join("clip").display()));
+    app.update(Action::EditorAction(EditorAction::Copy));
+    app.registers.set(YANK_REGISTER, "yanked").unwrap();
+    app.update(Action::EditAction(EditAction::Paste));
+    assert_eq!("yankedidk\nsome\nshit\n", app.buffer.to_string());
+    // copied outside the editor
+    std::fs::write(dir.join("clipboard"), "other").unwrap();
+    app.update(Action::EditAction(EditAction::Paste));
+    assert_eq!("yankedotheridk\nsome\nshit\n", app.buffer.to_string());
+}
@@ -0,0 +1,257 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use crate::{
+    action::{Action, EditAction},
+    application::{Event, option_value},
+    instance::Instance,
+    mode::Mode,
+    registers::{SEARCH_REGISTER, COMMAND_REGISTER},
+};
+use crate::tests::common::{test_application, execute, wait_for_shell_command};
+
+
+#[test] fn semicolon_separated_commands_all_execute(){
+    let mut app = test_application("idk\nsome\nshit\n");
+    execute(&mut app, "set tab_width 2; set use_hard_tab true");
+    assert_eq!(Some("set tab_width 2; set use_hard_tab true"), app.registers.get(COMMAND_REGISTER));
+    assert_eq!(Ok(String::from("2")), option_value(&app, "tab_width"));
+    assert_eq!(Ok(String::from("true")), option_value(&app, "use_hard_tab"));
+}
+#[test] fn execution_stops_at_first_failing_command(){
+    let mut app = test_application("idk\nsome\nshit\n");
+    execute(&mut app, "set tab_width idk; set use_hard_tab true");
+    assert_eq!(Mode::Error, app.mode());
+    assert_eq!(Ok(String::from("false")), option_value(&app, "use_hard_tab"));
+}
+#[test] fn quoted_argument_keeps_whitespace(){
+    let mut app = test_application("idk some shit\n");
+    execute(&mut app, "search 'some shit'");
+    assert_eq!(Some("some shit"), app.registers.get(SEARCH_REGISTER));
+    assert_eq!("some shit", app.selections.primary.to_string(&app.buffer));
+}
+#[test] fn option_expansion_evaluated_at_execution(){
+    let mut app = test_application("idk\n");
+    // the expansion in the second command sees the value set by the first
+    execute(&mut app, "set tab_width 3; set view_scroll_amount %opt{tab_width}");
+    assert_eq!(Ok(String::from("3")), option_value(&app, "view_scroll_amount"));
+}
+#[test] fn register_expansion(){
+    let mut app = test_application("idk some shit\n");
+    app.registers.set("a", "shit").unwrap();
+    execute(&mut app, "search %reg{a}");
+    assert_eq!("shit", app.selections.primary.to_string(&app.buffer));
+}
+#[test] fn shell_expansion_strips_trailing_newline(){
+    let mut app = test_application("idk\n");
+    execute(&mut app, "set tab_width %sh{echo 8}");
+    assert_eq!(Ok(String::from("8")), option_value(&app, "tab_width"));
+}
+#[test] fn unknown_expansion_errors(){
+    let mut app = test_application("idk\n");
+    execute(&mut app, "set tab_width %idk{8}");
+    assert_eq!(Mode::Error, app.mode());
+    assert_eq!(Ok(String::from("4")), option_value(&app, "tab_width"));
+}
+
+#[test] fn every_registered_action_has_a_unique_name(){
+    let names: Vec<String> = Action::registry().iter().map(|action| action.command_name()).collect();
+    for (i, name) in names.iter().enumerate(){
+        assert!(!names[i + 1..].contains(name), "{} is not unique", name);
+        assert_eq!(Some(name.clone()), Action::from_command_name(&name.replace(' ', "_")).map(|action| action.command_name()));
+    }
+}
+#[test] fn action_executes_by_name_with_count(){
+    let mut app = test_application("idk\nsome\nshit\n");
+    execute(&mut app, "move_cursor_down 2");
+    assert_eq!(Mode::Insert, app.mode());
+    assert_eq!(9, app.selections.primary.range.start);
+    execute(&mut app, "select_line");
+    assert_eq!("shit\n", app.selections.primary.to_string(&app.buffer));
+}
+#[test] fn non_selection_action_repeats_count_times(){
+    let mut app = test_application("idk\n");
+    execute(&mut app, "insert_newline 3");
+    assert_eq!("\n\n\nidk\n", app.buffer.to_string());
+    execute(&mut app, "undo 2");
+    assert_eq!("\nidk\n", app.buffer.to_string());
+}
+#[test] fn action_with_invalid_count_errors(){
+    let mut app = test_application("idk\nsome\nshit\n");
+    execute(&mut app, "move_cursor_down idk");
+    assert_eq!(Mode::Error, app.mode());
+    assert_eq!(0, app.selections.primary.range.start);
+}
+#[test] fn shell_commands_receive_editor_context(){
+    let mut app = test_application("idk\nsome\nshit\n");
+    execute(&mut app, "move_cursor_down");
+    execute(&mut app, "add_option context string %sh{echo $EDIT_SELECTION_COUNT $EDIT_PRIMARY_INDEX $EDIT_CURSOR_LINE $EDIT_CURSOR_COLUMN $EDIT_MODE $EDIT_SELECTIONS}");
+    assert_eq!(Ok(String::from("1 0 2 1 Command 2:1,2:2")), option_value(&app, "context"));
+}
+#[test] fn shell_commands_run_in_file_directory(){
+    let dir = std::env::temp_dir().join(format!("edit_shell_directory_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let mut app = test_application("idk\n");
+    app.buffer.file_path = Some(dir.join("file.rs"));
+    execute(&mut app, "add_option context string %sh{echo $(pwd) $EDIT_BUFFER_NAME $EDIT_FILE_PATH}");
+    assert_eq!(Ok(format!("{} file.rs {}", dir.display(), dir.join("file.rs").display())), option_value(&app, "context"));
+}
+#[test] fn shell_commands_receive_instance_socket(){
+    let runtime_dir = std::env::temp_dir().join(format!("edit_shell_instance_{}", std::process::id()));
+    std::fs::create_dir_all(&runtime_dir).unwrap();
+    let (instance, _listener) = Instance::register(&runtime_dir, 1, None).unwrap();
+    let mut app = test_application("idk\n");
+    app.instance = Some(instance.clone());
+    execute(&mut app, "add_option context string %sh{echo $EDIT_INSTANCE_ID $EDIT_SOCKET}");
+    assert_eq!(Ok(format!("1 {}", instance.socket_path.display())), option_value(&app, "context"));
+    instance.cleanup();
+}
+#[test] fn redirect_sends_every_selection_and_shows_output(){
+    let mut app = test_application("idk some idk\n");
+    execute(&mut app, "search idk");
+    assert_eq!(2, app.selections.count());
+    execute(&mut app, "> cat");
+    assert_eq!(Mode::Info, app.mode());
+    assert_eq!(Some(String::from("idk\nidk")), app.mode_message());
+    assert_eq!("idk some idk\n", app.buffer.to_string());
+}
+#[test] fn bang_discards_output_and_reports_exit_status(){
+    let mut app = test_application("idk\n");
+    execute(&mut app, "! echo some shit");
+    assert_eq!(Mode::Notify, app.mode());
+    assert_eq!("idk\n", app.buffer.to_string());
+    execute(&mut app, "! exit 3");
+    assert_eq!(Mode::Error, app.mode());
+    assert_eq!(Some(String::from("shell command failed with exit status: 3")), app.mode_message());
+}
+#[test] fn pipe_replaces_each_selection_as_one_change(){
+    let mut app = test_application("idk some idk\n");
+    execute(&mut app, "search idk");
+    let undo_count = app.undo_stack.len();
+    execute(&mut app, "| tr a-z A-Z");
+    assert_eq!("IDK some IDK\n", app.buffer.to_string());
+    assert_eq!(undo_count + 1, app.undo_stack.len());
+    app.update(Action::EditAction(EditAction::Undo));
+    assert_eq!("idk some idk\n", app.buffer.to_string());
+}
+#[test] fn pipe_runs_once_per_selection(){
+    let mut app = test_application("b\na\nc\nb\n");
+    execute(&mut app, "select_all");
+    execute(&mut app, "| sort");
+    assert_eq!("a\nb\nb\nc\n", app.buffer.to_string());
+    execute(&mut app, "search '[ab]\\n'");
+    execute(&mut app, "| wc -l");
+    assert_eq!("1\n1\n1\nc\n", app.buffer.to_string());
+}
+#[test] fn insert_replaces_each_selection_with_output(){
+    let mut app = test_application("idk some idk\n");
+    execute(&mut app, "search idk");
+    execute(&mut app, "< echo shit");
+    assert_eq!("shit some shit\n", app.buffer.to_string());
+}
+#[test] fn shell_commands_get_raw_text_of_their_line(){
+    let mut app = test_application("idk some idk\n");
+    execute(&mut app, "search idk");
+    // ';' and '#' are the shell's, not command separators or comments
+    execute(&mut app, "< cd /; pwd");
+    assert_eq!("/ some /\n", app.buffer.to_string());
+    execute(&mut app, "search /");
+    execute(&mut app, "| tr / '#' # comment");
+    assert_eq!("# some #\n", app.buffer.to_string());
+    execute(&mut app, "search '#'");
+    execute(&mut app, "< echo a#b");
+    assert_eq!("a#b some a#b\n", app.buffer.to_string());
+}
+#[test] fn piped_command_keeps_quoted_percent(){
+    let mut app = test_application("idk some idk\n");
+    execute(&mut app, "search idk");
+    execute(&mut app, "| sed 's/k/%{k}/' | tr -d '{}'");
+    assert_eq!("id%k some id%k\n", app.buffer.to_string());
+    // a quote mid word doesn't start a quoted word
+    execute(&mut app, "search 'id%k'");
+    execute(&mut app, "< printf x'%s %s' y z");
+    assert_eq!("xy z some xy z\n", app.buffer.to_string());
+}
+#[test] fn commands_after_shell_command_line_execute(){
+    let mut app = test_application("idk\n");
+    execute(&mut app, "! true; set tab_width 8\nset use_hard_tab true");
+    assert_eq!(Ok(String::from("4")), option_value(&app, "tab_width"));
+    assert_eq!(Ok(String::from("true")), option_value(&app, "use_hard_tab"));
+}
+#[test] fn failing_pipe_leaves_buffer_unchanged(){
+    let mut app = test_application("idk some idk\n");
+    execute(&mut app, "search idk");
+    execute(&mut app, "| grep some");
+    assert_eq!(Mode::Error, app.mode());
+    assert_eq!("idk some idk\n", app.buffer.to_string());
+}
+#[test] fn shell_command_killed_after_timeout(){
+    let mut app = test_application("idk\n");
+    execute(&mut app, "set shell_command_timeout 1");
+    let start = std::time::Instant::now();
+    execute(&mut app, "! sleep 10");
+    assert!(start.elapsed() < std::time::Duration::from_secs(5));
+    assert_eq!(Mode::Error, app.mode());
+    assert_eq!(Some(String::from("shell command timed out after 1s")), app.mode_message());
+}
+#[test] fn background_process_holding_output_pipes_killed_after_timeout(){
+    let mut app = test_application("idk\n");
+    execute(&mut app, "set shell_command_timeout 1");
+    let start = std::time::Instant::now();
+    // the shell exits immediately, but sleep keeps its stdout and stderr open
+    execute(&mut app, "! sleep 10 &");
+    assert!(start.elapsed() < std::time::Duration::from_secs(5));
+    assert_eq!(Mode::Error, app.mode());
+    assert_eq!(Some(String::from("shell command timed out after 1s")), app.mode_message());
+}
+#[test] fn esc_cancels_background_process_holding_output_pipes(){
+    let (event_tx, event_rx) = std::sync::mpsc::channel();
+    let mut app = test_application("idk\n");
+    app.shell.set_event_sender(event_tx.clone());
+    let start = std::time::Instant::now();
+    execute(&mut app, "! sleep 10 &");
+    event_tx.send(Event::KeyboardInput(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE))).unwrap();
+    wait_for_shell_command(&mut app, &event_rx);
+    assert!(start.elapsed() < std::time::Duration::from_secs(5));
+    assert_eq!(Some(String::from("shell command cancelled")), app.mode_message());
+    assert!(app.shell.running().is_none());
+}
+#[test] fn shell_command_runs_on_worker_thread(){
+    let (event_tx, event_rx) = std::sync::mpsc::channel();
+    let mut app = test_application("idk some idk\n");
+    app.shell.set_event_sender(event_tx);
+    execute(&mut app, "search idk");
+    execute(&mut app, "| tr a-z A-Z");
+    // output is applied once the worker's event is handled
+    assert_eq!("idk some idk\n", app.buffer.to_string());
+    assert_eq!("running: tr a-z A-Z", app.ui.status_bar.shell_command_widget.text);
+    wait_for_shell_command(&mut app, &event_rx);
+    assert_eq!("IDK some IDK\n", app.buffer.to_string());
+    assert_eq!("", app.ui.status_bar.shell_command_widget.text);
+}
+#[test] fn esc_cancels_running_shell_command(){
+    let (event_tx, event_rx) = std::sync::mpsc::channel();
+    let mut app = test_application("idk\n");
+    app.shell.set_event_sender(event_tx.clone());
+    let start = std::time::Instant::now();
+    execute(&mut app, "! sleep 10");
+    // only one shell command runs at a time
+    execute(&mut app, "! echo idk");
+    assert_eq!(Mode::Error, app.mode());
+    event_tx.send(Event::KeyboardInput(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE))).unwrap();
+    wait_for_shell_command(&mut app, &event_rx);
+    assert!(start.elapsed() < std::time::Duration::from_secs(5));
+    assert_eq!(Mode::Notify, app.mode());
+    assert_eq!(Some(String::from("shell command cancelled")), app.mode_message());
+}
+#[test] fn pipe_output_discarded_if_selections_changed_while_running(){
+    let (event_tx, event_rx) = std::sync::mpsc::channel();
+    let mut app = test_application("idk some idk\n");
+    app.shell.set_event_sender(event_tx);
+    execute(&mut app, "search idk");
+    execute(&mut app, "| tr a-z A-Z");
+    execute(&mut app, "clear_non_primary_selections");
+    assert_eq!(1, app.selections.count());
+    wait_for_shell_command(&mut app, &event_rx);
+    assert_eq!(Mode::Error, app.mode());
+    assert_eq!(Some(String::from("selections changed while shell command ran. its output was discarded")), app.mode_message());
+    assert_eq!("idk some idk\n", app.buffer.to_string());
+}
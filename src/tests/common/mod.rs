@@ -1,8 +1,10 @@
 use crate::{
-    application::Application, 
+    action::{Action, EditorAction, UtilAction},
+    application::{Application, Event},
     buffer::Buffer, 
     config::Config, 
     display_area::DisplayArea,
+    mode::Mode,
     selection::{CursorSemantics, Selection}, 
     selections::Selections
 };
@@ -64,3 +66,35 @@ pub fn generate_selections(selections: Vec<Selection>, primary: usize, buffer: &
     }
     Selections::new(new_selections, primary, buffer, semantics)
 }
+
+/// Editable test application with the default config, in an 80x50 terminal.
+pub fn test_application(buffer_text: &str) -> Application{
+    test_application_with(Config::default(), buffer_text, false)
+}
+/// Test application with `config`, in an 80x50 terminal.
+pub fn test_application_with(config: Config, buffer_text: &str, read_only: bool) -> Application{
+    match set_up_test_application(config, DisplayArea::new(0, 0, 80, 50), buffer_text, read_only){
+        Ok(app) => app,
+        Err(e) => panic!("{}", e)
+    }
+}
+/// Enters `command` in the util bar, then accepts it. Any mode the command results in (a notification, or an error) is left
+/// for the caller to check.
+pub fn execute(app: &mut Application, command: &str){
+    app.update(Action::EditorAction(EditorAction::ModePush(Mode::Command, None)));
+    for c in command.chars(){app.update(Action::UtilAction(UtilAction::InsertChar(c)));}
+    app.update(Action::UtilAction(UtilAction::Accept));
+}
+/// Enters `mode`, and types `input` in the util bar, without accepting it.
+pub fn type_in_mode(app: &mut Application, mode: Mode, input: &str){
+    app.update(Action::EditorAction(EditorAction::ModePush(mode, None)));
+    for c in input.chars(){app.update(Action::UtilAction(UtilAction::InsertChar(c)));}
+}
+/// Text of each selection, in order.
+pub fn selected_texts(app: &Application) -> Vec<String>{
+    app.selections.iter().map(|selection| selection.to_string(&app.buffer)).collect()
+}
+/// Handles events from `event_rx` until the running shell command's result has been handled.
+pub fn wait_for_shell_command(app: &mut Application, event_rx: &std::sync::mpsc::Receiver<Event>){
+    while app.shell.running().is_some(){app.handle_event(event_rx).unwrap();}
+}
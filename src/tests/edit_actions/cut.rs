@@ -4,7 +4,7 @@ use crate::{
     range::Range,
     selection::{Selection, CursorSemantics::Block, Direction},
     display_area::DisplayArea,
-    config::{DisplayMode, READ_ONLY_BUFFER_DISPLAY_MODE, /*READ_ONLY_BUFFER, */MULTIPLE_SELECTIONS_DISPLAY_MODE, /*MULTIPLE_SELECTIONS, */Config},
+    config::{DisplayMode, READ_ONLY_BUFFER_DISPLAY_MODE, /*READ_ONLY_BUFFER, */Config},
     keybind::default_keybinds
 };
 use crate::tests::edit_actions::test_edit_action;
@@ -81,7 +81,7 @@ use crate::tests::edit_actions::test_edit_action;
     );
 }
 
-#[test] fn cut_with_multiple_selections_returns_error(){
+#[test] fn cut_with_multiple_selections_cuts_each_selection(){
     test_edit_action(
         Config{
             semantics: Block, 
@@ -108,22 +108,16 @@ use crate::tests::edit_actions::test_edit_action;
         ], 
         0, 
         "",
-        "idk\nsome\nshit\n", 
-        match MULTIPLE_SELECTIONS_DISPLAY_MODE{
-            DisplayMode::Error => {Mode::Error/*(MULTIPLE_SELECTIONS.to_string())*/}
-            DisplayMode::Warning => {Mode::Warning/*(MULTIPLE_SELECTIONS.to_string())*/}
-            DisplayMode::Notify => {Mode::Notify/*(MULTIPLE_SELECTIONS.to_string())*/}
-            DisplayMode::Info => {Mode::Info/*(MULTIPLE_SELECTIONS.to_string())*/}
-            DisplayMode::Ignore => {Mode::Insert}
-        }, 
+        "\ne\nshit\n", 
+        Mode::Insert, 
         vec![
-            //(0, 3, None),
-            Selection::new_unchecked(Range::new(0, 3), Some(Direction::Forward), None),
-            //(4, 7, None)
-            Selection::new_unchecked(Range::new(4, 7), Some(Direction::Forward), None),
+            //(0, 1, Some(0)),
+            Selection::new_unchecked(Range::new(0, 1), None, Some(0)),
+            //(1, 2, Some(0))
+            Selection::new_unchecked(Range::new(1, 2), None, Some(0)),
         ], 
         0,
-        ""
+        "idk\nsom"
     );
 }
 
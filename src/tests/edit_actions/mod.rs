@@ -5,6 +5,7 @@ use crate::{
     buffer::Buffer,
     selection::Selection,
     display_area::DisplayArea,
+    registers::YANK_REGISTER,
 };
 use crate::tests::common::{
     set_up_test_application,
@@ -54,7 +55,7 @@ pub fn test_edit_action(
             let expected_buffer = Buffer::new(expected_buffer_text, None, read_only);
             let expected_selections = generate_selections(expected_selections, expected_primary, &expected_buffer, config.semantics.clone());
 
-            app.clipboard = clipboard.to_string();
+            app.registers.set(YANK_REGISTER, clipboard).unwrap();
             app.selections = selections;
             //call action specific test(selection/view/edit/etc)
             //app.edit_action(&edit_action);
@@ -64,7 +65,7 @@ pub fn test_edit_action(
             assert_eq!(expected_selections, app.selections);
             assert_eq!(expected_primary, app.selections.primary_selection_index());
             assert_eq!(expected_buffer, app.buffer);
-            assert_eq!(expected_clipboard, app.clipboard);
+            assert_eq!(Some(expected_clipboard), app.registers.get(YANK_REGISTER));
 
             //assert!(app.buffer.is_modified());    //doesn't work in tests because we can't compare to persistent file
         }
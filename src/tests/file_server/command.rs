@@ -0,0 +1,30 @@
+use crate::{
+    config::Config,
+    file_server::ServedFile,
+    mode::Mode,
+};
+use crate::tests::common::test_application_with;
+use crate::tests::file_server::{read, write};
+
+#[test] fn read_before_any_command_is_empty(){
+    let mut app = test_application_with(Config::default(), "idk\n", false);
+    assert_eq!(Ok(Vec::new()), read(&mut app, ServedFile::Command, 0, 8192));
+}
+#[test] fn write_executes_command(){
+    let mut app = test_application_with(Config::default(), "idk\nsome\nidk\n", false);
+    assert_eq!(Ok(()), write(&mut app, ServedFile::Command, vec!["search idk\n"]));
+    assert_eq!(2, app.selections.count());
+    assert_eq!(Ok(b"Ok\n".to_vec()), read(&mut app, ServedFile::Command, 0, 8192));
+}
+#[test] fn each_write_is_a_separate_command(){
+    let mut app = test_application_with(Config::default(), "idk\n", false);
+    let show_line_numbers = app.ui.document_viewport.line_number_widget.show;
+    assert_eq!(Ok(()), write(&mut app, ServedFile::Command, vec!["ln\n", "ln\n", "ln\n"]));
+    assert_eq!(!show_line_numbers, app.ui.document_viewport.line_number_widget.show);
+}
+#[test] fn failed_command_errors_and_error_readable_back(){
+    let mut app = test_application_with(Config::default(), "idk\n", false);
+    assert_eq!(Err(String::from("too few arguments: search <regex>")), write(&mut app, ServedFile::Command, vec!["search\n"]));
+    assert_eq!(Ok(b"too few arguments: search <regex>\n".to_vec()), read(&mut app, ServedFile::Command, 0, 8192));
+    assert_eq!(Mode::Error, app.mode());
+}
@@ -0,0 +1,55 @@
+use crate::{
+    config::Config,
+    file_server::ServedFile,
+    action::{Action, EditAction, EditorAction},
+    mode::Mode,
+};
+use crate::tests::common::test_application_with;
+use crate::tests::file_server::{read, write};
+
+fn read_events(app: &mut crate::application::Application) -> String{
+    String::from_utf8(read(app, ServedFile::Events, 0, 8192).unwrap()).unwrap()
+}
+
+#[test] fn events_empty_at_startup(){
+    let mut app = test_application_with(Config::default(), "idk\nsome\nshit\n", false);
+    assert_eq!("", read_events(&mut app));
+}
+#[test] fn insert_emits_insert_text(){
+    let mut app = test_application_with(Config::default(), "idk\nsome\nshit\n", false);
+    app.update(Action::EditAction(EditAction::InsertChar('x')));
+    assert_eq!("InsertText 0 \"x\"\n", read_events(&mut app));
+}
+#[test] fn undo_emits_inverse_text_events(){
+    let mut app = test_application_with(Config::default(), "idk\nsome\nshit\n", false);
+    app.update(Action::EditAction(EditAction::InsertChar('x')));
+    app.update(Action::EditAction(EditAction::Undo));
+    assert_eq!("InsertText 0 \"x\"\nRemoveText 0 \"x\"\n", read_events(&mut app));
+}
+#[test] fn raw_text_write_emits_remove_and_insert(){
+    let mut app = test_application_with(Config::default(), "idk\n", false);
+    assert_eq!(Ok(()), write(&mut app, ServedFile::BufferRawText, vec!["some\n"]));
+    assert_eq!("RemoveText 0 \"idk\\n\"\nInsertText 0 \"some\\n\"\n", read_events(&mut app));
+}
+#[test] fn mode_push_and_pop_emit_mode_change(){
+    let mut app = test_application_with(Config::default(), "idk\n", false);
+    app.update(Action::EditorAction(EditorAction::ModePush(Mode::Find, None)));
+    app.update(Action::EditorAction(EditorAction::ModePop));
+    assert_eq!("ModeChange Insert Find\nModeChange Find Insert\n", read_events(&mut app));
+}
+#[test] fn error_mode_emits_error(){
+    let mut app = test_application_with(Config::default(), "idk\n", false);
+    app.update(Action::EditorAction(EditorAction::ModePush(Mode::Error, Some(String::from("some error")))));
+    assert_eq!("ModeChange Insert Error\nError \"some error\"\n", read_events(&mut app));
+}
+#[test] fn read_at_offset_returns_only_newer_events(){
+    let mut app = test_application_with(Config::default(), "idk\n", false);
+    app.update(Action::EditAction(EditAction::InsertChar('x')));
+    let offset = read_events(&mut app).len() as u64;
+    app.update(Action::EditAction(EditAction::InsertChar('y')));
+    assert_eq!(Ok(b"InsertText 1 \"y\"\n".to_vec()), read(&mut app, ServedFile::Events, offset, 8192));
+}
+#[test] fn write_errors(){
+    let mut app = test_application_with(Config::default(), "idk\n", false);
+    assert!(write(&mut app, ServedFile::Events, vec!["idk"]).is_err());
+}
@@ -0,0 +1,94 @@
+use std::sync::mpsc;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serve9p::file_system::FsRequest;
+use crate::{
+    application::Event,
+    config::Config,
+    file_server::ServedFile,
+    event_log::{EditorEvent, Acknowledgement},
+};
+use crate::tests::common::test_application_with;
+use crate::tests::file_server::{read, write};
+
+/// Queues the requests a handler would make to acknowledge an intercepted event.
+fn send_acknowledgement(event_tx: &mpsc::Sender<Event>, acknowledgement: &str){
+    let qid_path = ServedFile::InterceptPending.qid_path();
+    let (reply, _) = mpsc::channel();
+    event_tx.send(Event::NineP(0, FsRequest::Open{user: String::from("test"), qid_path, mode: 1, reply})).unwrap();
+    let (reply, _) = mpsc::channel();
+    event_tx.send(Event::NineP(0, FsRequest::Write{qid_path, offset: 0, data: acknowledgement.as_bytes().to_vec(), reply})).unwrap();
+    let (reply, _) = mpsc::channel();
+    event_tx.send(Event::NineP(0, FsRequest::Clunk{qid_path, reply})).unwrap();
+}
+fn ctrl_s() -> EditorEvent{EditorEvent::Key(KeyEvent::new(KeyCode::Char('s'), KeyModifiers::CONTROL))}
+
+#[test] fn registrations_can_be_read_back(){
+    let mut app = test_application_with(Config::default(), "idk\n", false);
+    assert_eq!(Ok(()), write(&mut app, ServedFile::InterceptRegistrations, vec!["Key Insert\n", "Mouse View\n"]));
+    assert_eq!(Ok(b"Key Insert\nMouse View\n".to_vec()), read(&mut app, ServedFile::InterceptRegistrations, 0, 8192));
+}
+#[test] fn invalid_registration_errors_and_keeps_existing_registrations(){
+    let mut app = test_application_with(Config::default(), "idk\n", false);
+    assert_eq!(Ok(()), write(&mut app, ServedFile::InterceptRegistrations, vec!["Key Insert\n"]));
+    assert!(write(&mut app, ServedFile::InterceptRegistrations, vec!["Key Insert\nKey Nonsense\n"]).is_err());
+    assert!(write(&mut app, ServedFile::InterceptRegistrations, vec!["Save Insert\n"]).is_err());
+    assert_eq!(Ok(b"Key Insert\n".to_vec()), read(&mut app, ServedFile::InterceptRegistrations, 0, 8192));
+}
+#[test] fn unregistered_event_passes_without_blocking(){
+    let mut app = test_application_with(Config::default(), "idk\n", false);
+    assert_eq!(Ok(()), write(&mut app, ServedFile::InterceptRegistrations, vec!["Key Command\n"]));
+    let (_event_tx, event_rx) = mpsc::channel();
+    assert_eq!(Acknowledgement::Pass, app.intercept(ctrl_s(), &event_rx));
+}
+#[test] fn handler_can_consume_event(){
+    let mut app = test_application_with(Config::default(), "idk\n", false);
+    assert_eq!(Ok(()), write(&mut app, ServedFile::InterceptRegistrations, vec!["Key Insert\n"]));
+    let (event_tx, event_rx) = mpsc::channel();
+    send_acknowledgement(&event_tx, "consumed\n");
+    assert_eq!(Acknowledgement::Consumed, app.intercept(ctrl_s(), &event_rx));
+}
+#[test] fn handler_can_pass_event(){
+    let mut app = test_application_with(Config::default(), "idk\n", false);
+    assert_eq!(Ok(()), write(&mut app, ServedFile::InterceptRegistrations, vec!["Key Insert\n"]));
+    let (event_tx, event_rx) = mpsc::channel();
+    send_acknowledgement(&event_tx, "pass");
+    assert_eq!(Acknowledgement::Pass, app.intercept(ctrl_s(), &event_rx));
+}
+#[test] fn pending_event_readable_while_blocked(){
+    let mut app = test_application_with(Config::default(), "idk\n", false);
+    assert_eq!(Ok(()), write(&mut app, ServedFile::InterceptRegistrations, vec!["Key Insert\n"]));
+    let (event_tx, event_rx) = mpsc::channel();
+    let (reply, reply_rx) = mpsc::channel();
+    event_tx.send(Event::NineP(0, FsRequest::Read{qid_path: ServedFile::InterceptPending.qid_path(), offset: 0, count: 8192, reply})).unwrap();
+    send_acknowledgement(&event_tx, "consumed");
+    assert_eq!(Acknowledgement::Consumed, app.intercept(ctrl_s(), &event_rx));
+    assert_eq!(Ok(b"Key ctrl-s\n".to_vec()), reply_rx.recv().unwrap());
+    // nothing pending once acknowledged
+    assert_eq!(Ok(Vec::new()), read(&mut app, ServedFile::InterceptPending, 0, 8192));
+}
+#[test] fn other_requests_wait_until_acknowledged(){
+    let mut app = test_application_with(Config::default(), "idk\n", false);
+    assert_eq!(Ok(()), write(&mut app, ServedFile::InterceptRegistrations, vec!["Key Insert\n"]));
+    let (event_tx, event_rx) = mpsc::channel();
+    let (reply, reply_rx) = mpsc::channel();
+    event_tx.send(Event::NineP(0, FsRequest::Write{qid_path: ServedFile::Command.qid_path(), offset: 0, data: b"insert_newline".to_vec(), reply})).unwrap();
+    send_acknowledgement(&event_tx, "consumed");
+    assert_eq!(Acknowledgement::Consumed, app.intercept(ctrl_s(), &event_rx));
+    // the command hasn't run, or been replied to
+    assert!(reply_rx.try_recv().is_err());
+    assert_eq!("idk\n", app.buffer.to_string());
+    app.handle_event(&event_rx).unwrap();
+    assert_eq!(Ok(14), reply_rx.recv().unwrap());
+    assert_eq!("\nidk\n", app.buffer.to_string());
+}
+#[test] fn unresponsive_handler_is_unregistered(){
+    let mut app = test_application_with(Config::default(), "idk\n", false);
+    assert_eq!(Ok(()), write(&mut app, ServedFile::InterceptRegistrations, vec!["Key Insert\n"]));
+    let (_event_tx, event_rx) = mpsc::channel();
+    assert_eq!(Acknowledgement::Pass, app.intercept(ctrl_s(), &event_rx));
+    assert_eq!(Ok(Vec::new()), read(&mut app, ServedFile::InterceptRegistrations, 0, 8192));
+}
+#[test] fn acknowledgement_without_pending_event_errors(){
+    let mut app = test_application_with(Config::default(), "idk\n", false);
+    assert!(write(&mut app, ServedFile::InterceptPending, vec!["consumed"]).is_err());
+}
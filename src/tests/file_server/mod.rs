@@ -0,0 +1,63 @@
+use std::sync::mpsc;
+use serve9p::file_system::{FsRequest, Qid};
+use crate::{
+    application::Application,
+    file_server::ServedFile,
+};
+
+
+
+mod walk;
+mod raw_text;
+mod selections;
+mod events;
+mod intercept;
+mod command;
+mod settings;
+mod registers;
+
+
+
+pub fn walk(app: &mut Application, from: ServedFile, wnames: Vec<&str>) -> Result<Vec<Qid>, String>{
+    let (reply, reply_rx) = mpsc::channel();
+    app.handle_fs_request(0, FsRequest::Walk{
+        user: String::from("test"), 
+        qid_path: from.qid_path(), 
+        wnames: wnames.iter().map(|name| name.to_string()).collect(), 
+        reply
+    });
+    reply_rx.recv().unwrap()
+}
+pub fn read(app: &mut Application, file: ServedFile, offset: u64, count: u32) -> Result<Vec<u8>, String>{
+    let (reply, reply_rx) = mpsc::channel();
+    app.handle_fs_request(0, FsRequest::Read{qid_path: file.qid_path(), offset, count, reply});
+    reply_rx.recv().unwrap()
+}
+/// Opens file for writing, writes each chunk at increasing offsets, then clunks. Returns the result of the clunk, 
+/// which is when written content is applied.
+pub fn write(app: &mut Application, file: ServedFile, chunks: Vec<&str>) -> Result<(), String>{
+    let mut offset = 0;
+    let mut writes = Vec::new();
+    for chunk in chunks{
+        writes.push((offset, chunk));
+        offset = offset + chunk.len() as u64;
+    }
+    write_at(app, file, writes)
+}
+/// Opens file for writing, writes each chunk at its offset, then clunks. Returns the result of the clunk.
+pub fn write_at(app: &mut Application, file: ServedFile, writes: Vec<(u64, &str)>) -> Result<(), String>{
+    let (reply, reply_rx) = mpsc::channel();
+    app.handle_fs_request(0, FsRequest::Open{user: String::from("test"), qid_path: file.qid_path(), mode: 1, reply});
+    reply_rx.recv().unwrap()?;
+
+    for (offset, chunk) in writes{
+        let (reply, reply_rx) = mpsc::channel();
+        app.handle_fs_request(0, FsRequest::Write{qid_path: file.qid_path(), offset, data: chunk.as_bytes().to_vec(), reply});
+        let bytes_written = reply_rx.recv().unwrap()?;
+        assert_eq!(chunk.len() as u32, bytes_written);
+    }
+
+    let (reply, reply_rx) = mpsc::channel();
+    app.handle_fs_request(0, FsRequest::Clunk{qid_path: file.qid_path(), reply});
+    reply_rx.recv().unwrap()
+}
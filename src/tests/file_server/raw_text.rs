@@ -0,0 +1,100 @@
+use crate::{
+    config::{Config, READ_ONLY_BUFFER},
+    file_server::ServedFile,
+    action::{Action, EditAction},
+};
+use crate::tests::common::test_application_with;
+use crate::tests::file_server::{read, write, write_at};
+
+#[test] fn read_returns_buffer_text(){
+    let mut app = test_application_with(Config::default(), "idk\nsome\nshit\n", false);
+    assert_eq!(Ok(b"idk\nsome\nshit\n".to_vec()), read(&mut app, ServedFile::BufferRawText, 0, 8192));
+}
+#[test] fn read_at_offset(){
+    let mut app = test_application_with(Config::default(), "idk\nsome\nshit\n", false);
+    assert_eq!(Ok(b"some".to_vec()), read(&mut app, ServedFile::BufferRawText, 4, 4));
+}
+#[test] fn read_past_end_returns_empty(){
+    let mut app = test_application_with(Config::default(), "idk\n", false);
+    assert_eq!(Ok(Vec::new()), read(&mut app, ServedFile::BufferRawText, 100, 8192));
+}
+
+#[test] fn write_replaces_buffer_text(){
+    let mut app = test_application_with(Config::default(), "idk\nsome\nshit\n", false);
+    assert_eq!(Ok(()), write(&mut app, ServedFile::BufferRawText, vec!["new\ntext\n"]));
+    assert_eq!("new\ntext\n", app.buffer.to_string());
+    assert_eq!(1, app.undo_stack.len());
+}
+#[test] fn write_in_multiple_chunks_is_a_single_change(){
+    let mut app = test_application_with(Config::default(), "idk\n", false);
+    assert_eq!(Ok(()), write(&mut app, ServedFile::BufferRawText, vec!["some\n", "shit\n"]));
+    assert_eq!("some\nshit\n", app.buffer.to_string());
+    assert_eq!(1, app.undo_stack.len());
+}
+#[test] fn write_at_earlier_offset_keeps_data_written_past_it(){
+    let mut app = test_application_with(Config::default(), "idk\n", false);
+    assert_eq!(Ok(()), write_at(&mut app, ServedFile::BufferRawText, vec![(0, "some\nshit\n"), (0, "idk")]));
+    assert_eq!("idke\nshit\n", app.buffer.to_string());
+}
+#[test] fn clunk_only_applies_writes_from_its_own_connection(){
+    use serve9p::file_system::FsRequest;
+    let mut app = test_application_with(Config::default(), "idk\n", false);
+    let qid_path = ServedFile::BufferRawText.qid_path();
+    for (connection, text) in [(1, "some\n"), (2, "shit\n")]{
+        let (reply, reply_rx) = std::sync::mpsc::channel();
+        app.handle_fs_request(connection, FsRequest::Open{user: String::from("test"), qid_path, mode: 1, reply});
+        assert!(reply_rx.recv().unwrap().is_ok());
+        let (reply, reply_rx) = std::sync::mpsc::channel();
+        app.handle_fs_request(connection, FsRequest::Write{qid_path, offset: 0, data: text.as_bytes().to_vec(), reply});
+        assert!(reply_rx.recv().unwrap().is_ok());
+    }
+    let clunk = |app: &mut crate::application::Application, connection|{
+        let (reply, reply_rx) = std::sync::mpsc::channel();
+        app.handle_fs_request(connection, FsRequest::Clunk{qid_path, reply});
+        reply_rx.recv().unwrap()
+    };
+    // each connection's write is applied whole, by its own clunk
+    assert_eq!(Ok(()), clunk(&mut app, 2));
+    assert_eq!("shit\n", app.buffer.to_string());
+    assert_eq!(Ok(()), clunk(&mut app, 1));
+    assert_eq!("some\n", app.buffer.to_string());
+}
+#[test] fn writes_from_disconnected_connection_are_dropped(){
+    use serve9p::file_system::FsRequest;
+    let mut app = test_application_with(Config::default(), "idk\n", false);
+    let qid_path = ServedFile::BufferRawText.qid_path();
+    let (reply, reply_rx) = std::sync::mpsc::channel();
+    app.handle_fs_request(1, FsRequest::Open{user: String::from("test"), qid_path, mode: 1, reply});
+    assert!(reply_rx.recv().unwrap().is_ok());
+    let (reply, reply_rx) = std::sync::mpsc::channel();
+    app.handle_fs_request(1, FsRequest::Write{qid_path, offset: 0, data: b"some\n".to_vec(), reply});
+    assert!(reply_rx.recv().unwrap().is_ok());
+    app.handle_fs_disconnect(1);
+    // nothing is left for a clunk to apply
+    let (reply, reply_rx) = std::sync::mpsc::channel();
+    app.handle_fs_request(1, FsRequest::Clunk{qid_path, reply});
+    assert_eq!(Ok(()), reply_rx.recv().unwrap());
+    assert_eq!("idk\n", app.buffer.to_string());
+}
+#[test] fn write_to_empty_buffer(){
+    let mut app = test_application_with(Config::default(), "", false);
+    assert_eq!(Ok(()), write(&mut app, ServedFile::BufferRawText, vec!["idk\n"]));
+    assert_eq!("idk\n", app.buffer.to_string());
+}
+#[test] fn write_can_be_undone_and_redone(){
+    let mut app = test_application_with(Config::default(), "idk\nsome\nshit\n", false);
+    assert_eq!(Ok(()), write(&mut app, ServedFile::BufferRawText, vec!["other\n"]));
+    app.update(Action::EditAction(EditAction::Undo));
+    assert_eq!("idk\nsome\nshit\n", app.buffer.to_string());
+    app.update(Action::EditAction(EditAction::Redo));
+    assert_eq!("other\n", app.buffer.to_string());
+}
+#[test] fn write_to_read_only_buffer_errors(){
+    let mut app = test_application_with(Config::default(), "idk\n", true);
+    assert_eq!(Err(String::from(READ_ONLY_BUFFER)), write(&mut app, ServedFile::BufferRawText, vec!["some\n"]));
+    assert_eq!("idk\n", app.buffer.to_string());
+}
+#[test] fn write_to_directory_errors(){
+    let mut app = test_application_with(Config::default(), "idk\n", false);
+    assert!(write(&mut app, ServedFile::BufferDirectory, vec!["some\n"]).is_err());
+}
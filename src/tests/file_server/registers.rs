@@ -0,0 +1,52 @@
+use crate::{
+    config::Config,
+    file_server::ServedFile,
+};
+use crate::tests::common::test_application_with;
+use crate::tests::file_server::{walk, read, write};
+
+fn register_content(app: &crate::application::Application, name: &str) -> ServedFile{
+    ServedFile::RegisterContent(app.registers.id(name).unwrap())
+}
+
+#[test] fn can_walk_to_register_content(){
+    let mut app = test_application_with(Config::default(), "idk\n", false);
+    let content = register_content(&app, "a");
+    let qids = walk(&mut app, ServedFile::Root, vec!["registers", "a", "content"]).unwrap();
+    assert_eq!(content.qid(), qids[2]);
+}
+#[test] fn write_sets_register_content(){
+    let mut app = test_application_with(Config::default(), "idk\n", false);
+    let content = register_content(&app, "a");
+    assert_eq!(Ok(()), write(&mut app, content, vec!["some\n", "shit\n"]));
+    assert_eq!(Some("some\nshit\n"), app.registers.get("a"));
+    assert_eq!(Ok(b"some\nshit\n".to_vec()), read(&mut app, content, 0, 8192));
+}
+#[test] fn add_creates_walkable_register(){
+    let mut app = test_application_with(Config::default(), "idk\n", false);
+    assert!(walk(&mut app, ServedFile::RegistersDirectory, vec!["snippet"]).is_err());
+    assert_eq!(Ok(()), write(&mut app, ServedFile::RegistersAdd, vec!["snippet\n"]));
+    assert!(app.registers.contains("snippet"));
+    let content = register_content(&app, "snippet");
+    let qids = walk(&mut app, ServedFile::RegistersDirectory, vec!["snippet", "content"]).unwrap();
+    assert_eq!(content.qid(), qids[1]);
+}
+#[test] fn add_existing_register_errors(){
+    let mut app = test_application_with(Config::default(), "idk\n", false);
+    assert!(write(&mut app, ServedFile::RegistersAdd, vec!["a"]).is_err());
+}
+#[test] fn del_removes_register(){
+    let mut app = test_application_with(Config::default(), "idk\n", false);
+    assert_eq!(Ok(()), write(&mut app, ServedFile::RegistersAdd, vec!["snippet"]));
+    let content = register_content(&app, "snippet");
+    assert_eq!(Ok(()), write(&mut app, ServedFile::RegistersDel, vec!["snippet\n"]));
+    assert!(!app.registers.contains("snippet"));
+    assert!(walk(&mut app, ServedFile::RegistersDirectory, vec!["snippet"]).is_err());
+    // previously walked fids no longer refer to anything
+    assert!(read(&mut app, content, 0, 8192).is_err());
+}
+#[test] fn del_builtin_register_errors(){
+    let mut app = test_application_with(Config::default(), "idk\n", false);
+    assert!(write(&mut app, ServedFile::RegistersDel, vec!["a"]).is_err());
+    assert!(app.registers.contains("a"));
+}
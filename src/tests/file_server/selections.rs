@@ -0,0 +1,140 @@
+use crate::{
+    config::Config,
+    file_server::ServedFile,
+    range::Range,
+    selection::{Selection, Direction, CursorSemantics::Block},
+    action::{Action, EditAction},
+};
+use crate::tests::{
+    common::{generate_selections, test_application_with},
+    file_server::{read, write},
+};
+
+#[test] fn read_range_addresses(){
+    let mut app = test_application_with(Config::default(), "idk\nsome\nshit\n", false);
+    app.selections = generate_selections(
+        vec![
+            Selection::new_unchecked(Range::new(0, 3), Some(Direction::Forward), None),
+            Selection::new_unchecked(Range::new(4, 9), Some(Direction::Forward), None),
+        ], 0, &app.buffer, Block
+    );
+    assert_eq!(Ok(b"1:1,1:4\n2:1,3:1\n".to_vec()), read(&mut app, ServedFile::SelectionsAllRangeAddresses, 0, 8192));
+}
+#[test] fn read_range_addresses_with_cursor_at_buffer_end(){
+    let mut app = test_application_with(Config::default(), "idk\n", false);
+    app.selections = generate_selections(vec![Selection::new_unchecked(Range::new(4, 5), None, None)], 0, &app.buffer, Block);
+    assert_eq!(Ok(b"2:1,2:2\n".to_vec()), read(&mut app, ServedFile::SelectionsAllRangeAddresses, 0, 8192));
+}
+
+#[test] fn write_range_addresses_replaces_selections(){
+    let mut app = test_application_with(Config::default(), "idk\nsome\nshit\n", false);
+    assert_eq!(Ok(()), write(&mut app, ServedFile::SelectionsAllRangeAddresses, vec!["1:1,1:4\n", "2:1,2:2\n", "2:2,2:5\n"]));
+    assert_eq!(
+        generate_selections(
+            vec![
+                Selection::new_unchecked(Range::new(0, 3), Some(Direction::Forward), None),
+                Selection::new_unchecked(Range::new(4, 5), None, None),
+                Selection::new_unchecked(Range::new(5, 8), Some(Direction::Forward), None),
+            ], 0, &app.buffer, Block
+        ),
+        app.selections
+    );
+}
+#[test] fn write_range_addresses_round_trips(){
+    let mut app = test_application_with(Config::default(), "idk\nsome\nshit\n", false);
+    assert_eq!(Ok(()), write(&mut app, ServedFile::SelectionsAllRangeAddresses, vec!["1:2,1:4\n3:1,3:5\n"]));
+    assert_eq!(Ok(b"1:2,1:4\n3:1,3:5\n".to_vec()), read(&mut app, ServedFile::SelectionsAllRangeAddresses, 0, 8192));
+}
+#[test] fn write_invalid_range_addresses_errors_and_preserves_selections(){
+    let mut app = test_application_with(Config::default(), "idk\nsome\nshit\n", false);
+    let selections_before = app.selections.clone();
+    assert!(write(&mut app, ServedFile::SelectionsAllRangeAddresses, vec!["1:1,1:1\n"]).is_err());    //block selection must contain at least one char
+    assert!(write(&mut app, ServedFile::SelectionsAllRangeAddresses, vec!["1:4,1:1\n"]).is_err());    //start after end
+    assert!(write(&mut app, ServedFile::SelectionsAllRangeAddresses, vec!["9:1,9:2\n"]).is_err());    //line out of bounds
+    assert!(write(&mut app, ServedFile::SelectionsAllRangeAddresses, vec!["1:9,1:10\n"]).is_err());   //column out of bounds
+    assert!(write(&mut app, ServedFile::SelectionsAllRangeAddresses, vec!["idk\n"]).is_err());
+    assert!(write(&mut app, ServedFile::SelectionsAllRangeAddresses, vec!["\n"]).is_err());
+    assert_eq!(selections_before, app.selections);
+}
+
+#[test] fn read_content_of_single_selection(){
+    let mut app = test_application_with(Config::default(), "idk\nsome\nshit\n", false);
+    app.selections = generate_selections(vec![Selection::new_unchecked(Range::new(4, 8), Some(Direction::Forward), None)], 0, &app.buffer, Block);
+    assert_eq!(Ok(b"some\n".to_vec()), read(&mut app, ServedFile::SelectionsAllContent, 0, 8192));
+}
+#[test] fn read_content_of_multiple_selections_is_one_escaped_line_per_selection(){
+    let mut app = test_application_with(Config::default(), "idk\nso\\me\nshit\n", false);
+    app.selections = generate_selections(
+        vec![
+            Selection::new_unchecked(Range::new(0, 3), Some(Direction::Forward), None),
+            Selection::new_unchecked(Range::new(4, 10), Some(Direction::Forward), None),
+        ], 0, &app.buffer, Block
+    );
+    assert_eq!(Ok(b"idk\nso\\\\me\\n\n".to_vec()), read(&mut app, ServedFile::SelectionsAllContent, 0, 8192));
+}
+#[test] fn write_content_round_trips(){
+    let mut app = test_application_with(Config::default(), "idk\nso\\me\nshit\n", false);
+    app.selections = generate_selections(
+        vec![
+            Selection::new_unchecked(Range::new(0, 3), Some(Direction::Forward), None),
+            Selection::new_unchecked(Range::new(4, 10), Some(Direction::Forward), None),
+        ], 0, &app.buffer, Block
+    );
+    let content = String::from_utf8(read(&mut app, ServedFile::SelectionsAllContent, 0, 8192).unwrap()).unwrap();
+    assert_eq!(Ok(()), write(&mut app, ServedFile::SelectionsAllContent, vec![&content]));
+    assert_eq!("idk\nso\\me\nshit\n", app.buffer.to_string());
+    assert_eq!(0, app.undo_stack.len());
+    assert_eq!(Ok(()), write(&mut app, ServedFile::SelectionsAllContent, vec![&content.to_uppercase().replace("\\N", "\\n")]));
+    assert_eq!("IDK\nSO\\ME\nshit\n", app.buffer.to_string());
+    app.update(Action::EditAction(EditAction::Undo));
+    assert_eq!("idk\nso\\me\nshit\n", app.buffer.to_string());
+}
+#[test] fn write_single_line_of_content_replaces_text_in_every_selection(){
+    let mut app = test_application_with(Config::default(), "idk\nsome\nshit\n", false);
+    app.selections = generate_selections(
+        vec![
+            Selection::new_unchecked(Range::new(0, 3), Some(Direction::Forward), None),
+            Selection::new_unchecked(Range::new(9, 13), Some(Direction::Forward), None),
+        ], 0, &app.buffer, Block
+    );
+    assert_eq!(Ok(()), write(&mut app, ServedFile::SelectionsAllContent, vec!["other\\n"]));
+    assert_eq!("other\n\nsome\nother\n\n", app.buffer.to_string());
+    assert_eq!(1, app.undo_stack.len());
+}
+#[test] fn write_content_with_wrong_line_count_errors(){
+    let mut app = test_application_with(Config::default(), "idk\nsome\nshit\n", false);
+    app.selections = generate_selections(
+        vec![
+            Selection::new_unchecked(Range::new(0, 3), Some(Direction::Forward), None),
+            Selection::new_unchecked(Range::new(9, 13), Some(Direction::Forward), None),
+        ], 0, &app.buffer, Block
+    );
+    assert!(write(&mut app, ServedFile::SelectionsAllContent, vec!["a\nb\nc\n"]).is_err());
+    assert!(write(&mut app, ServedFile::SelectionsAllContent, vec!["a\\b\n"]).is_err());
+    assert_eq!("idk\nsome\nshit\n", app.buffer.to_string());
+}
+#[test] fn write_content_to_read_only_buffer_errors(){
+    let mut app = test_application_with(Config::default(), "idk\nsome\nshit\n", true);
+    assert!(write(&mut app, ServedFile::SelectionsAllContent, vec!["other"]).is_err());
+    assert_eq!("idk\nsome\nshit\n", app.buffer.to_string());
+}
+
+#[test] fn read_and_write_primary_index(){
+    let mut app = test_application_with(Config::default(), "idk\nsome\nshit\n", false);
+    app.selections = generate_selections(
+        vec![
+            Selection::new_unchecked(Range::new(0, 1), None, None),
+            Selection::new_unchecked(Range::new(4, 5), None, None),
+        ], 0, &app.buffer, Block
+    );
+    assert_eq!(Ok(b"0\n".to_vec()), read(&mut app, ServedFile::SelectionsPrimaryIndex, 0, 8192));
+    assert_eq!(Ok(()), write(&mut app, ServedFile::SelectionsPrimaryIndex, vec!["1\n"]));
+    assert_eq!(1, app.selections.primary_selection_index());
+    assert_eq!(Ok(b"1\n".to_vec()), read(&mut app, ServedFile::SelectionsPrimaryIndex, 0, 8192));
+}
+#[test] fn write_out_of_bounds_primary_index_errors(){
+    let mut app = test_application_with(Config::default(), "idk\nsome\nshit\n", false);
+    assert!(write(&mut app, ServedFile::SelectionsPrimaryIndex, vec!["1"]).is_err());
+    assert!(write(&mut app, ServedFile::SelectionsPrimaryIndex, vec!["idk"]).is_err());
+    assert_eq!(0, app.selections.primary_selection_index());
+}
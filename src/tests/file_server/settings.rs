@@ -0,0 +1,41 @@
+use crate::{
+    config::Config,
+    file_server::{ServedFile, SETTINGS},
+};
+use crate::tests::common::test_application_with;
+use crate::tests::file_server::{walk, read, write};
+
+#[test] fn every_setting_can_be_walked_to_and_read(){
+    let mut app = test_application_with(Config::default(), "idk\n", false);
+    for name in SETTINGS{
+        let qids = walk(&mut app, ServedFile::Root, vec!["settings", name]).unwrap();
+        assert_eq!(ServedFile::Setting(name).qid(), qids[1]);
+        assert!(read(&mut app, ServedFile::Setting(name), 0, 8192).is_ok());
+    }
+}
+#[test] fn unknown_setting_cannot_be_walked_to(){
+    let mut app = test_application_with(Config::default(), "idk\n", false);
+    assert_eq!(Ok(vec![ServedFile::SettingsDirectory.qid()]), walk(&mut app, ServedFile::Root, vec!["settings", "idk"]));
+}
+#[test] fn read_returns_current_value(){
+    let mut app = test_application_with(Config{tab_width: 8, ..Config::default()}, "idk\n", false);
+    assert_eq!(Ok(b"8\n".to_vec()), read(&mut app, ServedFile::Setting("tab_width"), 0, 8192));
+    assert_eq!(Ok(b"Block\n".to_vec()), read(&mut app, ServedFile::Setting("cursor_semantics"), 0, 8192));
+}
+#[test] fn write_sets_value(){
+    let mut app = test_application_with(Config::default(), "idk\n", false);
+    assert_eq!(Ok(()), write(&mut app, ServedFile::Setting("tab_width"), vec!["2\n"]));
+    assert_eq!(Ok(b"2\n".to_vec()), read(&mut app, ServedFile::Setting("tab_width"), 0, 8192));
+}
+#[test] fn write_sets_ui_toggle(){
+    let mut app = test_application_with(Config::default(), "idk\n", false);
+    assert_eq!(Ok(()), write(&mut app, ServedFile::Setting("show_status_bar"), vec!["false"]));
+    assert!(!app.ui.status_bar.show);
+    assert_eq!(Ok(b"false\n".to_vec()), read(&mut app, ServedFile::Setting("show_status_bar"), 0, 8192));
+}
+#[test] fn invalid_value_errors_and_keeps_current_value(){
+    let mut app = test_application_with(Config::default(), "idk\n", false);
+    assert!(write(&mut app, ServedFile::Setting("use_hard_tab"), vec!["idk\n"]).is_err());
+    assert!(write(&mut app, ServedFile::Setting("cursor_semantics"), vec!["idk\n"]).is_err());
+    assert_eq!(Ok(b"false\n".to_vec()), read(&mut app, ServedFile::Setting("use_hard_tab"), 0, 8192));
+}
@@ -0,0 +1,29 @@
+use crate::{
+    config::Config,
+    file_server::ServedFile,
+};
+use crate::tests::common::test_application_with;
+use crate::tests::file_server::{walk};
+
+#[test] fn walk_to_nested_file(){
+    let mut app = test_application_with(Config::default(), "idk\n", false);
+    assert_eq!(
+        Ok(vec![ServedFile::BufferDirectory.qid(), ServedFile::BufferRawText.qid()]), 
+        walk(&mut app, ServedFile::Root, vec!["buffer", "raw_text"])
+    );
+}
+#[test] fn walk_to_parent_directory(){
+    let mut app = test_application_with(Config::default(), "idk\n", false);
+    assert_eq!(Ok(vec![ServedFile::Root.qid()]), walk(&mut app, ServedFile::BufferDirectory, vec![".."]));
+}
+#[test] fn partial_walk_returns_qids_walked(){
+    let mut app = test_application_with(Config::default(), "idk\n", false);
+    assert_eq!(
+        Ok(vec![ServedFile::BufferDirectory.qid()]), 
+        walk(&mut app, ServedFile::Root, vec!["buffer", "does_not_exist"])
+    );
+}
+#[test] fn walk_to_non_existent_file_errors(){
+    let mut app = test_application_with(Config::default(), "idk\n", false);
+    assert!(walk(&mut app, ServedFile::Root, vec!["does_not_exist"]).is_err());
+}
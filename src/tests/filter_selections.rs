@@ -0,0 +1,75 @@
+use crate::{
+    action::{Action, UtilAction},
+    mode::Mode,
+};
+use crate::tests::common::{test_application, execute, type_in_mode, selected_texts};
+
+
+#[test] fn keep_matching_keeps_selections_whose_text_matches(){
+    let mut app = test_application("idk\n//TODO: some\nshit\n//TODO: shit\n");
+    execute(&mut app, "search '.*\\n'");
+    assert_eq!(4, app.selections.count());
+    execute(&mut app, "keep_matching TODO");
+    assert_eq!(Mode::Insert, app.mode());
+    assert_eq!(vec!["//TODO: some\n", "//TODO: shit\n"], selected_texts(&app));
+}
+#[test] fn remove_matching_removes_selections_whose_text_matches(){
+    let mut app = test_application("idk\n//TODO: some\nshit\n//TODO: shit\n");
+    execute(&mut app, "search '.*\\n'");
+    execute(&mut app, "remove_matching TODO");
+    assert_eq!(vec!["idk\n", "shit\n"], selected_texts(&app));
+}
+#[test] fn removed_primary_passes_to_preceding_selection(){
+    let mut app = test_application("idk some shit\n");
+    execute(&mut app, "search '\\w+'");
+    execute(&mut app, "increment_primary_selection");
+    assert_eq!("some", app.selections.primary.to_string(&app.buffer));
+    execute(&mut app, "remove_matching some");
+    assert_eq!("idk", app.selections.primary.to_string(&app.buffer));
+}
+#[test] fn filtering_every_selection_out_errors(){
+    let mut app = test_application("idk some shit\n");
+    execute(&mut app, "search '\\w+'");
+    execute(&mut app, "keep_matching nothing");
+    assert_eq!(Mode::Error, app.mode());
+    assert_eq!(Some(String::from("no selections would remain")), app.mode_message());
+    assert_eq!(3, app.selections.count());
+}
+#[test] fn invalid_regex_reports_compile_error(){
+    let mut app = test_application("idk some shit\n");
+    execute(&mut app, "search '\\w+'");
+    let pattern = String::from("idk(");
+    execute(&mut app, &format!("keep_matching {}", pattern));
+    assert_eq!(Mode::Error, app.mode());
+    assert_eq!(Some(format!("{}", regex::Regex::new(&pattern).unwrap_err())), app.mode_message());
+    assert_eq!(3, app.selections.count());
+}
+#[test] fn keep_mode_shows_invalid_regex_without_error(){
+    let mut app = test_application("idk some shit\n");
+    execute(&mut app, "search '\\w+'");
+    type_in_mode(&mut app, Mode::Keep, "s(");
+    assert_eq!(Mode::Keep, app.mode());
+    assert!(!app.ui.util_bar.utility_widget.text_box.text_is_valid);
+    assert_eq!(3, app.selections.count());
+}
+
+#[test] fn keep_mode_filters_selections_as_regex_is_typed(){
+    let mut app = test_application("idk some shit\n");
+    execute(&mut app, "search '\\w+'");
+    type_in_mode(&mut app, Mode::Keep, "s");
+    assert_eq!(vec!["some", "shit"], selected_texts(&app));
+    app.update(Action::UtilAction(UtilAction::InsertChar('h')));
+    assert_eq!(vec!["shit"], selected_texts(&app));
+    app.update(Action::UtilAction(UtilAction::Accept));
+    assert_eq!(Mode::Insert, app.mode());
+    assert_eq!(vec!["shit"], selected_texts(&app));
+}
+#[test] fn exiting_remove_mode_restores_selections(){
+    let mut app = test_application("idk some shit\n");
+    execute(&mut app, "search '\\w+'");
+    type_in_mode(&mut app, Mode::Remove, "idk");
+    assert_eq!(vec!["some", "shit"], selected_texts(&app));
+    app.update(Action::UtilAction(UtilAction::Exit));
+    assert_eq!(Mode::Insert, app.mode());
+    assert_eq!(vec!["idk", "some", "shit"], selected_texts(&app));
+}
@@ -0,0 +1,89 @@
+use crate::{
+    action::{Action, EditAction, EditorAction},
+    application::{Application, option_value},
+    mode::Mode,
+};
+use crate::tests::common::{test_application, execute};
+
+/// Executes `command`, then dismisses any resulting notification, so hooks see the editor back in Insert mode.
+fn execute_and_dismiss(app: &mut Application, command: &str){
+    execute(app, command);
+    while app.mode() != Mode::Insert && app.mode() != Mode::Error{app.update(Action::EditorAction(EditorAction::ModePop));}
+}
+/// Returns the path of a file, unique to the calling test, containing `text`. For a buffer to be saved to.
+fn save_path(test_name: &str, text: &str) -> std::path::PathBuf{
+    let path = std::env::temp_dir().join(format!("edit_hooks_{}_{}", test_name, std::process::id()));
+    std::fs::write(&path, text).unwrap();
+    path
+}
+
+#[test] fn hook_runs_when_filter_matches_event_parameter(){
+    let mut app = test_application("idk\n");
+    execute_and_dismiss(&mut app, "add_hook --group test --event TextInserted --filter ^x$ --command 'set tab_width 8'");
+    app.update(Action::EditAction(EditAction::InsertChar('y')));
+    assert_eq!(Ok(String::from("4")), option_value(&app, "tab_width"));
+    app.update(Action::EditAction(EditAction::InsertChar('x')));
+    assert_eq!(Ok(String::from("8")), option_value(&app, "tab_width"));
+}
+#[test] fn removed_hooks_no_longer_run(){
+    let mut app = test_application("idk\n");
+    execute_and_dismiss(&mut app, "add_hook --group test --event TextInserted --command 'set tab_width 8'");
+    execute_and_dismiss(&mut app, "remove_hooks test");
+    app.update(Action::EditAction(EditAction::InsertChar('x')));
+    assert_eq!(Ok(String::from("4")), option_value(&app, "tab_width"));
+    execute_and_dismiss(&mut app, "remove_hooks test");
+    assert_eq!(Mode::Error, app.mode());
+}
+#[test] fn hook_does_not_trigger_itself(){
+    let mut app = test_application("idk\n");
+    execute_and_dismiss(&mut app, "add_hook --group test --event TextInserted --command insert_newline");
+    app.update(Action::EditAction(EditAction::InsertChar('x')));
+    assert_eq!("x\nidk\n", app.buffer.to_string());
+}
+#[test] fn buffer_save_pre_hook_runs_before_buffer_is_written(){
+    let path = save_path("save_pre", "idk\n");
+    let mut app = test_application("idk\n");
+    app.buffer.file_path = Some(path.clone());
+    execute_and_dismiss(&mut app, "add_hook --group test --event BufferSavePre --command insert_newline");
+    app.update(Action::EditAction(EditAction::InsertChar('x')));
+    app.update(Action::EditorAction(EditorAction::Save));
+    assert_eq!("x\nidk\n", std::fs::read_to_string(&path).unwrap());
+    let _ = std::fs::remove_file(path);
+}
+#[test] fn buffer_saved_hook_runs_after_save(){
+    let path = save_path("saved", "idk\n");
+    let mut app = test_application("idk\n");
+    app.buffer.file_path = Some(path.clone());
+    execute_and_dismiss(&mut app, &format!("add_hook --group test --event BufferSaved --filter {} --command 'set tab_width 8'", path.display()));
+    app.update(Action::EditAction(EditAction::InsertChar('x')));
+    app.update(Action::EditorAction(EditorAction::Save));
+    assert_eq!(Ok(String::from("8")), option_value(&app, "tab_width"));
+    let _ = std::fs::remove_file(path);
+}
+#[test] fn error_occurred_hook_receives_message(){
+    let mut app = test_application("idk\n");
+    execute_and_dismiss(&mut app, "add_hook --group test --event ErrorOccurred --filter 'no matching' --command 'set tab_width 8'");
+    execute_and_dismiss(&mut app, "search shit");
+    assert_eq!(Ok(String::from("8")), option_value(&app, "tab_width"));
+}
+#[test] fn selections_changed_hook_runs_on_selection_movement(){
+    let mut app = test_application("idk\nsome\n");
+    execute_and_dismiss(&mut app, "add_hook --group test --event SelectionsChanged --filter ^s$ --command 'set tab_width 8'");
+    execute_and_dismiss(&mut app, "move_cursor_down");
+    assert_eq!(Ok(String::from("8")), option_value(&app, "tab_width"));
+}
+#[test] fn invalid_hook_event_errors(){
+    let mut app = test_application("idk\n");
+    execute_and_dismiss(&mut app, "add_hook --group test --event Idk --command 'set tab_width 8'");
+    assert_eq!(Mode::Error, app.mode());
+}
+#[test] fn hook_action_doesnt_exit_find_mode(){
+    let mut app = test_application("idk\nsome\n");
+    execute_and_dismiss(&mut app, "add_hook --group test --event ModeChanged --filter '^Insert Find$' --command move_cursor_down");
+    app.update(Action::EditorAction(EditorAction::ModePush(Mode::Find, None)));
+    // the hook fails, instead of exiting find mode and dropping its saved selections
+    assert_eq!(Mode::Error, app.mode());
+    app.update(Action::EditorAction(EditorAction::ModePop));
+    assert_eq!(Mode::Find, app.mode());
+    assert_eq!(0, app.selections.primary.range.start);
+}
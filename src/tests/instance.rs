@@ -0,0 +1,73 @@
+use std::path::{Path, PathBuf};
+use crate::instance::Instance;
+
+/// Returns an empty directory, unique to the calling test, to use as the runtime dir.
+fn test_runtime_dir(test_name: &str) -> PathBuf{
+    let dir = std::env::temp_dir().join(format!("edit_{}_{}", test_name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+fn read_index(runtime_dir: &Path) -> String{
+    std::fs::read_to_string(runtime_dir.join("edit").join("index")).unwrap()
+}
+
+#[test] fn register_binds_socket_and_adds_index_entry(){
+    let runtime_dir = test_runtime_dir("register");
+    let (instance, _listener) = Instance::register(&runtime_dir, 1, Some(Path::new("/some/file.rs"))).unwrap();
+    assert_eq!(runtime_dir.join("edit").join("1.sock"), instance.socket_path);
+    assert!(instance.socket_path.exists());
+    assert_eq!("1 /some/file.rs\n", read_index(&runtime_dir));
+    instance.cleanup();
+}
+#[test] fn temp_buffer_index_entry_has_no_file_path(){
+    let runtime_dir = test_runtime_dir("temp_buffer");
+    let (instance, _listener) = Instance::register(&runtime_dir, 1, None).unwrap();
+    assert_eq!("1\n", read_index(&runtime_dir));
+    instance.cleanup();
+}
+#[test] fn multiple_instances_coexist(){
+    let runtime_dir = test_runtime_dir("coexist");
+    let (first, _first_listener) = Instance::register(&runtime_dir, 1, Some(Path::new("/idk"))).unwrap();
+    let (second, _second_listener) = Instance::register(&runtime_dir, 2, Some(Path::new("/some/shit"))).unwrap();
+    assert_eq!("1 /idk\n2 /some/shit\n", read_index(&runtime_dir));
+    assert!(first.socket_path.exists());
+    assert!(second.socket_path.exists());
+    second.cleanup();
+    first.cleanup();
+}
+#[test] fn cleanup_removes_socket_and_only_own_index_entry(){
+    let runtime_dir = test_runtime_dir("cleanup");
+    let (first, _first_listener) = Instance::register(&runtime_dir, 1, None).unwrap();
+    let (second, _second_listener) = Instance::register(&runtime_dir, 2, None).unwrap();
+    first.cleanup();
+    assert!(!first.socket_path.exists());
+    assert_eq!("2\n", read_index(&runtime_dir));
+    // safe to call again, such as from the panic hook after a drop
+    first.cleanup();
+    assert_eq!("2\n", read_index(&runtime_dir));
+    second.cleanup();
+    assert_eq!("", read_index(&runtime_dir));
+}
+#[test] fn entries_for_instances_no_longer_listening_are_dropped(){
+    let runtime_dir = test_runtime_dir("stale");
+    let (crashed, crashed_listener) = Instance::register(&runtime_dir, 1, None).unwrap();
+    drop(crashed_listener); // socket file left behind, but nothing listening
+    assert!(crashed.socket_path.exists());
+    let (instance, _listener) = Instance::register(&runtime_dir, 2, None).unwrap();
+    assert_eq!("2\n", read_index(&runtime_dir));
+    instance.cleanup();
+}
+#[test] fn instances_registering_at_once_all_get_index_entries(){
+    let runtime_dir = test_runtime_dir("concurrent");
+    let handles: Vec<_> = (1..=16).map(|id|{
+        let runtime_dir = runtime_dir.clone();
+        std::thread::spawn(move || Instance::register(&runtime_dir, id, None).unwrap())
+    }).collect();
+    let instances: Vec<_> = handles.into_iter().map(|handle| handle.join().unwrap()).collect();
+    let mut ids: Vec<u32> = read_index(&runtime_dir).lines().map(|line| line.parse().unwrap()).collect();
+    ids.sort();
+    assert_eq!((1..=16).collect::<Vec<u32>>(), ids);
+    for (instance, _listener) in &instances{instance.cleanup();}
+    assert_eq!("", read_index(&runtime_dir));
+}
@@ -0,0 +1,86 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use crate::{
+    action::{Action, EditorAction, SelectionAction},
+    application::{Event, option_value},
+    keybind::{string_to_key_event, key_event_to_string},
+    mode::Mode,
+};
+use crate::tests::common::{test_application, execute};
+
+
+#[test] fn key_chord_strings_round_trip(){
+    for key_event in [
+        KeyEvent::new(KeyCode::Enter, KeyModifiers::CONTROL | KeyModifiers::SHIFT),
+        KeyEvent::new(KeyCode::Char('r'), KeyModifiers::ALT),
+        KeyEvent::new(KeyCode::Char(' '), KeyModifiers::CONTROL),
+        KeyEvent::new(KeyCode::Char('-'), KeyModifiers::NONE),
+        KeyEvent::new(KeyCode::F(5), KeyModifiers::NONE),
+        KeyEvent::new(KeyCode::PageDown, KeyModifiers::SHIFT),
+    ]{
+        assert_eq!(Ok(key_event), string_to_key_event(&key_event_to_string(&key_event)));
+    }
+}
+#[test] fn invalid_key_chords_error(){
+    assert!(string_to_key_event("super-a").is_err());
+    assert!(string_to_key_event("ctrl-idk").is_err());
+    assert!(string_to_key_event("").is_err());
+}
+#[test] fn action_names_accept_underscores(){
+    assert!(matches!(Action::from_command_name("select_line"), Some(Action::SelectionAction(SelectionAction::SelectLine, 1))));
+    assert!(matches!(Action::from_command_name("select line"), Some(Action::SelectionAction(SelectionAction::SelectLine, 1))));
+    assert!(Action::from_command_name("idk").is_none());
+}
+#[test] fn add_keybind_binds_action_name(){
+    let mut app = test_application("idk\n");
+    execute(&mut app, "add_keybind Object ctrl-l select_line");
+    let key_event = KeyEvent::new(KeyCode::Char('l'), KeyModifiers::CONTROL);
+    assert!(matches!(app.keybinds().get(&(Mode::Object, key_event)), Some(Action::SelectionAction(SelectionAction::SelectLine, 1))));
+    // popup menus are regenerated to include the new keybind
+    assert!(app.ui.popups.object.text.contains("select line"));
+}
+#[test] fn add_keybind_binds_command_string(){
+    let mut app = test_application("idk\n");
+    execute(&mut app, "add_keybind Insert ctrl-shift-enter 'set tab_width 8'");
+    let key_event = KeyEvent::new(KeyCode::Enter, KeyModifiers::CONTROL | KeyModifiers::SHIFT);
+    let action = app.keybinds().get(&(Mode::Insert, key_event)).cloned().unwrap();
+    assert_eq!("set tab_width 8", action.command_name());
+    app.update(action);
+    assert_eq!(Ok(String::from("8")), option_value(&app, "tab_width"));
+}
+#[test] fn add_keybind_errors_if_action_cant_run_in_mode(){
+    let (event_tx, event_rx) = std::sync::mpsc::channel();
+    let mut app = test_application("idk\n");
+    for (mode, key, command) in [(Mode::View, 's', "add_keybind View ctrl-s save"), (Mode::Find, 'l', "add_keybind Find ctrl-l select_line")]{
+        execute(&mut app, command);
+        assert_eq!(Mode::Error, app.mode());
+        let key_event = KeyEvent::new(KeyCode::Char(key), KeyModifiers::CONTROL);
+        assert!(!app.keybinds().contains_key(&(mode.clone(), key_event)));
+        // pressing the key in that mode doesn't panic by performing the action
+        app.update(Action::EditorAction(EditorAction::ModePush(mode, None)));
+        event_tx.send(Event::KeyboardInput(key_event)).unwrap();
+        app.handle_event(&event_rx).unwrap();
+        app.update(Action::EditorAction(EditorAction::ModePop));
+    }
+}
+#[test] fn add_keybind_errors_if_already_mapped(){
+    let mut app = test_application("idk\n");
+    execute(&mut app, "add_keybind Insert ctrl-s quit");
+    assert_eq!(Mode::Error, app.mode());
+    let key_event = KeyEvent::new(KeyCode::Char('s'), KeyModifiers::CONTROL);
+    assert!(matches!(app.keybinds().get(&(Mode::Insert, key_event)), Some(Action::EditorAction(EditorAction::Save))));
+}
+#[test] fn remove_keybind_removes_mapping_and_updates_popups(){
+    let mut app = test_application("idk\n");
+    assert!(app.ui.popups.view.text.contains("scroll up"));
+    execute(&mut app, "remove_keybind View up");
+    assert!(!app.keybinds().contains_key(&(Mode::View, KeyEvent::new(KeyCode::Up, KeyModifiers::NONE))));
+    assert!(!app.ui.popups.view.text.contains("scroll up"));
+    execute(&mut app, "remove_keybind View up");
+    assert_eq!(Mode::Error, app.mode());
+}
+#[test] fn list_keybinds_for_mode(){
+    let mut app = test_application("idk\n");
+    execute(&mut app, "list_keybinds Register");
+    assert_eq!(Mode::Info, app.mode());
+    assert_eq!(Some(String::from("Register esc exit mode")), app.mode_message());
+}
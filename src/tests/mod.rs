@@ -6,3 +6,16 @@ mod view_actions;
 mod buffer;
 mod display_area;
 mod selection;
+mod file_server;
+mod instance;
+mod registers;
+mod clipboard;
+mod commands;
+mod keybind;
+mod user_commands;
+mod hooks;
+mod user_options;
+mod sam;
+mod replace;
+mod filter_selections;
+mod search;
@@ -0,0 +1,156 @@
+use crate::{
+    action::{Action, EditAction, EditorAction},
+    mode::Mode,
+    registers::{Registers, YANK_REGISTER, SEARCH_REGISTER, COMMAND_REGISTER},
+    application::Application,
+    range::Range,
+    selection::{Selection, CursorSemantics::Block},
+};
+use crate::tests::common::{generate_selections, test_application};
+
+/// Selects the first character of each line in "idk\nsome\nshit\n".
+fn select_line_starts(app: &mut Application){
+    app.selections = generate_selections(
+        vec![
+            Selection::new_unchecked(Range::new(0, 1), None, None),
+            Selection::new_unchecked(Range::new(4, 5), None, None),
+            Selection::new_unchecked(Range::new(9, 10), None, None),
+        ],
+        0,
+        &app.buffer,
+        Block
+    );
+}
+fn select_register(app: &mut Application, name: char){
+    app.update(Action::EditorAction(EditorAction::ModePush(Mode::Register, None)));
+    assert_eq!(Mode::Register, app.mode());
+    app.update(Action::EditorAction(EditorAction::SelectRegister(name)));
+}
+
+#[test] fn lettered_and_special_registers_exist_by_default(){
+    let registers = Registers::default();
+    for name in ('a'..='z').map(|c| c.to_string()){
+        assert_eq!(Some(""), registers.get(&name));
+    }
+    assert_eq!(Some(""), registers.get(YANK_REGISTER));
+    assert_eq!(Some(""), registers.get(SEARCH_REGISTER));
+    assert_eq!(Some(""), registers.get(COMMAND_REGISTER));
+}
+#[test] fn added_registers_can_be_removed(){
+    let mut registers = Registers::default();
+    assert_eq!(Ok(()), registers.add("snippet"));
+    assert!(registers.add("snippet").is_err());
+    assert_eq!(Ok(()), registers.set("snippet", "idk"));
+    assert_eq!(Some("idk"), registers.get("snippet"));
+    assert_eq!(Ok(()), registers.remove("snippet"));
+    assert_eq!(None, registers.get("snippet"));
+}
+#[test] fn builtin_registers_cannot_be_removed(){
+    let mut registers = Registers::default();
+    assert!(registers.remove("a").is_err());
+    assert!(registers.remove(YANK_REGISTER).is_err());
+    assert!(registers.remove("idk").is_err());
+}
+#[test] fn invalid_register_names_cannot_be_added(){
+    let mut registers = Registers::default();
+    assert!(registers.add("").is_err());
+    assert!(registers.add("some/shit").is_err());
+    assert!(registers.add("some shit").is_err());
+}
+#[test] fn set_values_joins_content(){
+    let mut registers = Registers::default();
+    assert_eq!(Ok(()), registers.set_values("a", vec![String::from("idk"), String::from("some")]));
+    assert_eq!(Some(&[String::from("idk"), String::from("some")][..]), registers.values("a"));
+    assert_eq!(Some("idk\nsome"), registers.get("a"));
+    assert_eq!(Ok(()), registers.set("a", "shit"));
+    assert_eq!(Some(&[String::from("shit")][..]), registers.values("a"));
+    assert!(registers.set_values("idk", vec![]).is_err());
+}
+
+#[test] fn copy_with_multiple_selections_stores_value_per_selection(){
+    let mut app = test_application("idk\nsome\nshit\n");
+    select_line_starts(&mut app);
+    app.update(Action::EditorAction(EditorAction::Copy));
+    assert_eq!(Some(&[String::from("i"), String::from("s"), String::from("s")][..]), app.registers.values(YANK_REGISTER));
+    assert_eq!(Some("i\ns\ns"), app.registers.get(YANK_REGISTER));
+}
+#[test] fn paste_with_value_per_selection_pastes_each_value_at_its_selection(){
+    let mut app = test_application("idk\nsome\nshit\n");
+    select_line_starts(&mut app);
+    app.registers.set_values(YANK_REGISTER, vec![String::from("a"), String::from("b"), String::from("c")]).unwrap();
+    app.update(Action::EditAction(EditAction::Paste));
+    assert_eq!("aidk\nbsome\ncshit\n", app.buffer.to_string());
+    // the paste is undone as a single change set
+    app.update(Action::EditAction(EditAction::Undo));
+    assert_eq!("idk\nsome\nshit\n", app.buffer.to_string());
+}
+#[test] fn paste_with_mismatched_value_count_pastes_joined_values_at_each_selection(){
+    let mut app = test_application("idk\nsome\nshit\n");
+    select_line_starts(&mut app);
+    app.registers.set_values(YANK_REGISTER, vec![String::from("a"), String::from("b")]).unwrap();
+    app.update(Action::EditAction(EditAction::Paste));
+    assert_eq!("a\nbidk\na\nbsome\na\nbshit\n", app.buffer.to_string());
+}
+
+#[test] fn cut_with_multiple_selections_then_paste_restores_each_selection(){
+    let mut app = test_application("idk\nsome\nshit\n");
+    select_line_starts(&mut app);
+    app.update(Action::EditAction(EditAction::Cut));
+    assert_eq!(Some(&[String::from("i"), String::from("s"), String::from("s")][..]), app.registers.values(YANK_REGISTER));
+    assert_eq!("dk\nome\nhit\n", app.buffer.to_string());
+    app.update(Action::EditAction(EditAction::Paste));
+    assert_eq!("idk\nsome\nshit\n", app.buffer.to_string());
+    // the cut is undone as a single change set
+    app.update(Action::EditAction(EditAction::Undo));
+    app.update(Action::EditAction(EditAction::Undo));
+    assert_eq!("idk\nsome\nshit\n", app.buffer.to_string());
+    assert!(app.undo_stack.is_empty());
+}
+#[test] fn copy_with_register_prefix_sets_register_and_yank(){
+    let mut app = test_application("idk\nsome\nshit\n");
+    select_register(&mut app, 'a');
+    assert_eq!(Mode::Insert, app.mode());
+    assert_eq!(Some(String::from("a")), app.selected_register);
+    app.update(Action::EditorAction(EditorAction::Copy));
+    assert_eq!(Some("i"), app.registers.get("a"));
+    assert_eq!(Some("i"), app.registers.get(YANK_REGISTER));
+    assert_eq!(None, app.selected_register);
+}
+#[test] fn copy_without_register_prefix_only_sets_yank(){
+    let mut app = test_application("idk\nsome\nshit\n");
+    app.update(Action::EditorAction(EditorAction::Copy));
+    assert_eq!(Some("i"), app.registers.get(YANK_REGISTER));
+    assert_eq!(Some(""), app.registers.get("a"));
+}
+#[test] fn cut_with_register_prefix_sets_register(){
+    let mut app = test_application("idk\nsome\nshit\n");
+    select_register(&mut app, 'b');
+    app.update(Action::EditAction(EditAction::Cut));
+    assert_eq!(Some("i"), app.registers.get("b"));
+    assert_eq!("dk\nsome\nshit\n", app.buffer.to_string());
+}
+#[test] fn paste_with_register_prefix_uses_register(){
+    let mut app = test_application("idk\nsome\nshit\n");
+    app.registers.set("c", "other").unwrap();
+    app.registers.set(YANK_REGISTER, "yanked").unwrap();
+    select_register(&mut app, 'c');
+    app.update(Action::EditAction(EditAction::Paste));
+    assert_eq!("otheridk\nsome\nshit\n", app.buffer.to_string());
+    // register selection only applies to the next paste
+    app.update(Action::EditAction(EditAction::Paste));
+    assert_eq!("otheryankedidk\nsome\nshit\n", app.buffer.to_string());
+}
+#[test] fn selecting_nonexistent_register_errors(){
+    let mut app = test_application("idk\n");
+    select_register(&mut app, '1');
+    assert_eq!(Mode::Error, app.mode());
+    assert_eq!(None, app.selected_register);
+}
+#[test] fn search_and_command_registers_updated_by_search_command(){
+    let mut app = test_application("idk\nsome\nidk\n");
+    app.update(Action::EditorAction(EditorAction::ModePush(Mode::Command, None)));
+    for c in "search idk".chars(){app.update(Action::UtilAction(crate::action::UtilAction::InsertChar(c)));}
+    app.update(Action::UtilAction(crate::action::UtilAction::Accept));
+    assert_eq!(Some("idk"), app.registers.get(SEARCH_REGISTER));
+    assert_eq!(Some("search idk"), app.registers.get(COMMAND_REGISTER));
+}
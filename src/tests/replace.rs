@@ -0,0 +1,83 @@
+use crate::{
+    action::{Action, EditAction, UtilAction},
+    mode::Mode,
+};
+use crate::tests::common::{test_application, execute, type_in_mode};
+
+
+#[test] fn replaces_within_each_selection_as_one_change(){
+    let mut app = test_application("idk_1 some idk_2 shit idk_3\n");
+    execute(&mut app, "search 'idk_[12]'");
+    let undo_count = app.undo_stack.len();
+    // a group number followed by a name char must be braced. $2_ would name the group "2_"
+    execute(&mut app, "replace '(\\w+)_(\\d)' ${2}_$1");
+    assert_eq!(Mode::Insert, app.mode());
+    assert_eq!("1_idk some 2_idk shit idk_3\n", app.buffer.to_string());
+    assert_eq!(undo_count + 1, app.undo_stack.len());
+    app.update(Action::EditAction(EditAction::Undo));
+    assert_eq!("idk_1 some idk_2 shit idk_3\n", app.buffer.to_string());
+}
+#[test] fn replaces_across_buffer_without_extended_selections(){
+    let mut app = test_application("idk=1\nsome=2\nshit=3\n");
+    execute(&mut app, "replace '(?<key>\\w+)=(?<value>\\d)' '${value}: ${key}'");
+    assert_eq!("1: idk\n2: some\n3: shit\n", app.buffer.to_string());
+    app.update(Action::EditAction(EditAction::Undo));
+    assert_eq!("idk=1\nsome=2\nshit=3\n", app.buffer.to_string());
+}
+#[test] fn missing_template_deletes_matches(){
+    let mut app = test_application("idk some shit\n");
+    execute(&mut app, "replace ' some'");
+    assert_eq!("idk shit\n", app.buffer.to_string());
+}
+#[test] fn no_matches_errors(){
+    let mut app = test_application("idk some shit\n");
+    execute(&mut app, "replace nothing something");
+    assert_eq!(Mode::Error, app.mode());
+    assert_eq!(Some(String::from("no matching regex")), app.mode_message());
+    assert_eq!("idk some shit\n", app.buffer.to_string());
+}
+#[test] fn replacement_resulting_in_same_text_changes_nothing(){
+    let mut app = test_application("idk some shit\n");
+    let undo_count = app.undo_stack.len();
+    execute(&mut app, "replace some some");
+    assert_eq!(Mode::Warning, app.mode());
+    assert_eq!(undo_count, app.undo_stack.len());
+}
+
+#[test] fn replace_mode_previews_replacement_as_it_is_typed(){
+    let mut app = test_application("idk some idk\n");
+    type_in_mode(&mut app, Mode::Replace, "idk s");
+    assert_eq!(Mode::Replace, app.mode());
+    assert_eq!("s some s\n", app.buffer.to_string());
+    for c in "hit".chars(){app.update(Action::UtilAction(UtilAction::InsertChar(c)));}
+    assert_eq!("shit some shit\n", app.buffer.to_string());
+    // only the final preview is kept, as a single change
+    app.update(Action::UtilAction(UtilAction::Accept));
+    assert_eq!(Mode::Insert, app.mode());
+    assert_eq!(1, app.undo_stack.len());
+    app.update(Action::EditAction(EditAction::Undo));
+    assert_eq!("idk some idk\n", app.buffer.to_string());
+}
+#[test] fn replace_mode_invalid_regex_shows_original_text(){
+    let mut app = test_application("idk some idk\n");
+    type_in_mode(&mut app, Mode::Replace, "idk(");
+    assert_eq!("idk some idk\n", app.buffer.to_string());
+    assert!(!app.ui.util_bar.utility_widget.text_box.text_is_valid);
+    app.update(Action::UtilAction(UtilAction::Accept));
+    assert_eq!(Mode::Error, app.mode());
+}
+#[test] fn exiting_replace_mode_restores_buffer_selections_and_redo_history(){
+    let mut app = test_application("idk some idk\n");
+    execute(&mut app, "insert_newline");
+    app.update(Action::EditAction(EditAction::Undo));
+    let selections = app.selections.clone();
+    assert_eq!(1, app.redo_stack.len());
+    type_in_mode(&mut app, Mode::Replace, "idk shit");
+    assert_eq!("shit some shit\n", app.buffer.to_string());
+    app.update(Action::UtilAction(UtilAction::Exit));
+    assert_eq!(Mode::Insert, app.mode());
+    assert_eq!("idk some idk\n", app.buffer.to_string());
+    assert_eq!(selections, app.selections);
+    assert_eq!(1, app.redo_stack.len());
+    assert!(app.undo_stack.is_empty());
+}
@@ -0,0 +1,143 @@
+use crate::{
+    action::{Action, EditAction},
+    mode::Mode,
+    range::Range,
+    sam::{self, Outcome},
+};
+use crate::tests::common::{test_application, execute, wait_for_shell_command, selected_texts};
+
+/// Runs `command` over `text`, with dot set to each of `dots`. The `|` command uppercases its input.
+fn run(command: &str, text: &str, dots: &[Range]) -> Result<Outcome, String>{
+    sam::execute(command, text, dots, &mut |_, input| Ok(input.to_uppercase()))
+}
+/// Text resulting from running `command` over `text`, with dot at the start of the text.
+fn edited(command: &str, text: &str) -> String{
+    let outcome = run(command, text, &[Range::new(0, 0)]).unwrap();
+    match outcome.replacement(text){
+        None => text.to_string(),
+        Some((range, replacement)) => {
+            let chars: Vec<char> = text.chars().collect();
+            format!("{}{}{}", chars[..range.start].iter().collect::<String>(), replacement, chars[range.end..].iter().collect::<String>())
+        }
+    }
+}
+fn selections(command: &str, text: &str, dots: &[Range]) -> Vec<Range>{
+    run(command, text, dots).unwrap().selections
+}
+
+#[test] fn loop_changes_each_match(){
+    let outcome = run(",x/idk/c/shit/", "idk some idk\n", &[Range::new(0, 0)]).unwrap();
+    assert_eq!(vec![Range::new(0, 4), Range::new(10, 14)], outcome.selections);
+    assert_eq!("shit some shit\n", edited(",x/idk/c/shit/", "idk some idk\n"));
+}
+#[test] fn loops_without_command_select(){
+    assert_eq!(vec![Range::new(0, 3), Range::new(9, 12)], selections(",x/idk/", "idk some idk\n", &[Range::new(0, 0)]));
+    assert_eq!(vec![Range::new(0, 1), Range::new(2, 3), Range::new(4, 5)], selections(",y/ /", "a b c", &[Range::new(0, 0)]));
+}
+#[test] fn guards_filter_dot(){
+    assert_eq!("idk\nshit\n", edited(",x/.*\\n/ g/TODO/ d", "idk\nTODO some\nshit\n"));
+    assert_eq!("TODO some\n", edited(",x/.*\\n/ v/TODO/ d", "idk\nTODO some\nshit\n"));
+}
+#[test] fn substitute_expands_matches_and_groups(){
+    assert_eq!("example at john", edited(",s/(\\w+)@(\\w+)/\\2 at \\1/", "john@example"));
+    assert_eq!("<idk> <idk>", edited(",s/idk/<&>/g", "idk idk"));
+    assert_eq!("f0o boo", edited(",s/o/0/", "foo boo"));
+    assert_eq!("f00 b00", edited(",s/o/0/g", "foo boo"));
+}
+#[test] fn append_and_insert_around_dot(){
+    assert_eq!("idk! some idk!", edited(",x/idk/ a/!/", "idk some idk"));
+    assert_eq!("!idk some !idk", edited(",x/idk/ i/!/", "idk some idk"));
+    assert_eq!("idk\nsome\n", edited("$a/some\\n/", "idk\n"));
+}
+#[test] fn line_and_char_addresses(){
+    let text = "one\ntwo\nthree\n";
+    assert_eq!(vec![Range::new(4, 8)], selections("2", text, &[Range::new(0, 0)]));
+    assert_eq!(vec![Range::new(8, 14)], selections("$-1", text, &[Range::new(0, 0)]));
+    assert_eq!(vec![Range::new(4, 8)], selections(".+1", text, &[Range::new(0, 4)]));
+    assert_eq!(vec![Range::new(4, 7)], selections("#4,#7", text, &[Range::new(0, 0)]));
+    assert_eq!("one\n", edited("2,3d", text));
+    assert_eq!(Err(String::from("address out of range")), run("9", text, &[Range::new(0, 0)]));
+}
+#[test] fn regex_addresses_search_from_dot_and_wrap(){
+    let text = "one\ntwo\nthree\n";
+    assert_eq!(vec![Range::new(4, 5)], selections("/t/", text, &[Range::new(0, 0)]));
+    assert_eq!(vec![Range::new(0, 3)], selections("/one/", text, &[Range::new(4, 8)]));
+    assert_eq!(vec![Range::new(8, 9)], selections("?t?", text, &[Range::new(14, 14)]));
+}
+#[test] fn pipe_replaces_dot_with_output(){
+    assert_eq!("IDK some IDK\n", edited(",x/idk/ |tr a-z A-Z", "idk some idk\n"));
+}
+#[test] fn invalid_commands_error(){
+    assert!(run("q", "idk", &[Range::new(0, 0)]).is_err());
+    assert!(run("x", "idk", &[Range::new(0, 0)]).is_err());
+    assert!(run(",x/(/", "idk", &[Range::new(0, 0)]).is_err());
+    // edits made from different selections must not overlap
+    assert_eq!(Err(String::from("changes overlap")), run("d", "idk", &[Range::new(0, 2), Range::new(1, 3)]));
+}
+
+
+#[test] fn edit_command_applies_changes_as_one_undoable_change(){
+    let mut app = test_application("idk some idk\n");
+    let undo_count = app.undo_stack.len();
+    execute(&mut app, "Edit ',x/idk/c/shit/'");
+    assert_eq!("shit some shit\n", app.buffer.to_string());
+    assert_eq!(2, app.selections.count());
+    assert_eq!("shit", app.selections.primary.to_string(&app.buffer));
+    assert_eq!(undo_count + 1, app.undo_stack.len());
+    app.update(Action::EditAction(EditAction::Undo));
+    assert_eq!("idk some idk\n", app.buffer.to_string());
+}
+#[test] fn positions_map_past_edits(){
+    // "idk idk\n" becomes "s s\n". positions within a changed word map to the start of its replacement
+    let outcome = run(",x/idk/ c/s/", "idk idk\n", &[Range::new(0, 0)]).unwrap();
+    assert_eq!(vec![0, 0, 0, 1, 2, 2, 2, 3], (0..8).map(|position| outcome.map_position(position)).collect::<Vec<usize>>());
+}
+#[test] fn edit_command_runs_over_each_selection(){
+    let mut app = test_application("idk\nsome\nidk\n");
+    execute(&mut app, "search idk");
+    execute(&mut app, "Edit 'a/!/'");
+    assert_eq!("idk!\nsome\nidk!\n", app.buffer.to_string());
+}
+#[test] fn edit_command_selects_without_changing(){
+    let mut app = test_application("one\ntwo\nthree\n");
+    let undo_count = app.undo_stack.len();
+    execute(&mut app, "Edit ',x/t[a-z]+/'");
+    assert_eq!(vec!["two", "three"], selected_texts(&app));
+    assert_eq!(undo_count, app.undo_stack.len());
+    execute(&mut app, "Edit ',x/idk/'");
+    assert_eq!(Mode::Error, app.mode());
+}
+#[test] fn edit_command_pipes_through_shell(){
+    let mut app = test_application("idk some idk\n");
+    execute(&mut app, "Edit ',x/idk/ |tr a-z A-Z'");
+    assert_eq!("IDK some IDK\n", app.buffer.to_string());
+}
+#[test] fn edit_command_pipe_runs_on_worker_thread(){
+    let (event_tx, event_rx) = std::sync::mpsc::channel();
+    let mut app = test_application("idk some idk\n");
+    app.shell.set_event_sender(event_tx);
+    execute(&mut app, "Edit ',x/idk/ |tr a-z A-Z'");
+    assert_eq!("idk some idk\n", app.buffer.to_string());
+    assert_eq!("running: tr a-z A-Z", app.ui.status_bar.shell_command_widget.text);
+    wait_for_shell_command(&mut app, &event_rx);
+    assert_eq!("IDK some IDK\n", app.buffer.to_string());
+    assert_eq!(vec!["IDK", "IDK"], selected_texts(&app));
+    app.update(Action::EditAction(EditAction::Undo));
+    assert_eq!("idk some idk\n", app.buffer.to_string());
+}
+#[test] fn edit_command_keeps_primary_nearest_old_primary(){
+    let mut app = test_application("idk\nsome\nidk\n");
+    execute(&mut app, "search idk");
+    execute(&mut app, "increment_primary_selection");
+    execute(&mut app, "Edit 'a/!/'");
+    assert_eq!("idk!\nsome\nidk!\n", app.buffer.to_string());
+    assert_eq!(1, app.selections.primary_selection_index());
+}
+#[test] fn edit_command_inserts_at_buffer_end(){
+    let mut app = test_application("idk\n");
+    execute(&mut app, "Edit '$a/shit/'");
+    assert_eq!("idk\nshit", app.buffer.to_string());
+    assert_eq!("shit", app.selections.primary.to_string(&app.buffer));
+    app.update(Action::EditAction(EditAction::Undo));
+    assert_eq!("idk\n", app.buffer.to_string());
+}
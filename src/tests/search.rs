@@ -0,0 +1,68 @@
+use crate::{
+    action::{Action, EditorAction, UtilAction},
+    mode::Mode,
+    registers::SEARCH_REGISTER,
+    search::{SearchFlag, SearchFlags},
+};
+use crate::tests::common::{test_application, execute, selected_texts};
+
+
+#[test] fn flags_build_regex(){
+    let flags = SearchFlags{literal: true, ignore_case: false, word: true};
+    assert_eq!(r"\b(?:a\.b\()\b", flags.regex("a.b(", false));
+    assert_eq!("(?i)idk", SearchFlags::default().regex("idk", true));
+    // smart case is off for patterns with uppercase chars
+    assert_eq!("Idk", SearchFlags::default().regex("Idk", true));
+    assert_eq!("", SearchFlags{literal: false, ignore_case: true, word: true}.regex("", false));
+}
+
+#[test] fn literal_flag_matches_regex_syntax_as_text(){
+    let mut app = test_application("idk(some) idk.shit idkxshit\n");
+    execute(&mut app, "search --literal idk.shit");
+    assert_eq!(vec!["idk.shit"], selected_texts(&app));
+    execute(&mut app, "search --literal 'idk(some)'");
+    assert_eq!(vec!["idk(some)"], selected_texts(&app));
+}
+#[test] fn ignore_case_and_word_flags(){
+    let mut app = test_application("Idk idks IDK\n");
+    execute(&mut app, "search --ignore-case --word idk");
+    assert_eq!(vec!["Idk", "IDK"], selected_texts(&app));
+    // the register holds the regex with flags applied, for next/previous match
+    assert_eq!(Some("(?i)\\b(?:idk)\\b"), app.registers.get(SEARCH_REGISTER));
+}
+#[test] fn double_dash_ends_flags(){
+    let mut app = test_application("idk --word\n");
+    execute(&mut app, "search -- --word");
+    assert_eq!(vec!["--word"], selected_texts(&app));
+    execute(&mut app, "search --idk idk");
+    assert_eq!(Mode::Error, app.mode());
+}
+#[test] fn smart_case_ignores_case_unless_pattern_has_uppercase(){
+    let mut app = test_application("idk Idk\n");
+    execute(&mut app, "set smart_case true");
+    execute(&mut app, "search idk");
+    assert_eq!(vec!["idk", "Idk"], selected_texts(&app));
+    execute(&mut app, "search Idk");
+    assert_eq!(vec!["Idk"], selected_texts(&app));
+}
+#[test] fn find_mode_flags_toggle_and_show_in_prompt(){
+    let mut app = test_application("idk.some idkxsome\n");
+    execute(&mut app, "select_all");
+    app.update(Action::EditorAction(EditorAction::ModePush(Mode::Find, None)));
+    for c in "idk.".chars(){app.update(Action::UtilAction(UtilAction::InsertChar(c)));}
+    assert_eq!(2, app.selections.count());
+    app.update(Action::UtilAction(UtilAction::ToggleSearchFlag(SearchFlag::Literal)));
+    assert_eq!(vec!["idk."], selected_texts(&app));
+    assert_eq!(" Find(literal): ".len() as u16, app.ui.util_bar.prompt.rect.width);
+    app.update(Action::UtilAction(UtilAction::Accept));
+    assert_eq!(Mode::Insert, app.mode());
+    assert_eq!(Some("idk\\."), app.registers.get(SEARCH_REGISTER));
+}
+#[test] fn failed_search_keeps_search_register(){
+    let mut app = test_application("idk some shit\n");
+    execute(&mut app, "search some");
+    execute(&mut app, "search nothing");
+    assert_eq!(Mode::Error, app.mode());
+    execute(&mut app, "search idk(");
+    assert_eq!(Some("some"), app.registers.get(SEARCH_REGISTER));
+}
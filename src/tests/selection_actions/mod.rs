@@ -45,6 +45,7 @@ mod decrement_primary_selection;
 mod surround;
 mod flip_direction;
 mod surrounding_pair;
+mod select_match;
 
 
 
@@ -0,0 +1,65 @@
+use crate::{
+    action::{Action, EditorAction, SelectionAction, UtilAction},
+    application::Application,
+    mode::Mode,
+    registers::SEARCH_REGISTER,
+};
+use crate::tests::common::{test_application, selected_texts};
+
+/// Test application with `search_register` as the last searched regex.
+fn test_application_searching(buffer_text: &str, search_register: &str) -> Application{
+    let mut app = test_application(buffer_text);
+    app.registers.set(SEARCH_REGISTER, search_register).unwrap();
+    app
+}
+
+#[test] fn select_next_match_moves_primary_past_cursor(){
+    let mut app = test_application_searching("idk some idk shit idk\n", "idk");
+    // a match under the cursor is skipped
+    app.update(Action::SelectionAction(SelectionAction::SelectNextMatch, 1));
+    assert_eq!(9, app.selections.primary.range.start);
+    assert_eq!(vec!["idk"], selected_texts(&app));
+    app.update(Action::SelectionAction(SelectionAction::SelectNextMatch, 1));
+    assert_eq!(18, app.selections.primary.range.start);
+}
+#[test] fn select_next_match_wraps_at_buffer_end(){
+    let mut app = test_application_searching("idk some idk shit idk\n", "idk");
+    app.update(Action::SelectionAction(SelectionAction::SelectNextMatch, 3));
+    assert_eq!(0, app.selections.primary.range.start);
+    assert_eq!(vec!["idk"], selected_texts(&app));
+}
+#[test] fn select_previous_match_wraps_at_buffer_start(){
+    let mut app = test_application_searching("idk some idk shit idk\n", "idk");
+    app.update(Action::SelectionAction(SelectionAction::SelectPreviousMatch, 1));
+    assert_eq!(18, app.selections.primary.range.start);
+    app.update(Action::SelectionAction(SelectionAction::SelectPreviousMatch, 1));
+    assert_eq!(9, app.selections.primary.range.start);
+}
+#[test] fn add_next_match_keeps_existing_selections(){
+    let mut app = test_application_searching("idk some idk shit idk\n", "idk");
+    app.update(Action::SelectionAction(SelectionAction::SelectNextMatch, 1));
+    app.update(Action::SelectionAction(SelectionAction::AddNextMatch, 1));
+    assert_eq!(2, app.selections.count());
+    assert_eq!(18, app.selections.primary.range.start);
+    app.update(Action::SelectionAction(SelectionAction::AddNextMatch, 1));
+    assert_eq!(3, app.selections.count());
+    assert_eq!(0, app.selections.primary.range.start);
+    // every match is already selected
+    app.update(Action::SelectionAction(SelectionAction::AddNextMatch, 1));
+    assert_eq!(3, app.selections.count());
+    assert_eq!(vec!["idk", "idk", "idk"], selected_texts(&app));
+}
+#[test] fn select_next_match_reuses_last_search(){
+    let mut app = test_application_searching("idk some shit some\n", "");
+    app.update(Action::SelectionAction(SelectionAction::SelectNextMatch, 1));
+    assert_eq!(Mode::Error, app.mode());
+    app.update(Action::EditorAction(EditorAction::ModePop));
+    app.update(Action::EditorAction(EditorAction::ModePush(Mode::Command, None)));
+    for c in "search some; clear_non_primary_selections".chars(){app.update(Action::UtilAction(UtilAction::InsertChar(c)));}
+    app.update(Action::UtilAction(UtilAction::Accept));
+    assert_eq!(vec!["some"], selected_texts(&app));
+    assert_eq!(4, app.selections.primary.range.start);
+    app.update(Action::SelectionAction(SelectionAction::SelectNextMatch, 1));
+    assert_eq!(14, app.selections.primary.range.start);
+    assert_eq!(vec!["some"], selected_texts(&app));
+}
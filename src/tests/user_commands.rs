@@ -0,0 +1,72 @@
+use crate::{
+    application::option_value,
+    mode::Mode,
+    registers::COMMAND_REGISTER,
+    user_commands::{UserCommand, substitute_parameters},
+};
+use crate::tests::common::{test_application, execute};
+
+fn user_command(body: &str) -> UserCommand{
+    UserCommand{body: String::from(body), doc: None}
+}
+
+#[test] fn positional_parameters_are_substituted(){
+    let args = [String::from("tab_width"), String::from("8")];
+    assert_eq!(Ok(String::from("set tab_width 8; set $HOME tab_width")), substitute_parameters("set $1 $2; set $HOME $1", &args, str::to_string));
+    assert_eq!(Ok(String::from("echo [8]")), substitute_parameters("echo $2", &args, |arg| format!("[{}]", arg)));
+    assert!(substitute_parameters("set $1 $2", &args[..1], str::to_string).is_err());
+    assert!(user_command("set $1").body_for(2).is_err());
+}
+#[test] fn body_without_parameters_appends_arguments(){
+    assert_eq!(Ok(String::from("search $1 $2")), user_command("search").body_for(2));
+    assert_eq!(Ok(String::from("write")), user_command("write").body_for(0));
+}
+#[test] fn added_command_executes_with_arguments(){
+    let mut app = test_application("idk\n");
+    execute(&mut app, "add_command tabs %{set tab_width $1; set use_hard_tab $2} --doc 'sets tab width and hard tabs'");
+    assert_eq!(Some(&UserCommand{body: String::from("set tab_width $1; set use_hard_tab $2"), doc: Some(String::from("sets tab width and hard tabs"))}), app.user_commands.get("tabs"));
+    execute(&mut app, "tabs 2 true");
+    assert_eq!(Ok(String::from("2")), option_value(&app, "tab_width"));
+    assert_eq!(Ok(String::from("true")), option_value(&app, "use_hard_tab"));
+    // the command register holds what was entered, not the expanded body
+    assert_eq!(Some("tabs 2 true"), app.registers.get(COMMAND_REGISTER));
+}
+#[test] fn arguments_are_bound_as_single_words(){
+    let mut app = test_application("idk\n");
+    execute(&mut app, "add_command tabs %{set tab_width $1}");
+    execute(&mut app, "tabs '8; set use_hard_tab true'");
+    assert_eq!(Mode::Error, app.mode());
+    assert_eq!(Ok(String::from("4")), option_value(&app, "tab_width"));
+    assert_eq!(Ok(String::from("false")), option_value(&app, "use_hard_tab"));
+    execute(&mut app, "add_command note %{diagnostic --info $1}");
+    execute(&mut app, "note 'some shit; %sh{exit 1}'");
+    assert_eq!(Some(String::from("some shit; %sh{exit 1}")), app.mode_message());
+}
+#[test] fn arguments_are_shell_quoted_in_shell_commands(){
+    let mut app = test_application("idk\n");
+    execute(&mut app, "add_command check %{! test $1 = 'some shit; it'\\''s'}");
+    execute(&mut app, "check \"some shit; it's\"");
+    assert_eq!(Mode::Notify, app.mode());
+    execute(&mut app, "add_command alias_check '! test'");
+    execute(&mut app, "alias_check 'some shit' = 'some shit'");
+    assert_eq!(Mode::Notify, app.mode());
+}
+#[test] fn added_command_is_listed_in_command_mode_popup(){
+    let mut app = test_application("idk\n");
+    execute(&mut app, "add_command tabs 'set tab_width $1' --doc 'sets tab width'");
+    assert!(app.ui.popups.command.text.contains("tabs"));
+    assert!(app.ui.popups.command.text.contains("sets tab width"));
+}
+#[test] fn adding_existing_command_errors(){
+    let mut app = test_application("idk\n");
+    execute(&mut app, "add_command w write");
+    execute(&mut app, "add_command w quit");
+    assert_eq!(Mode::Error, app.mode());
+    assert_eq!(Some(&user_command("write")), app.user_commands.get("w"));
+}
+#[test] fn recursive_command_errors(){
+    let mut app = test_application("idk\n");
+    execute(&mut app, "add_command again again");
+    execute(&mut app, "again");
+    assert_eq!(Mode::Error, app.mode());
+}
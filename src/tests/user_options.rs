@@ -0,0 +1,49 @@
+use crate::{
+    application::option_value,
+    mode::Mode,
+    user_options::{OptionType, OptionValue},
+};
+use crate::tests::common::{test_application, execute};
+
+
+#[test] fn values_parse_as_their_type(){
+    assert_eq!(Ok(OptionValue::Bool(true)), OptionValue::parse(OptionType::Bool, "true"));
+    assert_eq!(Ok(OptionValue::Int(-3)), OptionValue::parse(OptionType::Int, "-3"));
+    assert_eq!(Ok(OptionValue::List(vec![String::from("some"), String::from("shit")])), OptionValue::parse(OptionType::List, "some  shit"));
+    assert!(OptionValue::parse(OptionType::Bool, "idk").is_err());
+    assert!(OptionValue::parse(OptionType::Int, "idk").is_err());
+    assert!(OptionValue::parse(OptionType::Regex, "(idk").is_err());
+}
+#[test] fn added_option_is_set_and_expanded(){
+    let mut app = test_application("idk\n");
+    execute(&mut app, "add_option lint_on_save bool");
+    assert_eq!(Ok(String::from("false")), option_value(&app, "lint_on_save"));
+    execute(&mut app, "set lint_on_save true");
+    assert_eq!(Ok(String::from("true")), option_value(&app, "lint_on_save"));
+    execute(&mut app, "add_option lint_command string %opt{lint_on_save}");
+    assert_eq!(Ok(String::from("true")), option_value(&app, "lint_command"));
+}
+#[test] fn setting_invalid_value_errors(){
+    let mut app = test_application("idk\n");
+    execute(&mut app, "add_option max_width int 80");
+    execute(&mut app, "set max_width idk");
+    assert_eq!(Mode::Error, app.mode());
+    assert_eq!(Ok(String::from("80")), option_value(&app, "max_width"));
+}
+#[test] fn adding_existing_option_errors(){
+    let mut app = test_application("idk\n");
+    execute(&mut app, "add_option tab_width int");
+    assert_eq!(Mode::Error, app.mode());
+    execute(&mut app, "add_option formatters list rustfmt");
+    execute(&mut app, "add_option formatters list");
+    assert_eq!(Mode::Error, app.mode());
+    assert_eq!(Ok(String::from("rustfmt")), option_value(&app, "formatters"));
+}
+#[test] fn options_are_exported_to_shell_commands(){
+    let mut app = test_application("idk\n");
+    execute(&mut app, "add_option formatters list rustfmt prettier");
+    execute(&mut app, "add_option selected_formatter string %sh{echo $EDIT_OPT_FORMATTERS | cut -d ' ' -f 2}");
+    assert_eq!(Ok(String::from("prettier")), option_value(&app, "selected_formatter"));
+    execute(&mut app, "set view_scroll_amount %sh{echo $EDIT_OPT_TAB_WIDTH}");
+    assert_eq!(Ok(String::from("4")), option_value(&app, "view_scroll_amount"));
+}
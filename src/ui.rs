@@ -11,7 +11,7 @@ mod document_viewport;
 mod status_bar;
 pub mod util_bar;
 mod interactive_text_box;
-mod popups;
+pub mod popups;
 
 
 
@@ -29,7 +29,7 @@ impl UserInterface{
             document_viewport: DocumentViewport::default(),
             status_bar: StatusBar::default(),
             util_bar: UtilBar::default(),
-            popups: Popups::new(keybinds),
+            popups: Popups::new(keybinds, &crate::user_commands::UserCommands::default()),
         }
     }
     //TODO: this can prob be removed if terminal.size() called in update_layouts...
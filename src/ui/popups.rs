@@ -116,6 +116,9 @@ pub struct Popups{
     pub command: PopupMenu,
     pub find: PopupMenu,
     pub split: PopupMenu,
+    pub keep: PopupMenu,
+    pub remove: PopupMenu,
+    pub replace: PopupMenu,
     pub error: PopupMenu,
     pub modified_error: PopupMenu,  //TODO?: maybe remove this, and use normal error mode display instead?...
     pub warning: PopupMenu,
@@ -124,6 +127,7 @@ pub struct Popups{
     pub view: PopupMenu,
     pub object: PopupMenu,
     pub add_surround: PopupMenu,
+    pub register: PopupMenu,
 }
 impl Popups{
     //TODO: popup text should probably be set in ModePush, so that keybinds added at run time can be included...
@@ -131,7 +135,7 @@ impl Popups{
     //or maybe reload whenev user adds a new keybind/command, because reloading every mode_push may be unnecessary
     //TODO: menus are not ordered when using HashMap. try BTreeMap(although, i think this may be more related to sorting), 
     //or the indexmap crate to retain insert order
-    pub fn new(keybinds: &/*std::collections::HashMap*/indexmap::IndexMap<(crate::mode::Mode, KeyEvent), crate::action::Action>) -> Self{
+    pub fn new(keybinds: &/*std::collections::HashMap*/indexmap::IndexMap<(crate::mode::Mode, KeyEvent), crate::action::Action>, user_commands: &crate::user_commands::UserCommands) -> Self{
         //the hashmap seems to have no set order. every time the editor runs, the order of menu items changes.
         //is there some way to force it to stay the same?...
         use crate::mode::Mode;
@@ -139,6 +143,9 @@ impl Popups{
         let mut command_mode_menu_items = Vec::new();
         let mut find_mode_menu_items = Vec::new();
         let mut split_mode_menu_items = Vec::new();
+        let mut keep_mode_menu_items = Vec::new();
+        let mut remove_mode_menu_items = Vec::new();
+        let mut replace_mode_menu_items = Vec::new();
         let mut error_mode_menu_items = Vec::new();
         let mut modified_error_mode_menu_items = Vec::new();
         let mut warning_mode_menu_items = Vec::new();
@@ -147,6 +154,7 @@ impl Popups{
         let mut view_mode_menu_items = Vec::new();
         let mut object_mode_menu_items = Vec::new();
         let mut add_surround_mode_menu_items = Vec::new();
+        let mut register_mode_menu_items = Vec::new();
         for ((mode, key_event), action) in keybinds{
             fn menu_item(key_event: &KeyEvent, action: &crate::action::Action) -> MenuItem{
                 MenuItem{
@@ -160,6 +168,9 @@ impl Popups{
                 Mode::Command => command_mode_menu_items.push(menu_item(key_event, action)),
                 Mode::Find => find_mode_menu_items.push(menu_item(key_event, action)),
                 Mode::Split => split_mode_menu_items.push(menu_item(key_event, action)),
+                Mode::Keep => keep_mode_menu_items.push(menu_item(key_event, action)),
+                Mode::Remove => remove_mode_menu_items.push(menu_item(key_event, action)),
+                Mode::Replace => replace_mode_menu_items.push(menu_item(key_event, action)),
                 //Mode::Error if !matches!(action, crate::action::Action::EditorAction(crate::action::EditorAction::Quit)) => error_mode_menu_items.push(menu_item(key_event, action)),
                 //Mode::Error => modified_error_mode_menu_items.push(menu_item(key_event, action)),
                 Mode::Error => {
@@ -176,15 +187,27 @@ impl Popups{
                 Mode::View => view_mode_menu_items.push(menu_item(key_event, action)),
                 Mode::Object => object_mode_menu_items.push(menu_item(key_event, action)),
                 Mode::AddSurround => add_surround_mode_menu_items.push(menu_item(key_event, action)),
+                Mode::Register => register_mode_menu_items.push(menu_item(key_event, action)),
                 Mode::Insert => {}
             }
         }
+        //user defined commands are listed, with their doc strings, under the command mode keybinds
+        for (name, user_command) in user_commands.iter(){
+            command_mode_menu_items.push(MenuItem{
+                key: name.clone(),
+                command: user_command.doc.clone().unwrap_or_else(|| user_command.body.clone()),
+                source: String::from("(user)")
+            });
+        }
 
         Self{
             goto: PopupMenu::new_from_mode_menu(&goto_mode_menu_items, "Goto"),
             command: PopupMenu::new_from_mode_menu(&command_mode_menu_items, "Command"),
             find: PopupMenu::new_from_mode_menu(&find_mode_menu_items, "Find"),
             split: PopupMenu::new_from_mode_menu(&split_mode_menu_items, "Split"),
+            keep: PopupMenu::new_from_mode_menu(&keep_mode_menu_items, "Keep"),
+            remove: PopupMenu::new_from_mode_menu(&remove_mode_menu_items, "Remove"),
+            replace: PopupMenu::new_from_mode_menu(&replace_mode_menu_items, "Replace"),
             error: PopupMenu::new_from_mode_menu(&error_mode_menu_items, "Error"),
             modified_error: PopupMenu::new_from_mode_menu(&modified_error_mode_menu_items, "Error(Modified)"),
             warning: PopupMenu::new_from_mode_menu(&warning_mode_menu_items, "Warning"),
@@ -192,7 +215,8 @@ impl Popups{
             info: PopupMenu::new_from_mode_menu(&info_mode_menu_items, "Info"),
             view: PopupMenu::new_from_mode_menu(&view_mode_menu_items, "View"),
             object: PopupMenu::new_from_mode_menu(&object_mode_menu_items, "Object"),
-            add_surround: PopupMenu::new_from_mode_menu(&add_surround_mode_menu_items, "Surround")
+            add_surround: PopupMenu::new_from_mode_menu(&add_surround_mode_menu_items, "Surround"),
+            register: PopupMenu::new_from_mode_menu(&register_mode_menu_items, "Register")
         }
     }
 }
@@ -19,6 +19,12 @@ use ratatui::layout::Rect;
     pub text: String,
 }
 
+/// Shows the shell command running on a worker thread, if any.
+#[derive(Default)] pub struct ShellCommandWidget{
+    pub rect: Rect,
+    pub text: String,
+}
+
 #[derive(Default)] pub struct CursorPositionWidget{
     pub rect: Rect,
     pub text: String,
@@ -46,6 +52,7 @@ use ratatui::layout::Rect;
     pub modified_widget: ModifiedWidget,
     pub padding_3: Padding,
     pub selections_widget: SelectionsWidget,
+    pub shell_command_widget: ShellCommandWidget,
     pub padding_4: Padding,
     pub cursor_position_widget: CursorPositionWidget,
     pub padding_5: Padding,
@@ -6,6 +6,9 @@ use ratatui::layout::Rect;
 pub const GOTO_PROMPT: &str = " Go to: ";
 pub const FIND_PROMPT: &str = " Find: ";
 pub const SPLIT_PROMPT: &str = " Split: ";
+pub const KEEP_PROMPT: &str = " Keep: ";
+pub const REMOVE_PROMPT: &str = " Remove: ";
+pub const REPLACE_PROMPT: &str = " Replace: ";
 pub const COMMAND_PROMPT: &str = " Command: ";
 
 #[derive(Default)] pub struct UtilityWidget{
@@ -0,0 +1,99 @@
+//! Named commands defined at run time with `add_command`, usually from the start file, so that workflows can be shared as
+//! ordinary commands. A command body is evaluated like any other command string, with positional parameters(`$1`, `$2`, ...)
+//! replaced by the arguments it was called with.
+
+use indexmap::IndexMap;
+
+
+
+/// Limits how deeply user commands may call user commands, so a command calling itself errors instead of overflowing the stack.
+const MAX_DEPTH: usize = 32;
+
+#[derive(Clone, Debug, PartialEq)] pub struct UserCommand{
+    pub body: String,
+    /// Shown next to the command name in the command mode popup.
+    pub doc: Option<String>,
+}
+impl UserCommand{
+    /// Returns the body to evaluate with `argument_count` arguments, which are bound to its positional parameters as it is
+    /// evaluated(see [`substitute_parameters`]). A body without positional parameters behaves as an alias, and has any 
+    /// arguments appended to it.
+    pub fn body_for(&self, argument_count: usize) -> Result<String, String>{
+        let highest_parameter = parameters(&self.body).map(|(_, _, parameter)| parameter).max().unwrap_or(0);
+        if highest_parameter == 0{
+            let mut body = self.body.clone();
+            for parameter in 1..=argument_count{body.push_str(&format!(" ${}", parameter));}
+            return Ok(body);
+        }
+        if argument_count > highest_parameter{
+            return Err(format!("too many arguments: expected {}, got {}", highest_parameter, argument_count));
+        }
+        Ok(self.body.clone())
+    }
+}
+/// Returns `text`, with each positional parameter replaced by `quote` applied to the matching argument.
+/// Arguments are substituted into parsed words, and into the raw text of shell commands, never into text that is parsed
+/// again, so that an argument can't be split into more words, or commands.
+pub fn substitute_parameters(text: &str, arguments: &[String], quote: fn(&str) -> String) -> Result<String, String>{
+    let mut substituted = String::new();
+    let mut copied_to = 0;
+    for (start, end, parameter) in parameters(text){
+        match arguments.get(parameter - 1){
+            None => return Err(format!("missing argument ${}", parameter)),
+            Some(argument) => {
+                substituted.push_str(&text[copied_to..start]);
+                substituted.push_str(&quote(argument));
+                copied_to = end;
+            }
+        }
+    }
+    substituted.push_str(&text[copied_to..]);
+    Ok(substituted)
+}
+/// Byte ranges of the positional parameters(`$1`, `$2`, ...) in `text`, and their numbers.
+fn parameters(text: &str) -> impl Iterator<Item = (usize, usize, usize)> + '_{
+    let mut chars = text.char_indices().peekable();
+    std::iter::from_fn(move ||{
+        while let Some((start, char)) = chars.next(){
+            if char == '$' && chars.peek().is_some_and(|(_, next)| next.is_ascii_digit() && *next != '0'){
+                let mut end = start + 1;
+                while let Some((index, _)) = chars.next_if(|(_, next)| next.is_ascii_digit()){end = index + 1;}
+                //too many digits to fit a usize can't name an argument. it is left to be reported as missing
+                let parameter = text[start + 1..end].parse::<usize>().unwrap_or(usize::MAX);
+                return Some((start, end, parameter));
+            }
+        }
+        None
+    })
+}
+
+#[derive(Default)] pub struct UserCommands{
+    commands: IndexMap<String, UserCommand>,
+    /// Number of user commands currently being evaluated.
+    depth: usize,
+}
+impl UserCommands{
+    /// Defines a new command. Errors if `name` is already defined.
+    pub fn add(&mut self, name: &str, command: UserCommand) -> Result<(), String>{
+        if name.is_empty() || name.contains(char::is_whitespace){return Err(format!("{:?} is not a valid command name", name));}
+        if self.commands.contains_key(name){return Err(format!("command {} already exists", name));}
+        self.commands.insert(name.to_string(), command);
+        Ok(())
+    }
+    pub fn get(&self, name: &str) -> Option<&UserCommand>{
+        self.commands.get(name)
+    }
+    /// Commands in the order they were defined.
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &UserCommand)>{
+        self.commands.iter()
+    }
+    /// Records the start of a user command's evaluation. Must be paired with [`UserCommands::exit`].
+    pub fn enter(&mut self) -> Result<(), String>{
+        if self.depth >= MAX_DEPTH{return Err(String::from("user commands nested too deeply"));}
+        self.depth = self.depth + 1;
+        Ok(())
+    }
+    pub fn exit(&mut self){
+        self.depth = self.depth.saturating_sub(1);
+    }
+}
@@ -0,0 +1,120 @@
+//! Options declared at run time with `add_option`, so that plugins(often shell scripts) have somewhere to keep configuration.
+//! They are set with `set`, like built in options, and read back through `%opt{name}` expansions, or the `$EDIT_OPT_<NAME>`
+//! environment variables given to shell commands.
+
+use indexmap::IndexMap;
+use regex::Regex;
+
+
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum OptionType{
+    Bool,
+    Int,
+    String,
+    Regex,
+    /// Whitespace separated words.
+    List,
+}
+impl std::str::FromStr for OptionType{
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err>{
+        match s{
+            "bool" => Ok(OptionType::Bool),
+            "int" => Ok(OptionType::Int),
+            "string" | "str" => Ok(OptionType::String),
+            "regex" => Ok(OptionType::Regex),
+            "list" => Ok(OptionType::List),
+            _ => Err(format!("{} is not a valid option type. expected bool, int, string, regex or list", s))
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum OptionValue{
+    Bool(bool),
+    Int(i64),
+    String(String),
+    /// Kept as the source text. Validated as a regex when set.
+    Regex(String),
+    List(Vec<String>),
+}
+impl OptionValue{
+    /// Value an option of `option_type` holds until it is first set.
+    pub fn default_for(option_type: OptionType) -> Self{
+        match option_type{
+            OptionType::Bool => OptionValue::Bool(false),
+            OptionType::Int => OptionValue::Int(0),
+            OptionType::String => OptionValue::String(String::new()),
+            OptionType::Regex => OptionValue::Regex(String::new()),
+            OptionType::List => OptionValue::List(Vec::new()),
+        }
+    }
+    /// Parses `value` as an `option_type` value. Errors if `value` is not valid for the type.
+    pub fn parse(option_type: OptionType, value: &str) -> Result<Self, String>{
+        match option_type{
+            OptionType::Bool => value.parse::<bool>().map(OptionValue::Bool).map_err(|error| format!("{}", error)),
+            OptionType::Int => value.parse::<i64>().map(OptionValue::Int).map_err(|error| format!("{}", error)),
+            OptionType::String => Ok(OptionValue::String(value.to_string())),
+            OptionType::Regex => {
+                match Regex::new(value){
+                    Err(error) => Err(format!("{}", error)),
+                    Ok(_) => Ok(OptionValue::Regex(value.to_string()))
+                }
+            }
+            OptionType::List => Ok(OptionValue::List(value.split_whitespace().map(String::from).collect())),
+        }
+    }
+    pub fn option_type(&self) -> OptionType{
+        match self{
+            OptionValue::Bool(_) => OptionType::Bool,
+            OptionValue::Int(_) => OptionType::Int,
+            OptionValue::String(_) => OptionType::String,
+            OptionValue::Regex(_) => OptionType::Regex,
+            OptionValue::List(_) => OptionType::List,
+        }
+    }
+}
+/// Formatted the way [`OptionValue::parse`] accepts it.
+impl std::fmt::Display for OptionValue{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result{
+        match self{
+            OptionValue::Bool(value) => write!(f, "{}", value),
+            OptionValue::Int(value) => write!(f, "{}", value),
+            OptionValue::String(value) | OptionValue::Regex(value) => write!(f, "{}", value),
+            OptionValue::List(values) => write!(f, "{}", values.join(" ")),
+        }
+    }
+}
+
+#[derive(Default)] pub struct UserOptions{
+    options: IndexMap<String, OptionValue>,
+}
+impl UserOptions{
+    /// Declares a new option holding `value`. Errors if `name` is already declared.
+    pub fn add(&mut self, name: &str, value: OptionValue) -> Result<(), String>{
+        if name.is_empty() || !name.chars().all(|char| char.is_ascii_alphanumeric() || char == '_'){
+            return Err(format!("{:?} is not a valid option name. names may only contain ascii letters, digits and '_'", name));
+        }
+        if self.options.contains_key(name){return Err(format!("option {} already exists", name));}
+        self.options.insert(name.to_string(), value);
+        Ok(())
+    }
+    pub fn get(&self, name: &str) -> Option<&OptionValue>{
+        self.options.get(name)
+    }
+    /// Sets a declared option to `value`, parsed as the option's type. Returns None if `name` is not declared.
+    pub fn set(&mut self, name: &str, value: &str) -> Option<Result<(), String>>{
+        let option = self.options.get_mut(name)?;
+        Some(OptionValue::parse(option.option_type(), value).map(|value| *option = value))
+    }
+    /// Options in the order they were declared.
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &OptionValue)>{
+        self.options.iter()
+    }
+}
+
+/// Name of the environment variable holding option `name`'s value, for shell commands.
+pub fn environment_variable_name(name: &str) -> String{
+    format!("EDIT_OPT_{}", name.to_uppercase())
+}